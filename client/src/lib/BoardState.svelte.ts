@@ -2,19 +2,110 @@ export type Item = {
   body: string;
   vote_count: number;
   sort_order: number;
+  merged: MergedItem[];
+  /** ID of the item's group within its lane */
+  group: string | null;
+  /** In the order they were written */
+  comments: { [k: string]: Comment };
+  /** Participants who reacted with each emoji, apart from votes */
+  reactions: { [emoji: string]: string[] };
+  /** Set for the items of action items lanes */
+  action: ActionDetails | null;
+  /** Unknown for items stored before this was recorded */
+  created_at: string | null;
+  /** When the body last changed */
+  updated_at: string | null;
+  /** Earlier bodies, oldest first */
+  revisions: Revision[];
+  /** Participant who added the item; left out while authors are hidden */
+  author?: string | null;
+  /** Added while the board was anonymous, so its author stays hidden */
+  anonymous: boolean;
 };
 
-export type LaneThemeKey = "went-well" | "to-improve" | "action-items";
+/** A body an item had before an edit replaced it */
+export type Revision = {
+  body: string;
+  replaced_at: string;
+};
+
+export type ActionStatus = "open" | "in_progress" | "done" | "dropped";
+
+export type CardRef = {
+  lane_id: string;
+  item_id: string;
+};
+
+export type ActionDetails = {
+  assignee: string | null;
+  /** YYYY-MM-DD */
+  due: string | null;
+  status: ActionStatus;
+  /** Card the action item came out of */
+  source: CardRef | null;
+  /** Action item of an earlier board this one was carried over from */
+  carried_from: Origin | null;
+};
+
+export type Origin = CardRef & {
+  board_id: string;
+};
+
+export type Comment = {
+  author: string;
+  body: string;
+  created_at: string;
+  edited_at: string | null;
+  /** ID of the comment this one replies to, on the same item */
+  reply_to: string | null;
+  /** Written while the board was anonymous, so its author stays hidden */
+  anonymous: boolean;
+};
+
+/** An item merged into another, kept so the merge can be reversed */
+export type MergedItem = {
+  id: string;
+  item: Item;
+  target_body: string;
+};
+
+export type LaneThemeKey =
+  | "went-well"
+  | "to-improve"
+  | "action-items"
+  | "action-review";
 
 export type Lane = {
   title: string;
   theme: LaneThemeKey;
   items: { [k: string]: Item };
+  /** In the order they are shown */
+  groups: { [k: string]: Group };
+  created_at: string | null;
+  /** When items were last added, removed or edited */
+  updated_at: string | null;
+};
+
+export type Group = {
+  title: string;
+  /** Votes on the group itself; add those of its items for the total */
+  vote_count: number;
+};
+
+export type BoardSettings = {
+  vote_on_groups: boolean;
+  /** Emoji offered as reactions */
+  reactions: string[];
+  /** Team holding the retro */
+  team: string | null;
+  /** Keep item authors hidden from everyone */
+  anonymous: boolean;
 };
 
 export type Board = {
   title: string;
   lanes: { [k: string]: Lane };
+  settings: BoardSettings;
 };
 
 export type ActionAddLane = {
@@ -69,6 +160,161 @@ export type ActionMergeItems = {
   merged_body: string;
 };
 
+export type ActionUnmergeItems = {
+  type: "UnmergeItems";
+  lane_id: string;
+  id: string;
+};
+
+export type ActionAddGroup = {
+  type: "AddGroup";
+  lane_id: string;
+  title: string;
+};
+
+export type ActionRenameGroup = {
+  type: "RenameGroup";
+  lane_id: string;
+  group_id: string;
+  title: string;
+};
+
+export type ActionRemoveGroup = {
+  type: "RemoveGroup";
+  lane_id: string;
+  group_id: string;
+};
+
+export type ActionGroupItem = {
+  type: "GroupItem";
+  lane_id: string;
+  item_id: string;
+  /** `null` takes the item out of its group */
+  group_id: string | null;
+};
+
+export type ActionReorderGroup = {
+  type: "ReorderGroup";
+  lane_id: string;
+  group_id: string;
+  new_position: number;
+};
+
+export type ActionUpvoteGroup = {
+  type: "UpvoteGroup";
+  lane_id: string;
+  group_id: string;
+};
+
+export type ActionUpdateSettings = {
+  type: "UpdateSettings";
+  vote_on_groups?: boolean;
+  reactions?: string[];
+  /** `null` clears the team */
+  team?: string | null;
+  /** Facilitators only */
+  anonymous?: boolean;
+};
+
+/** Toggles the sender's reaction */
+export type ActionReact = {
+  type: "React";
+  lane_id: string;
+  item_id: string;
+  emoji: string;
+};
+
+export type ActionAddComment = {
+  type: "AddComment";
+  lane_id: string;
+  item_id: string;
+  body: string;
+  reply_to?: string | null;
+};
+
+/** Only the comment's author or a facilitator can edit or delete it */
+export type ActionEditComment = {
+  type: "EditComment";
+  lane_id: string;
+  item_id: string;
+  comment_id: string;
+  body: string;
+};
+
+export type ActionDeleteComment = {
+  type: "DeleteComment";
+  lane_id: string;
+  item_id: string;
+  comment_id: string;
+};
+
+export type ActionAddActionItem = {
+  type: "AddActionItem";
+  body: string;
+  assignee?: string | null;
+  due?: string | null;
+  source?: CardRef | null;
+};
+
+/** Fields left out stay as they are; `null` clears assignee or due */
+export type ActionUpdateActionItem = {
+  type: "UpdateActionItem";
+  lane_id: string;
+  item_id: string;
+  assignee?: string | null;
+  due?: string | null;
+  status?: ActionStatus;
+};
+
+export type ActionCompleteActionItem = {
+  type: "CompleteActionItem";
+  lane_id: string;
+  item_id: string;
+};
+
+/** Move a carried over action item into the action items lane, open again */
+export type ActionRecommitActionItem = {
+  type: "RecommitActionItem";
+  lane_id: string;
+  item_id: string;
+  due?: string | null;
+};
+
+/** Facilitators only */
+export type ActionRollBackItem = {
+  type: "RollBackItem";
+  lane_id: string;
+  item_id: string;
+  /** Index into the item's revisions */
+  revision: number;
+};
+
+export type ActionRemoveLane = {
+  type: "RemoveLane";
+  lane_id: string;
+};
+
+export type ActionRestore = {
+  type: "Restore";
+  /** Key of the entry in the board's trash */
+  id: string;
+};
+
+export type ActionEmptyTrash = {
+  type: "EmptyTrash";
+};
+
+export type ActionUndo = {
+  type: "Undo";
+  /** Undo anyone's latest action rather than your own. Facilitators only. */
+  everyone?: boolean;
+};
+
+export type ActionRedo = {
+  type: "Redo";
+  everyone?: boolean;
+};
+
 export type AllActions =
   | ActionAddItem
   | ActionAddLane
@@ -77,10 +323,48 @@ export type AllActions =
   | ActionMoveItem
   | ActionReorderItem
   | ActionEditItem
-  | ActionMergeItems;
+  | ActionMergeItems
+  | ActionUnmergeItems
+  | ActionAddGroup
+  | ActionRenameGroup
+  | ActionRemoveGroup
+  | ActionGroupItem
+  | ActionReorderGroup
+  | ActionUpvoteGroup
+  | ActionUpdateSettings
+  | ActionReact
+  | ActionAddComment
+  | ActionEditComment
+  | ActionDeleteComment
+  | ActionAddActionItem
+  | ActionUpdateActionItem
+  | ActionCompleteActionItem
+  | ActionRecommitActionItem
+  | ActionRollBackItem
+  | ActionRemoveLane
+  | ActionRestore
+  | ActionEmptyTrash
+  | ActionUndo
+  | ActionRedo;
 
 export type SendActionFunc = (action: AllActions) => void;
 
+/** Sent instead of a board snapshot when only an item's comments changed */
+export type CommentsChanged = {
+  type: "CommentsChanged";
+  lane_id: string;
+  item_id: string;
+  comments: { [k: string]: Comment };
+};
+
+/** Sent instead of a board snapshot when only an item's reactions changed */
+export type ReactionsChanged = {
+  type: "ReactionsChanged";
+  lane_id: string;
+  item_id: string;
+  reactions: { [emoji: string]: string[] };
+};
+
 /** Merge source state shared across the board */
 export type MergeSource = {
   laneId: string;
@@ -98,6 +98,14 @@ describe("WebsocketWrapper", () => {
     expect(ws.url).toMatch(/^ws:\/\/.+\/ws$/);
   });
 
+  test("joins the board named in the page URL", () => {
+    window.history.replaceState(null, "", "/?board=sprint-42");
+    renderWrapper();
+    const ws = MockWebSocket.instances.at(-1)!;
+    expect(ws.url).toMatch(/\/ws\?board=sprint-42$/);
+    window.history.replaceState(null, "", "/");
+  });
+
   test("updates to connected when socket opens", async () => {
     renderWrapper();
     const ws = MockWebSocket.instances.at(-1)!;
@@ -158,4 +166,39 @@ describe("WebsocketWrapper", () => {
       );
     });
   });
+
+  test("keeps the board when an action is rejected", async () => {
+    renderWrapper();
+    const ws = MockWebSocket.instances.at(-1)!;
+    ws._open();
+    ws._message({ title: "My Retro", lanes: {} });
+    ws._message({ type: "ActionRejected", reason: "board is full" });
+    await vi.waitFor(() => {
+      expect(screen.getByTestId("board-title")).toHaveTextContent("My Retro");
+    });
+  });
+
+  test("reconnects after the server announces a restart", async () => {
+    vi.useFakeTimers();
+    try {
+      renderWrapper();
+      const ws = MockWebSocket.instances.at(-1)!;
+      ws._open();
+      ws._message({ title: "My Retro", lanes: {} });
+      ws._message({ type: "ServerRestarting", retry_after_ms: 1000 });
+      ws._close();
+      await vi.waitFor(() => {
+        expect(screen.getByTestId("socket-state")).toHaveTextContent(
+          "restarting",
+        );
+      });
+      // The board stays visible while the server restarts
+      expect(screen.getByTestId("board-title")).toHaveTextContent("My Retro");
+
+      vi.advanceTimersByTime(1000);
+      expect(MockWebSocket.instances).toHaveLength(2);
+    } finally {
+      vi.useRealTimers();
+    }
+  });
 });
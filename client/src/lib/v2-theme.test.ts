@@ -21,6 +21,12 @@ describe("v2-theme", () => {
     expect(theme.accent).toContain("blue");
   });
 
+  test("returns action-review theme", () => {
+    const theme = getLaneTheme("action-review");
+    expect(theme.icon).toBe("🔁");
+    expect(theme.accent).toContain("violet");
+  });
+
   test("falls back to went-well for unknown key", () => {
     const theme = getLaneTheme("nonexistent" as LaneThemeKey);
     expect(theme).toEqual(laneThemeMap["went-well"]);
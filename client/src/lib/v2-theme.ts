@@ -55,6 +55,17 @@ export const laneThemeMap: Record<LaneThemeKey, LaneTheme> = {
     laneBorder: "border-blue-400/30 dark:border-blue-500/20",
     accent: "bg-blue-500",
   },
+  "action-review": {
+    headerBg: "bg-violet-500/10",
+    headerText: "text-violet-700 dark:text-violet-400",
+    badge: "preset-filled-secondary-200-800",
+    icon: "🔁",
+    cardBg: "bg-violet-500/5 dark:bg-violet-500/10",
+    cardBorder: "border-violet-400/40 dark:border-violet-500/30",
+    laneBg: "bg-violet-500/[0.03] dark:bg-violet-500/[0.06]",
+    laneBorder: "border-violet-400/30 dark:border-violet-500/20",
+    accent: "bg-violet-500",
+  },
 };
 
 /** Get theme for a lane, falling back to went-well */
@@ -0,0 +1,2279 @@
+use crate::cards::Card;
+use crate::config::Limits;
+use crate::history;
+use crate::retroboard::{self, ActionDetails, ActionStatus, CardRef, Comment, Deleted, RetroBoard};
+use chrono::{NaiveDate, TimeDelta, Utc};
+use indexmap::IndexMap;
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::HashMap;
+
+/// How many emoji a board can offer as reactions.
+const MAX_REACTIONS: usize = 20;
+/// Longest reaction in characters, enough for emoji joined into one.
+const MAX_REACTION_LENGTH: usize = 16;
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum Action {
+    AddLane {
+        title: String,
+    },
+    AddItem {
+        lane_id: String,
+        body: String,
+    },
+    /// Move an item to the trash. Only its author or a facilitator can.
+    RemoveItem {
+        lane_id: String,
+        id: String,
+    },
+    UpvoteItem {
+        lane_id: String,
+        id: String,
+    },
+    MoveItem {
+        from_lane_id: String,
+        to_lane_id: String,
+        item_id: String,
+    },
+    ReorderItem {
+        lane_id: String,
+        item_id: String,
+        new_position: u64,
+    },
+    /// Change an item's body. Only its author or a facilitator can.
+    EditItem {
+        lane_id: String,
+        id: String,
+        body: String,
+    },
+    MergeItems {
+        lane_id: String,
+        source_id: String,
+        target_id: String,
+        merged_body: String,
+    },
+    /// Add an empty named group to a lane
+    AddGroup {
+        lane_id: String,
+        title: String,
+    },
+    RenameGroup {
+        lane_id: String,
+        group_id: String,
+        title: String,
+    },
+    /// Remove a group, leaving its items in the lane
+    RemoveGroup {
+        lane_id: String,
+        group_id: String,
+    },
+    /// Put an item into a group of its lane, or out of its group if `None`
+    GroupItem {
+        lane_id: String,
+        item_id: String,
+        group_id: Option<String>,
+    },
+    ReorderGroup {
+        lane_id: String,
+        group_id: String,
+        new_position: u64,
+    },
+    /// Upvote a group, on boards that vote on groups
+    UpvoteGroup {
+        lane_id: String,
+        group_id: String,
+    },
+    /// Change the board's settings, leaving those not given as they are
+    UpdateSettings {
+        vote_on_groups: Option<bool>,
+        /// Emoji participants can react with
+        reactions: Option<Vec<String>>,
+        /// Team holding the retro, `null` for none
+        #[serde(default, deserialize_with = "present")]
+        team: Option<Option<String>>,
+        /// Whether to keep item authors hidden. Only a facilitator can
+        /// change it.
+        anonymous: Option<bool>,
+    },
+    /// Add the sender's reaction to an item, or take it back if it is there
+    React {
+        lane_id: String,
+        item_id: String,
+        emoji: String,
+    },
+    /// Split the items merged into an item back out
+    UnmergeItems {
+        lane_id: String,
+        id: String,
+    },
+    /// Give an item back the body of one of its revisions, by index.
+    /// Facilitators only.
+    RollBackItem {
+        lane_id: String,
+        item_id: String,
+        revision: usize,
+    },
+    /// Comment on an item, or reply to one of its comments
+    AddComment {
+        lane_id: String,
+        item_id: String,
+        body: String,
+        #[serde(default)]
+        reply_to: Option<String>,
+    },
+    /// Change a comment. Only its author or a facilitator can.
+    EditComment {
+        lane_id: String,
+        item_id: String,
+        comment_id: String,
+        body: String,
+    },
+    /// Delete a comment. Only its author or a facilitator can.
+    DeleteComment {
+        lane_id: String,
+        item_id: String,
+        comment_id: String,
+    },
+    /// Add an action item to the board's action items lane
+    AddActionItem {
+        body: String,
+        #[serde(default)]
+        assignee: Option<String>,
+        #[serde(default)]
+        due: Option<NaiveDate>,
+        /// Card the action item came out of
+        #[serde(default)]
+        source: Option<CardRef>,
+    },
+    /// Change an action item's details, leaving those not given as they
+    /// are. `null` clears the assignee or due date.
+    UpdateActionItem {
+        lane_id: String,
+        item_id: String,
+        #[serde(default, deserialize_with = "present")]
+        assignee: Option<Option<String>>,
+        #[serde(default, deserialize_with = "present")]
+        due: Option<Option<NaiveDate>>,
+        #[serde(default)]
+        status: Option<ActionStatus>,
+    },
+    /// Mark an action item done
+    CompleteActionItem {
+        lane_id: String,
+        item_id: String,
+    },
+    /// Move an action item carried over from the previous retro into the
+    /// action items lane, open again, optionally with a new due date
+    RecommitActionItem {
+        lane_id: String,
+        item_id: String,
+        #[serde(default)]
+        due: Option<NaiveDate>,
+    },
+    /// Add several items at once, matching lanes by title
+    AddCards {
+        cards: Vec<Card>,
+        /// Create lanes no existing lane title matches, instead of refusing
+        #[serde(default)]
+        create_lanes: bool,
+    },
+    /// Move a lane and its items to the trash
+    RemoveLane {
+        lane_id: String,
+    },
+    /// Put an item or lane back from the trash
+    Restore {
+        id: String,
+    },
+    /// Delete everything in the trash for good. Facilitators only.
+    EmptyTrash,
+    /// Undo the sender's latest action, or anyone's for a facilitator
+    Undo {
+        #[serde(default)]
+        everyone: bool,
+    },
+    /// Redo the sender's latest undone action, or anyone's for a facilitator
+    Redo {
+        #[serde(default)]
+        everyone: bool,
+    },
+}
+
+/// Why an action was refused. The board is left unchanged.
+#[derive(Debug)]
+pub struct Rejection(pub String);
+
+impl std::fmt::Display for Rejection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// A change small enough to broadcast on its own, instead of a snapshot of
+/// the whole board. Tagged by `type` like the other server messages.
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum Update {
+    /// Every comment on an item, after one of them was added, edited or deleted
+    CommentsChanged {
+        lane_id: String,
+        item_id: String,
+        comments: IndexMap<String, Comment>,
+    },
+    /// Every reaction to an item, after one was added or taken back
+    ReactionsChanged {
+        lane_id: String,
+        item_id: String,
+        reactions: IndexMap<String, Vec<String>>,
+    },
+}
+
+/// Builds the update for one item of a board, once an action changed it.
+type ItemUpdate = fn(&RetroBoard, &str, &str) -> Option<Update>;
+
+impl Update {
+    /// The comments of an item as they are now, without the authors kept
+    /// hidden.
+    fn comments(board: &RetroBoard, lane_id: &str, item_id: &str) -> Option<Self> {
+        let item = board.lanes.get(lane_id)?.items.get(item_id)?;
+        let mut comments = item.comments.clone();
+        retroboard::hide_comment_authors(&mut comments, &board.settings);
+        Some(Update::CommentsChanged {
+            lane_id: lane_id.to_string(),
+            item_id: item_id.to_string(),
+            comments,
+        })
+    }
+
+    /// The reactions to an item as they are now, without the participants
+    /// kept hidden.
+    fn reactions(board: &RetroBoard, lane_id: &str, item_id: &str) -> Option<Self> {
+        let item = board.lanes.get(lane_id)?.items.get(item_id)?;
+        Some(Update::ReactionsChanged {
+            lane_id: lane_id.to_string(),
+            item_id: item_id.to_string(),
+            reactions: item.shown_reactions(&board.settings),
+        })
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).unwrap()
+    }
+
+    /// Make the same change to a copy of the board, such as the one an
+    /// instance keeps of a board shared through Redis. That copy gets the
+    /// hidden participants blanked like clients do, until the next snapshot
+    /// replaces it.
+    pub fn apply_to(self, board: &mut RetroBoard) {
+        let (lane_id, item_id) = match &self {
+            Update::CommentsChanged {
+                lane_id, item_id, ..
+            }
+            | Update::ReactionsChanged {
+                lane_id, item_id, ..
+            } => (lane_id, item_id),
+        };
+        let Some(item) = board
+            .lanes
+            .get_mut(lane_id)
+            .and_then(|lane| lane.items.get_mut(item_id))
+        else {
+            tracing::error!("Item with ID '{}' not found in lane '{}'", item_id, lane_id);
+            return;
+        };
+        match self {
+            Update::CommentsChanged { comments, .. } => item.comments = comments,
+            Update::ReactionsChanged { reactions, .. } => item.reactions = reactions,
+        }
+    }
+}
+
+/// Who sent an action, and the limits it is checked against.
+pub struct ActionContext<'a> {
+    pub participant: &'a str,
+    /// Whether the sender may act on other participants' actions
+    pub facilitator: bool,
+    pub limits: &'a Limits,
+}
+
+impl Action {
+    /// The action's `type` tag, for logs and metrics.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Action::AddLane { .. } => "AddLane",
+            Action::AddItem { .. } => "AddItem",
+            Action::RemoveItem { .. } => "RemoveItem",
+            Action::UpvoteItem { .. } => "UpvoteItem",
+            Action::MoveItem { .. } => "MoveItem",
+            Action::ReorderItem { .. } => "ReorderItem",
+            Action::EditItem { .. } => "EditItem",
+            Action::MergeItems { .. } => "MergeItems",
+            Action::UnmergeItems { .. } => "UnmergeItems",
+            Action::RollBackItem { .. } => "RollBackItem",
+            Action::AddGroup { .. } => "AddGroup",
+            Action::RenameGroup { .. } => "RenameGroup",
+            Action::RemoveGroup { .. } => "RemoveGroup",
+            Action::GroupItem { .. } => "GroupItem",
+            Action::ReorderGroup { .. } => "ReorderGroup",
+            Action::UpvoteGroup { .. } => "UpvoteGroup",
+            Action::UpdateSettings { .. } => "UpdateSettings",
+            Action::React { .. } => "React",
+            Action::AddComment { .. } => "AddComment",
+            Action::EditComment { .. } => "EditComment",
+            Action::DeleteComment { .. } => "DeleteComment",
+            Action::AddActionItem { .. } => "AddActionItem",
+            Action::UpdateActionItem { .. } => "UpdateActionItem",
+            Action::CompleteActionItem { .. } => "CompleteActionItem",
+            Action::RecommitActionItem { .. } => "RecommitActionItem",
+            Action::AddCards { .. } => "AddCards",
+            Action::RemoveLane { .. } => "RemoveLane",
+            Action::Restore { .. } => "Restore",
+            Action::EmptyTrash => "EmptyTrash",
+            Action::Undo { .. } => "Undo",
+            Action::Redo { .. } => "Redo",
+        }
+    }
+
+    /// The lane and item this action changes, and how to describe the
+    /// change, for actions that change only the comments or reactions of an
+    /// item.
+    fn updated_item(&self) -> Option<(&str, &str, ItemUpdate)> {
+        match self {
+            Action::AddComment {
+                lane_id, item_id, ..
+            }
+            | Action::EditComment {
+                lane_id, item_id, ..
+            }
+            | Action::DeleteComment {
+                lane_id, item_id, ..
+            } => Some((lane_id, item_id, Update::comments)),
+            Action::React {
+                lane_id, item_id, ..
+            } => Some((lane_id, item_id, Update::reactions)),
+            _ => None,
+        }
+    }
+
+    /// What this action may change on `board` when applied by `participant`,
+    /// to record for undo.
+    fn scope(&self, board: &RetroBoard, participant: &str) -> history::Scope {
+        let lane = |lane_id: &str| history::Scope {
+            lanes: vec![lane_id.to_string()],
+            lane_items: vec![lane_id.to_string()],
+            ..history::Scope::default()
+        };
+        let items = |items: &[(&str, &str)]| history::Scope {
+            items: items
+                .iter()
+                .map(|(lane_id, item_id)| (lane_id.to_string(), item_id.to_string()))
+                .collect(),
+            ..history::Scope::default()
+        };
+        let action_items_lane = board.action_items_lane_id();
+        match self {
+            Action::AddLane { title } => lane(title),
+            Action::AddItem { lane_id, .. } => history::Scope {
+                lane_items: vec![lane_id.clone()],
+                ..history::Scope::default()
+            },
+            Action::RemoveItem { lane_id, id } => history::Scope {
+                new_trash: true,
+                ..items(&[(lane_id, id)])
+            },
+            Action::UpvoteItem { lane_id, id } => history::Scope {
+                votes: vec![participant.to_string()],
+                ..items(&[(lane_id, id)])
+            },
+            Action::MoveItem {
+                from_lane_id,
+                to_lane_id,
+                item_id,
+            } => items(&[(from_lane_id, item_id), (to_lane_id, item_id)]),
+            Action::MergeItems {
+                lane_id,
+                source_id,
+                target_id,
+                ..
+            } => items(&[(lane_id, source_id), (lane_id, target_id)]),
+            Action::EditItem { lane_id, id, .. } => items(&[(lane_id, id)]),
+            Action::RollBackItem {
+                lane_id, item_id, ..
+            }
+            | Action::GroupItem {
+                lane_id, item_id, ..
+            }
+            | Action::React {
+                lane_id, item_id, ..
+            }
+            | Action::AddComment {
+                lane_id, item_id, ..
+            }
+            | Action::EditComment {
+                lane_id, item_id, ..
+            }
+            | Action::DeleteComment {
+                lane_id, item_id, ..
+            }
+            | Action::UpdateActionItem {
+                lane_id, item_id, ..
+            }
+            | Action::CompleteActionItem { lane_id, item_id } => items(&[(lane_id, item_id)]),
+            // Reordering renumbers the other items of the lane
+            Action::ReorderItem { lane_id, .. } | Action::UnmergeItems { lane_id, .. } => {
+                history::Scope {
+                    lane_items: vec![lane_id.clone()],
+                    ..history::Scope::default()
+                }
+            }
+            Action::AddGroup { lane_id, .. }
+            | Action::RenameGroup { lane_id, .. }
+            | Action::ReorderGroup { lane_id, .. } => history::Scope {
+                lanes: vec![lane_id.clone()],
+                ..history::Scope::default()
+            },
+            Action::UpvoteGroup { lane_id, .. } => history::Scope {
+                lanes: vec![lane_id.clone()],
+                votes: vec![participant.to_string()],
+                ..history::Scope::default()
+            },
+            Action::RemoveGroup { lane_id, .. } => lane(lane_id),
+            Action::UpdateSettings { .. } => history::Scope {
+                settings: true,
+                ..history::Scope::default()
+            },
+            Action::AddActionItem { .. } => match action_items_lane {
+                Some(lane_id) => history::Scope {
+                    lane_items: vec![lane_id.to_string()],
+                    ..history::Scope::default()
+                },
+                None => history::Scope {
+                    new_lanes: true,
+                    ..history::Scope::default()
+                },
+            },
+            Action::RecommitActionItem {
+                lane_id, item_id, ..
+            } => match action_items_lane {
+                Some(to_lane_id) => items(&[(lane_id, item_id), (to_lane_id, item_id)]),
+                None => history::Scope {
+                    new_lanes: true,
+                    ..items(&[(lane_id, item_id)])
+                },
+            },
+            Action::AddCards { cards, .. } => history::Scope {
+                lane_items: cards
+                    .iter()
+                    .filter_map(|card| board.lane_id_by_title(&card.lane))
+                    .map(str::to_string)
+                    .collect(),
+                new_lanes: true,
+                ..history::Scope::default()
+            },
+            Action::RemoveLane { lane_id } => history::Scope {
+                new_trash: true,
+                ..lane(lane_id)
+            },
+            Action::Restore { id } => {
+                let restored = match board.trash.get(id).map(|trashed| &trashed.deleted) {
+                    Some(Deleted::Item { lane_id, .. }) => history::Scope {
+                        lane_items: vec![lane_id.clone()],
+                        ..history::Scope::default()
+                    },
+                    Some(Deleted::Lane { lane_id, .. }) => lane(lane_id),
+                    None => history::Scope::default(),
+                };
+                history::Scope {
+                    trash: vec![id.clone()],
+                    ..restored
+                }
+            }
+            Action::EmptyTrash => history::Scope {
+                trash: board.trash.keys().cloned().collect(),
+                ..history::Scope::default()
+            },
+            Action::Undo { .. } | Action::Redo { .. } => history::Scope::default(),
+        }
+    }
+
+    /// Apply this action like `apply`, and return what to broadcast instead
+    /// of a snapshot if it only changed something small.
+    pub fn apply_with_update(
+        self,
+        board: &mut RetroBoard,
+        ctx: &ActionContext,
+    ) -> Result<Option<Update>, Rejection> {
+        let updated = self
+            .updated_item()
+            .map(|(lane_id, item_id, update)| (lane_id.to_string(), item_id.to_string(), update));
+        let trashed = board.trash.len();
+        self.apply(board, ctx)?;
+        // Clients need a snapshot to see expired trash go
+        if board.trash.len() != trashed {
+            return Ok(None);
+        }
+        Ok(updated.and_then(|(lane_id, item_id, update)| update(board, &lane_id, &item_id)))
+    }
+
+    /// Apply this action to a board. Persisting the result is left to the caller.
+    pub fn apply(self, board: &mut RetroBoard, ctx: &ActionContext) -> Result<(), Rejection> {
+        // Anonymous boards keep who did what out of the logs
+        let who = if board.settings.anonymous {
+            "a participant"
+        } else {
+            ctx.participant
+        };
+        if let Err(reason) = self.check_limits(board, ctx) {
+            tracing::warn!("Rejecting action from {}: {}", who, reason);
+            return Err(Rejection(reason));
+        }
+
+        let cutoff = Utc::now() - TimeDelta::days(ctx.limits.trash_retention_days as i64);
+        let purged = board.purge_trash(Some(cutoff));
+        if purged > 0 {
+            tracing::debug!("Purged {} items and lanes from the trash", purged);
+        }
+
+        match self {
+            Action::Undo { everyone } | Action::Redo { everyone }
+                if everyone && !ctx.facilitator =>
+            {
+                Err(Rejection(
+                    "only a facilitator can undo other participants' actions".to_string(),
+                ))
+            }
+            Action::EmptyTrash if !ctx.facilitator => Err(Rejection(
+                "only a facilitator can empty the trash".to_string(),
+            )),
+            Action::RollBackItem { .. } if !ctx.facilitator => Err(Rejection(
+                "only a facilitator can roll back an item".to_string(),
+            )),
+            Action::RemoveLane { ref lane_id }
+                if !ctx.facilitator && has_others_items(board, lane_id, ctx.participant) =>
+            {
+                Err(Rejection(
+                    "only a facilitator can remove a lane with other participants' items"
+                        .to_string(),
+                ))
+            }
+            Action::UpdateSettings {
+                anonymous: Some(anonymous),
+                ..
+            } if anonymous != board.settings.anonymous && !ctx.facilitator => Err(Rejection(
+                "only a facilitator can change whether authors are shown".to_string(),
+            )),
+            Action::Undo { everyone } => {
+                tracing::debug!("Undoing the latest action of {}", who);
+                history::undo(board, ctx.participant, everyone)
+            }
+            Action::Redo { everyone } => {
+                tracing::debug!("Redoing the latest undone action of {}", who);
+                history::redo(board, ctx.participant, everyone)
+            }
+            action => {
+                let name = action.name();
+                let scope = action.scope(board, ctx.participant);
+                let before = history::Snapshot::of(board, scope);
+                action.change(board, ctx);
+                history::record(board, ctx.participant, name, before);
+                Ok(())
+            }
+        }
+    }
+
+    /// Make the change this action describes, once it passed the checks.
+    fn change(self, board: &mut RetroBoard, ctx: &ActionContext) {
+        match self {
+            Action::AddLane { title } => {
+                tracing::debug!("Adding lane: {}", title);
+                board.add_lane(&title);
+            }
+            Action::AddItem { lane_id, body } => {
+                tracing::debug!("Adding item to lane {}: {}", lane_id, body);
+                if let Some(id) = board.add_item(&lane_id, &body) {
+                    board.set_author(&lane_id, &id, ctx.participant);
+                }
+            }
+            Action::RemoveItem { lane_id, id } => {
+                tracing::debug!("Removing item from lane {}: {}", lane_id, id);
+                board.remove_item(&lane_id, &id, ctx.participant);
+            }
+            Action::UpvoteItem { lane_id, id } => {
+                tracing::debug!("Upvoting item in lane {}: {}", lane_id, id);
+                if board.upvote_item(&lane_id, &id) {
+                    board.count_vote(ctx.participant);
+                }
+            }
+            Action::MoveItem {
+                from_lane_id,
+                to_lane_id,
+                item_id,
+            } => {
+                tracing::debug!(
+                    "Moving item {} from lane {} to lane {}",
+                    item_id,
+                    from_lane_id,
+                    to_lane_id
+                );
+                board.move_item(&from_lane_id, &to_lane_id, &item_id);
+            }
+            Action::ReorderItem {
+                lane_id,
+                item_id,
+                new_position,
+            } => {
+                tracing::debug!(
+                    "Reordering item {} in lane {} to position {}",
+                    item_id,
+                    lane_id,
+                    new_position
+                );
+                board.reorder_item(&lane_id, &item_id, new_position);
+            }
+            Action::EditItem { lane_id, id, body } => {
+                tracing::debug!("Editing item {} in lane {}: {}", id, lane_id, body);
+                board.edit_item(&lane_id, &id, &body);
+            }
+            Action::MergeItems {
+                lane_id,
+                source_id,
+                target_id,
+                merged_body,
+            } => {
+                tracing::debug!(
+                    "Merging item {} into {} in lane {}",
+                    source_id,
+                    target_id,
+                    lane_id
+                );
+                board.merge_items(&lane_id, &source_id, &target_id, &merged_body);
+            }
+            Action::UnmergeItems { lane_id, id } => {
+                tracing::debug!("Unmerging item {} in lane {}", id, lane_id);
+                board.unmerge_items(&lane_id, &id);
+            }
+            Action::RollBackItem {
+                lane_id,
+                item_id,
+                revision,
+            } => {
+                tracing::debug!(
+                    "Rolling item {} in lane {} back to revision {}",
+                    item_id,
+                    lane_id,
+                    revision
+                );
+                board.roll_back_item(&lane_id, &item_id, revision);
+            }
+            Action::AddGroup { lane_id, title } => {
+                tracing::debug!("Adding group to lane {}: {}", lane_id, title);
+                board.add_group(&lane_id, &title);
+            }
+            Action::RenameGroup {
+                lane_id,
+                group_id,
+                title,
+            } => {
+                tracing::debug!("Renaming group {} in lane {}: {}", group_id, lane_id, title);
+                board.rename_group(&lane_id, &group_id, &title);
+            }
+            Action::RemoveGroup { lane_id, group_id } => {
+                tracing::debug!("Removing group {} from lane {}", group_id, lane_id);
+                board.remove_group(&lane_id, &group_id);
+            }
+            Action::GroupItem {
+                lane_id,
+                item_id,
+                group_id,
+            } => {
+                tracing::debug!(
+                    "Putting item {} in lane {} into group {:?}",
+                    item_id,
+                    lane_id,
+                    group_id
+                );
+                board.group_item(&lane_id, &item_id, group_id.as_deref());
+            }
+            Action::ReorderGroup {
+                lane_id,
+                group_id,
+                new_position,
+            } => {
+                tracing::debug!(
+                    "Reordering group {} in lane {} to position {}",
+                    group_id,
+                    lane_id,
+                    new_position
+                );
+                board.reorder_group(&lane_id, &group_id, new_position);
+            }
+            Action::UpvoteGroup { lane_id, group_id } => {
+                tracing::debug!("Upvoting group {} in lane {}", group_id, lane_id);
+                if board.upvote_group(&lane_id, &group_id) {
+                    board.count_vote(ctx.participant);
+                }
+            }
+            Action::UpdateSettings {
+                vote_on_groups,
+                reactions,
+                team,
+                anonymous,
+            } => {
+                tracing::debug!("Updating board settings");
+                if let Some(vote_on_groups) = vote_on_groups {
+                    board.settings.vote_on_groups = vote_on_groups;
+                }
+                if let Some(reactions) = reactions {
+                    board.settings.reactions = reactions;
+                }
+                if let Some(team) = team {
+                    board.settings.team = team;
+                }
+                if let Some(anonymous) = anonymous {
+                    board.settings.anonymous = anonymous;
+                }
+            }
+            Action::React {
+                lane_id,
+                item_id,
+                emoji,
+            } => {
+                tracing::debug!("Reacting with {} to item {}", emoji, item_id);
+                board.toggle_reaction(&lane_id, &item_id, &emoji, ctx.participant);
+            }
+            Action::AddComment {
+                lane_id,
+                item_id,
+                body,
+                reply_to,
+            } => {
+                tracing::debug!("Commenting on item {} in lane {}", item_id, lane_id);
+                board.add_comment(
+                    &lane_id,
+                    &item_id,
+                    ctx.participant,
+                    &body,
+                    reply_to.as_deref(),
+                );
+            }
+            Action::EditComment {
+                lane_id,
+                item_id,
+                comment_id,
+                body,
+            } => {
+                tracing::debug!("Editing comment {} on item {}", comment_id, item_id);
+                board.edit_comment(&lane_id, &item_id, &comment_id, &body);
+            }
+            Action::DeleteComment {
+                lane_id,
+                item_id,
+                comment_id,
+            } => {
+                tracing::debug!("Deleting comment {} on item {}", comment_id, item_id);
+                board.delete_comment(&lane_id, &item_id, &comment_id);
+            }
+            Action::AddActionItem {
+                body,
+                assignee,
+                due,
+                source,
+            } => {
+                tracing::debug!("Adding action item: {}", body);
+                let details = ActionDetails {
+                    assignee,
+                    due,
+                    status: ActionStatus::Open,
+                    source,
+                    carried_from: None,
+                };
+                let (lane_id, id) = board.add_action_item(&body, details);
+                board.set_author(&lane_id, &id, ctx.participant);
+            }
+            Action::UpdateActionItem {
+                lane_id,
+                item_id,
+                assignee,
+                due,
+                status,
+            } => {
+                tracing::debug!("Updating action item {} in lane {}", item_id, lane_id);
+                board.update_action_item(&lane_id, &item_id, assignee, due, status);
+            }
+            Action::CompleteActionItem { lane_id, item_id } => {
+                tracing::debug!("Completing action item {} in lane {}", item_id, lane_id);
+                let done = Some(ActionStatus::Done);
+                board.update_action_item(&lane_id, &item_id, None, None, done);
+            }
+            Action::RecommitActionItem {
+                lane_id,
+                item_id,
+                due,
+            } => {
+                tracing::debug!("Recommitting action item {} in lane {}", item_id, lane_id);
+                board.recommit_action_item(&lane_id, &item_id, due);
+            }
+            Action::AddCards { cards, .. } => {
+                tracing::debug!("Adding {} cards", cards.len());
+                for card in cards {
+                    let lane_id = match board.lane_id_by_title(&card.lane) {
+                        Some(lane_id) => lane_id.to_string(),
+                        None => board.add_imported_lane(&card.lane),
+                    };
+                    if let Some(id) = board.add_item(&lane_id, card.body.trim()) {
+                        board.lanes[&lane_id].items.get_mut(&id).unwrap().vote_count = card.votes;
+                    }
+                }
+            }
+            Action::RemoveLane { lane_id } => {
+                tracing::debug!("Removing lane {}", lane_id);
+                board.remove_lane(&lane_id, ctx.participant);
+            }
+            Action::Restore { id } => {
+                tracing::debug!("Restoring {} from the trash", id);
+                board.restore(&id);
+            }
+            Action::EmptyTrash => {
+                tracing::debug!("Emptying the trash");
+                board.purge_trash(None);
+            }
+            Action::Undo { .. } | Action::Redo { .. } => unreachable!("handled by apply"),
+        }
+    }
+
+    fn check_limits(&self, board: &RetroBoard, ctx: &ActionContext) -> Result<(), String> {
+        let limits = ctx.limits;
+        let body = match self {
+            Action::AddLane { title } => {
+                if board.lanes.len() >= limits.max_lanes {
+                    return Err(format!("board already has {} lanes", limits.max_lanes));
+                }
+                Some(title)
+            }
+            Action::AddItem { lane_id, body } => {
+                check_lane_has_room(board, lane_id, limits)?;
+                Some(body)
+            }
+            Action::MoveItem {
+                from_lane_id,
+                to_lane_id,
+                ..
+            } if from_lane_id != to_lane_id => {
+                check_lane_has_room(board, to_lane_id, limits)?;
+                None
+            }
+            Action::UpvoteItem { lane_id, id } => {
+                let grouped = board
+                    .lanes
+                    .get(lane_id)
+                    .and_then(|lane| lane.items.get(id))
+                    .is_some_and(|item| item.group.is_some());
+                if board.settings.vote_on_groups && grouped {
+                    return Err("this board votes on groups, not on the items in them".to_string());
+                }
+                check_votes_left(board, ctx)?;
+                None
+            }
+            Action::UpvoteGroup { .. } => {
+                if !board.settings.vote_on_groups {
+                    return Err("this board votes on items, not on groups".to_string());
+                }
+                check_votes_left(board, ctx)?;
+                None
+            }
+            Action::AddGroup { lane_id, title } => {
+                if let Some(lane) = board.lanes.get(lane_id) {
+                    if lane.groups.len() >= limits.max_items_per_lane {
+                        return Err(format!(
+                            "lane '{}' already has {} groups",
+                            lane_id, limits.max_items_per_lane
+                        ));
+                    }
+                }
+                Some(title)
+            }
+            Action::RenameGroup { title, .. } => Some(title),
+            Action::AddCards {
+                cards,
+                create_lanes,
+            } => {
+                check_cards(board, cards, *create_lanes, limits)?;
+                None
+            }
+            Action::UnmergeItems { lane_id, id } => {
+                check_item_author(board, lane_id, id, ctx)?;
+                if let Some(lane) = board.lanes.get(lane_id) {
+                    let unmerged = lane.items.get(id).map_or(0, |item| item.merged.len());
+                    if lane.items.len() + unmerged > limits.max_items_per_lane {
+                        return Err(format!(
+                            "lane '{}' would have more than {} items",
+                            lane_id, limits.max_items_per_lane
+                        ));
+                    }
+                }
+                None
+            }
+            Action::Restore { id } => {
+                match board.trash.get(id).map(|trashed| &trashed.deleted) {
+                    Some(Deleted::Item { lane_id, .. }) => {
+                        check_lane_has_room(board, lane_id, limits)?
+                    }
+                    Some(Deleted::Lane { .. }) if board.lanes.len() >= limits.max_lanes => {
+                        return Err(format!("board already has {} lanes", limits.max_lanes));
+                    }
+                    _ => (),
+                }
+                None
+            }
+            Action::UpdateSettings {
+                reactions, team, ..
+            } => {
+                if let Some(reactions) = reactions {
+                    check_reactions(reactions)?;
+                }
+                team.as_ref().and_then(Option::as_ref)
+            }
+            Action::React {
+                lane_id,
+                item_id,
+                emoji,
+            } => {
+                let reacted = board
+                    .lanes
+                    .get(lane_id)
+                    .and_then(|lane| lane.items.get(item_id))
+                    .and_then(|item| item.reactions.get(emoji))
+                    .is_some_and(|participants| participants.iter().any(|p| p == ctx.participant));
+                // Taking a reaction back is fine even once it is no longer offered
+                if !reacted && !board.settings.reactions.contains(emoji) {
+                    return Err(format!("'{}' is not one of this board's reactions", emoji));
+                }
+                None
+            }
+            Action::AddActionItem {
+                body,
+                assignee,
+                source,
+                ..
+            } => {
+                match board.action_items_lane_id() {
+                    Some(lane_id) => check_lane_has_room(board, lane_id, limits)?,
+                    None if board.lanes.len() >= limits.max_lanes => {
+                        return Err(format!("board already has {} lanes", limits.max_lanes));
+                    }
+                    None => (),
+                }
+                if let Some(source) = source {
+                    let found = board
+                        .lanes
+                        .get(&source.lane_id)
+                        .is_some_and(|lane| lane.items.contains_key(&source.item_id));
+                    if !found {
+                        return Err(format!("card '{}' not found", source.item_id));
+                    }
+                }
+                check_assignee(assignee.as_deref(), limits)?;
+                Some(body)
+            }
+            Action::UpdateActionItem { assignee, .. } => {
+                check_assignee(assignee.clone().flatten().as_deref(), limits)?;
+                None
+            }
+            Action::RecommitActionItem {
+                lane_id, item_id, ..
+            } => {
+                let carried = board
+                    .lanes
+                    .get(lane_id)
+                    .and_then(|lane| lane.items.get(item_id))
+                    .and_then(|item| item.action.as_ref())
+                    .is_some_and(|details| details.carried_from.is_some());
+                if !carried {
+                    return Err(format!(
+                        "'{}' is not an action item carried over from an earlier retro",
+                        item_id
+                    ));
+                }
+                match board.action_items_lane_id() {
+                    Some(to_lane_id) if to_lane_id != lane_id => {
+                        check_lane_has_room(board, to_lane_id, limits)?
+                    }
+                    None if board.lanes.len() >= limits.max_lanes => {
+                        return Err(format!("board already has {} lanes", limits.max_lanes));
+                    }
+                    _ => (),
+                }
+                None
+            }
+            Action::AddComment { body, .. } => Some(body),
+            Action::EditComment {
+                lane_id,
+                item_id,
+                comment_id,
+                body,
+            } => {
+                check_comment_author(board, lane_id, item_id, comment_id, ctx)?;
+                Some(body)
+            }
+            Action::DeleteComment {
+                lane_id,
+                item_id,
+                comment_id,
+            } => {
+                check_comment_author(board, lane_id, item_id, comment_id, ctx)?;
+                None
+            }
+            Action::EditItem { lane_id, id, body } => {
+                check_item_author(board, lane_id, id, ctx)?;
+                Some(body)
+            }
+            Action::RemoveItem { lane_id, id } => {
+                check_item_author(board, lane_id, id, ctx)?;
+                None
+            }
+            Action::MergeItems {
+                lane_id,
+                source_id,
+                target_id,
+                merged_body,
+            } => {
+                check_item_author(board, lane_id, source_id, ctx)?;
+                check_item_author(board, lane_id, target_id, ctx)?;
+                Some(merged_body)
+            }
+            _ => None,
+        };
+
+        match body {
+            Some(body) if body.chars().count() > limits.max_body_length => Err(format!(
+                "text is longer than {} characters",
+                limits.max_body_length
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+fn check_votes_left(board: &RetroBoard, ctx: &ActionContext) -> Result<(), String> {
+    let cast = board.votes_cast.get(ctx.participant).copied().unwrap_or(0);
+    match ctx.limits.votes_per_participant {
+        Some(allowed) if cast >= allowed => Err(format!("all {} votes have been used", allowed)),
+        _ => Ok(()),
+    }
+}
+
+fn check_assignee(assignee: Option<&str>, limits: &Limits) -> Result<(), String> {
+    match assignee {
+        Some(assignee) if assignee.chars().count() > limits.max_body_length => Err(format!(
+            "assignee is longer than {} characters",
+            limits.max_body_length
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Tells a field given as `null`, which is `Some(None)`, from one left out,
+/// which `#[serde(default)]` makes `None`.
+pub fn present<'de, T, D>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    T: Deserialize<'de>,
+    D: Deserializer<'de>,
+{
+    Option::deserialize(deserializer).map(Some)
+}
+
+/// Emoji offered as reactions must be short, distinct and not too many.
+fn check_reactions(reactions: &[String]) -> Result<(), String> {
+    if reactions.len() > MAX_REACTIONS {
+        return Err(format!(
+            "at most {} reactions can be offered",
+            MAX_REACTIONS
+        ));
+    }
+    for (index, emoji) in reactions.iter().enumerate() {
+        let length = emoji.chars().count();
+        if length == 0 || length > MAX_REACTION_LENGTH {
+            return Err(format!("'{}' is not a valid reaction", emoji));
+        }
+        if reactions[..index].contains(emoji) {
+            return Err(format!("'{}' is offered twice", emoji));
+        }
+    }
+    Ok(())
+}
+
+/// Comments can only be changed by whoever wrote them, or a facilitator.
+fn check_comment_author(
+    board: &RetroBoard,
+    lane_id: &str,
+    item_id: &str,
+    comment_id: &str,
+    ctx: &ActionContext,
+) -> Result<(), String> {
+    match board.comment(lane_id, item_id, comment_id) {
+        Some(comment) if comment.author != ctx.participant && !ctx.facilitator => {
+            Err("only the author or a facilitator can change a comment".to_string())
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Items can only be edited or deleted by whoever added them, or a
+/// facilitator. Items with no known author are open to everyone.
+fn check_item_author(
+    board: &RetroBoard,
+    lane_id: &str,
+    item_id: &str,
+    ctx: &ActionContext,
+) -> Result<(), String> {
+    let author = board
+        .lanes
+        .get(lane_id)
+        .and_then(|lane| lane.items.get(item_id))
+        .and_then(|item| item.author.as_deref());
+    match author {
+        Some(author) if author != ctx.participant && !ctx.facilitator => {
+            Err("only the author or a facilitator can change an item".to_string())
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Whether a lane holds items added by someone other than `participant`.
+fn has_others_items(board: &RetroBoard, lane_id: &str, participant: &str) -> bool {
+    board.lanes.get(lane_id).is_some_and(|lane| {
+        lane.items.values().any(|item| {
+            item.author
+                .as_deref()
+                .is_some_and(|author| author != participant)
+        })
+    })
+}
+
+fn check_lane_has_room(board: &RetroBoard, lane_id: &str, limits: &Limits) -> Result<(), String> {
+    match board.lanes.get(lane_id) {
+        Some(lane) if lane.items.len() >= limits.max_items_per_lane => Err(format!(
+            "lane '{}' already has {} items",
+            lane_id, limits.max_items_per_lane
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Check that `cards` fit on the board, counting the lanes they create.
+fn check_cards(
+    board: &RetroBoard,
+    cards: &[Card],
+    create_lanes: bool,
+    limits: &Limits,
+) -> Result<(), String> {
+    // Items added per existing lane ID, and per title of a lane to create
+    let mut added: HashMap<&str, usize> = HashMap::new();
+    let mut created: HashMap<String, usize> = HashMap::new();
+    for card in cards {
+        if card.body.trim().is_empty() {
+            return Err(format!("a card in lane '{}' is empty", card.lane));
+        }
+        if card.body.chars().count() > limits.max_body_length {
+            return Err(format!(
+                "text is longer than {} characters",
+                limits.max_body_length
+            ));
+        }
+        match board.lane_id_by_title(&card.lane) {
+            Some(lane_id) => *added.entry(lane_id).or_default() += 1,
+            None if !create_lanes => return Err(format!("no lane is titled '{}'", card.lane)),
+            None if card.lane.trim().is_empty() => return Err("lane title is empty".to_string()),
+            None => *created.entry(card.lane.trim().to_lowercase()).or_default() += 1,
+        }
+    }
+
+    let lanes = board.lanes.len() + created.len();
+    if lanes > limits.max_lanes {
+        return Err(format!(
+            "board would have {} lanes, at most {} are allowed",
+            lanes, limits.max_lanes
+        ));
+    }
+    let existing = added.into_iter().map(|(lane_id, count)| {
+        (
+            lane_id.to_string(),
+            board.lanes[lane_id].items.len() + count,
+        )
+    });
+    for (lane, count) in existing.chain(created) {
+        if count > limits.max_items_per_lane {
+            return Err(format!(
+                "lane '{}' would have {} items, at most {} are allowed",
+                lane, count, limits.max_items_per_lane
+            ));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn apply_as(participant: &str, action: Action, board: &mut RetroBoard, limits: &Limits) {
+        let _ = action.apply(
+            board,
+            &ActionContext {
+                participant,
+                facilitator: false,
+                limits,
+            },
+        );
+    }
+
+    /// Apply an action as a facilitator, checking that its history entry
+    /// records everything it changed on the board.
+    fn apply_recorded(action: Action, board: &mut RetroBoard, limits: &Limits) {
+        let context = ActionContext {
+            participant: "alice",
+            facilitator: true,
+            limits,
+        };
+        let mut everything = history::Scope::board(board);
+        everything.votes.push(context.participant.to_string());
+        let before = history::Snapshot::of(board, everything);
+        let recorded = board.history.entries().count();
+        let name = action.name();
+        action.apply(board, &context).unwrap();
+
+        let entry = board.history.entries().last().unwrap();
+        assert_eq!(board.history.entries().count(), recorded + 1, "{}", name);
+        assert_eq!(
+            entry.changes_json(),
+            history::changes_json_since(before, board),
+            "{}",
+            name
+        );
+    }
+
+    #[test]
+    fn test_action_serialization() {
+        // Test AddLane action
+        let add_lane = Action::AddLane {
+            title: "Test Lane".to_string(),
+        };
+        let json = serde_json::to_string(&add_lane).unwrap();
+        let parsed: Action = serde_json::from_str(&json).unwrap();
+        match parsed {
+            Action::AddLane { title } => assert_eq!(title, "Test Lane"),
+            _ => panic!("Wrong action type"),
+        }
+
+        // Test AddItem action
+        let add_item = Action::AddItem {
+            lane_id: "lane1".to_string(),
+            body: "Test item".to_string(),
+        };
+        let json = serde_json::to_string(&add_item).unwrap();
+        let parsed: Action = serde_json::from_str(&json).unwrap();
+        match parsed {
+            Action::AddItem { lane_id, body } => {
+                assert_eq!(lane_id, "lane1");
+                assert_eq!(body, "Test item");
+            }
+            _ => panic!("Wrong action type"),
+        }
+
+        // Test RemoveItem action
+        let remove_item = Action::RemoveItem {
+            lane_id: "lane1".to_string(),
+            id: "item1".to_string(),
+        };
+        let json = serde_json::to_string(&remove_item).unwrap();
+        let parsed: Action = serde_json::from_str(&json).unwrap();
+        match parsed {
+            Action::RemoveItem { lane_id, id } => {
+                assert_eq!(lane_id, "lane1");
+                assert_eq!(id, "item1");
+            }
+            _ => panic!("Wrong action type"),
+        }
+
+        // Test UpvoteItem action
+        let upvote_item = Action::UpvoteItem {
+            lane_id: "lane1".to_string(),
+            id: "item1".to_string(),
+        };
+        let json = serde_json::to_string(&upvote_item).unwrap();
+        let parsed: Action = serde_json::from_str(&json).unwrap();
+        match parsed {
+            Action::UpvoteItem { lane_id, id } => {
+                assert_eq!(lane_id, "lane1");
+                assert_eq!(id, "item1");
+            }
+            _ => panic!("Wrong action type"),
+        }
+
+        // Test MoveItem action
+        let move_item = Action::MoveItem {
+            from_lane_id: "lane1".to_string(),
+            to_lane_id: "lane2".to_string(),
+            item_id: "item1".to_string(),
+        };
+        let json = serde_json::to_string(&move_item).unwrap();
+        let parsed: Action = serde_json::from_str(&json).unwrap();
+        match parsed {
+            Action::MoveItem {
+                from_lane_id,
+                to_lane_id,
+                item_id,
+            } => {
+                assert_eq!(from_lane_id, "lane1");
+                assert_eq!(to_lane_id, "lane2");
+                assert_eq!(item_id, "item1");
+            }
+            _ => panic!("Wrong action type"),
+        }
+
+        // Test ReorderItem action
+        let reorder_item = Action::ReorderItem {
+            lane_id: "lane1".to_string(),
+            item_id: "item1".to_string(),
+            new_position: 5,
+        };
+        let json = serde_json::to_string(&reorder_item).unwrap();
+        let parsed: Action = serde_json::from_str(&json).unwrap();
+        match parsed {
+            Action::ReorderItem {
+                lane_id,
+                item_id,
+                new_position,
+            } => {
+                assert_eq!(lane_id, "lane1");
+                assert_eq!(item_id, "item1");
+                assert_eq!(new_position, 5);
+            }
+            _ => panic!("Wrong action type"),
+        }
+
+        // Test EditItem action
+        let edit_item = Action::EditItem {
+            lane_id: "lane1".to_string(),
+            id: "item1".to_string(),
+            body: "Updated body".to_string(),
+        };
+        let json = serde_json::to_string(&edit_item).unwrap();
+        let parsed: Action = serde_json::from_str(&json).unwrap();
+        match parsed {
+            Action::EditItem { lane_id, id, body } => {
+                assert_eq!(lane_id, "lane1");
+                assert_eq!(id, "item1");
+                assert_eq!(body, "Updated body");
+            }
+            _ => panic!("Wrong action type"),
+        }
+
+        // Test MergeItems action
+        let merge_items = Action::MergeItems {
+            lane_id: "lane1".to_string(),
+            source_id: "item1".to_string(),
+            target_id: "item2".to_string(),
+            merged_body: "Merged text".to_string(),
+        };
+        let json = serde_json::to_string(&merge_items).unwrap();
+        let parsed: Action = serde_json::from_str(&json).unwrap();
+        match parsed {
+            Action::MergeItems {
+                lane_id,
+                source_id,
+                target_id,
+                merged_body,
+            } => {
+                assert_eq!(lane_id, "lane1");
+                assert_eq!(source_id, "item1");
+                assert_eq!(target_id, "item2");
+                assert_eq!(merged_body, "Merged text");
+            }
+            _ => panic!("Wrong action type"),
+        }
+    }
+
+    #[test]
+    fn test_invalid_action_json() {
+        let invalid_json = r#"{"type": "InvalidAction"}"#;
+        let result: Result<Action, _> = serde_json::from_str(invalid_json);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_vote_limit_per_participant() {
+        let mut board = RetroBoard::default();
+        let limits = Limits {
+            votes_per_participant: Some(2),
+            ..Limits::default()
+        };
+        let upvote = || Action::UpvoteItem {
+            lane_id: "went-well".to_string(),
+            id: "1".to_string(),
+        };
+
+        apply_as("alice", upvote(), &mut board, &limits);
+        apply_as("alice", upvote(), &mut board, &limits);
+        apply_as("alice", upvote(), &mut board, &limits);
+        apply_as("bob", upvote(), &mut board, &limits);
+
+        let item = board
+            .lanes
+            .get("went-well")
+            .unwrap()
+            .items
+            .get("1")
+            .unwrap();
+        assert_eq!(item.vote_count, 3);
+        assert_eq!(board.votes_cast.get("alice"), Some(&2));
+        assert_eq!(board.votes_cast.get("bob"), Some(&1));
+    }
+
+    #[test]
+    fn test_upvote_of_missing_item_does_not_use_a_vote() {
+        let mut board = RetroBoard::default();
+        let action = Action::UpvoteItem {
+            lane_id: "went-well".to_string(),
+            id: "missing".to_string(),
+        };
+        apply_as("alice", action, &mut board, &Limits::default());
+        assert!(!board.votes_cast.contains_key("alice"));
+    }
+
+    #[test]
+    fn test_lane_and_item_limits() {
+        let mut board = RetroBoard::new("Board");
+        let limits = Limits {
+            max_lanes: 2,
+            max_items_per_lane: 1,
+            ..Limits::default()
+        };
+
+        for title in ["A", "B", "C"] {
+            let action = Action::AddLane {
+                title: title.to_string(),
+            };
+            apply_as("alice", action, &mut board, &limits);
+        }
+        assert_eq!(board.lanes.len(), 2);
+
+        for body in ["first", "second"] {
+            let action = Action::AddItem {
+                lane_id: "A".to_string(),
+                body: body.to_string(),
+            };
+            apply_as("alice", action, &mut board, &limits);
+        }
+        assert_eq!(board.lanes.get("A").unwrap().items.len(), 1);
+
+        // B is full, so the item stays in A
+        board.add_item("B", "other");
+        let item_id = board
+            .lanes
+            .get("A")
+            .unwrap()
+            .items
+            .keys()
+            .next()
+            .unwrap()
+            .clone();
+        let action = Action::MoveItem {
+            from_lane_id: "A".to_string(),
+            to_lane_id: "B".to_string(),
+            item_id: item_id.clone(),
+        };
+        apply_as("alice", action, &mut board, &limits);
+        assert!(board.lanes.get("A").unwrap().items.contains_key(&item_id));
+    }
+
+    #[test]
+    fn test_body_length_limit() {
+        let mut board = RetroBoard::default();
+        let limits = Limits {
+            max_body_length: 5,
+            ..Limits::default()
+        };
+
+        let action = Action::AddItem {
+            lane_id: "went-well".to_string(),
+            body: "too long".to_string(),
+        };
+        let result = action.apply(
+            &mut board,
+            &ActionContext {
+                participant: "alice",
+                facilitator: false,
+                limits: &limits,
+            },
+        );
+        assert!(result.is_err());
+        assert_eq!(board.lanes.get("went-well").unwrap().items.len(), 2);
+
+        let action = Action::EditItem {
+            lane_id: "went-well".to_string(),
+            id: "1".to_string(),
+            body: "short".to_string(),
+        };
+        apply_as("alice", action, &mut board, &limits);
+        let item = board
+            .lanes
+            .get("went-well")
+            .unwrap()
+            .items
+            .get("1")
+            .unwrap();
+        assert_eq!(item.body, "short");
+    }
+
+    #[test]
+    fn test_history_records_everything_actions_change() {
+        let mut board = RetroBoard::from_template("went-well").unwrap();
+        let limits = Limits::default();
+        let lane_id = || "went-well".to_string();
+        let a = board.add_item("went-well", "Deploys").unwrap();
+        let b = board.add_item("went-well", "Releases").unwrap();
+        let c = board.add_item("went-well", "Reviews").unwrap();
+        let group = board.add_group("went-well", "Shipping").unwrap();
+        board.group_item("went-well", &a, Some(&group));
+        board.add_comment("went-well", &a, "bob", "Agreed", None);
+        board.edit_item("went-well", &a, "Fast deploys");
+        board.settings.vote_on_groups = true;
+
+        let actions = vec![
+            Action::AddLane {
+                title: "Kudos".to_string(),
+            },
+            Action::AddItem {
+                lane_id: lane_id(),
+                body: "Pairing".to_string(),
+            },
+            Action::UpvoteItem {
+                lane_id: lane_id(),
+                id: b.clone(),
+            },
+            Action::EditItem {
+                lane_id: lane_id(),
+                id: b.clone(),
+                body: "Weekly releases".to_string(),
+            },
+            Action::RollBackItem {
+                lane_id: lane_id(),
+                item_id: a.clone(),
+                revision: 0,
+            },
+            Action::ReorderItem {
+                lane_id: lane_id(),
+                item_id: c.clone(),
+                new_position: 0,
+            },
+            Action::MergeItems {
+                lane_id: lane_id(),
+                source_id: a.clone(),
+                target_id: b.clone(),
+                merged_body: "Deploys and releases".to_string(),
+            },
+            Action::UnmergeItems {
+                lane_id: lane_id(),
+                id: b.clone(),
+            },
+            Action::RenameGroup {
+                lane_id: lane_id(),
+                group_id: group.clone(),
+                title: "Delivery".to_string(),
+            },
+            Action::UpvoteGroup {
+                lane_id: lane_id(),
+                group_id: group.clone(),
+            },
+            Action::RemoveGroup {
+                lane_id: lane_id(),
+                group_id: group.clone(),
+            },
+            Action::React {
+                lane_id: lane_id(),
+                item_id: c.clone(),
+                emoji: "🎉".to_string(),
+            },
+            Action::AddComment {
+                lane_id: lane_id(),
+                item_id: c.clone(),
+                body: "Nice".to_string(),
+                reply_to: None,
+            },
+            Action::UpdateSettings {
+                vote_on_groups: Some(false),
+                reactions: None,
+                team: None,
+                anonymous: Some(false),
+            },
+            Action::MoveItem {
+                from_lane_id: lane_id(),
+                to_lane_id: "to-improve".to_string(),
+                item_id: c.clone(),
+            },
+            Action::RemoveItem {
+                lane_id: lane_id(),
+                id: a.clone(),
+            },
+            Action::RemoveLane {
+                lane_id: "action-items".to_string(),
+            },
+            Action::AddActionItem {
+                body: "Automate deploys".to_string(),
+                assignee: None,
+                due: None,
+                source: None,
+            },
+            Action::AddCards {
+                cards: vec![
+                    Card {
+                        lane: "To Improve".to_string(),
+                        body: "Flaky tests".to_string(),
+                        votes: 2,
+                    },
+                    Card {
+                        lane: "Shout-outs".to_string(),
+                        body: "Thanks Bob".to_string(),
+                        votes: 0,
+                    },
+                ],
+                create_lanes: true,
+            },
+        ];
+        for action in actions {
+            apply_recorded(action, &mut board, &limits);
+        }
+
+        let (trash_id, _) = board
+            .trash
+            .iter()
+            .find(|(_, trashed)| matches!(trashed.deleted, Deleted::Item { .. }))
+            .unwrap();
+        let restore = Action::Restore {
+            id: trash_id.clone(),
+        };
+        apply_recorded(restore, &mut board, &limits);
+        apply_recorded(Action::EmptyTrash, &mut board, &limits);
+    }
+
+    #[test]
+    fn test_only_facilitators_undo_for_everyone() {
+        let mut board = RetroBoard::from_template("went-well").unwrap();
+        let add = Action::AddItem {
+            lane_id: "went-well".to_string(),
+            body: "From bob".to_string(),
+        };
+        apply_as("bob", add, &mut board, &Limits::default());
+
+        let limits = Limits::default();
+        let undo = || Action::Undo { everyone: true };
+        let context = |facilitator| ActionContext {
+            participant: "alice",
+            facilitator,
+            limits: &limits,
+        };
+        let result = undo().apply(&mut board, &context(false));
+        assert!(result.is_err());
+        assert_eq!(board.lanes["went-well"].items.len(), 1);
+
+        undo().apply(&mut board, &context(true)).unwrap();
+        assert!(board.lanes["went-well"].items.is_empty());
+        Action::Redo { everyone: true }
+            .apply(&mut board, &context(true))
+            .unwrap();
+        assert_eq!(board.lanes["went-well"].items.len(), 1);
+    }
+
+    #[test]
+    fn test_trash() {
+        let mut board = RetroBoard::from_template("went-well").unwrap();
+        let limits = Limits::default();
+        let remove = Action::RemoveLane {
+            lane_id: "to-improve".to_string(),
+        };
+        apply_as("alice", remove, &mut board, &limits);
+        let (trash_id, trashed) = board.trash.first().unwrap();
+        assert_eq!(trashed.deleted_by, "alice");
+
+        let restore = Action::Restore {
+            id: trash_id.clone(),
+        };
+        apply_as("bob", restore, &mut board, &limits);
+        assert!(board.lanes.contains_key("to-improve"));
+        assert!(board.trash.is_empty());
+
+        let remove = Action::RemoveLane {
+            lane_id: "to-improve".to_string(),
+        };
+        apply_as("alice", remove, &mut board, &limits);
+        apply_as("alice", Action::EmptyTrash, &mut board, &limits);
+        assert_eq!(board.trash.len(), 1);
+        let context = ActionContext {
+            participant: "carol",
+            facilitator: true,
+            limits: &limits,
+        };
+        Action::EmptyTrash.apply(&mut board, &context).unwrap();
+        assert!(board.trash.is_empty());
+    }
+
+    #[test]
+    fn test_only_facilitators_roll_back_items() {
+        let mut board = RetroBoard::from_template("went-well").unwrap();
+        let limits = Limits::default();
+        let item = board.add_item("went-well", "Fast deploys").unwrap();
+        let edit = Action::EditItem {
+            lane_id: "went-well".to_string(),
+            id: item.clone(),
+            body: "Slow deploys".to_string(),
+        };
+        apply_as("alice", edit, &mut board, &limits);
+        let roll_back = || Action::RollBackItem {
+            lane_id: "went-well".to_string(),
+            item_id: item.clone(),
+            revision: 0,
+        };
+
+        apply_as("alice", roll_back(), &mut board, &limits);
+        assert_eq!(board.lanes["went-well"].items[&item].body, "Slow deploys");
+        let context = ActionContext {
+            participant: "carol",
+            facilitator: true,
+            limits: &limits,
+        };
+        roll_back().apply(&mut board, &context).unwrap();
+        let restored = &board.lanes["went-well"].items[&item];
+        assert_eq!(restored.body, "Fast deploys");
+        let bodies: Vec<_> = restored.revisions.iter().map(|r| &r.body).collect();
+        assert_eq!(bodies, ["Fast deploys", "Slow deploys"]);
+    }
+
+    #[test]
+    fn test_authors_stay_hidden_on_anonymous_boards() {
+        let mut board = RetroBoard::from_template("went-well").unwrap();
+        let limits = Limits::default();
+        let add = |body: &str| Action::AddItem {
+            lane_id: "went-well".to_string(),
+            body: body.to_string(),
+        };
+        let show_authors = || Action::UpdateSettings {
+            vote_on_groups: None,
+            reactions: None,
+            team: None,
+            anonymous: Some(false),
+        };
+
+        apply_as("alice", add("Before"), &mut board, &limits);
+        let before = board.lanes["went-well"].items.values().next().unwrap();
+        assert_eq!(before.author.as_deref(), Some("alice"));
+        assert!(!board.client_snapshot().contains("alice"));
+        let item_id = board.lanes["went-well"]
+            .items
+            .keys()
+            .next()
+            .unwrap()
+            .clone();
+        let ctx = |participant| ActionContext {
+            participant,
+            facilitator: false,
+            limits: &limits,
+        };
+        // Nor do comment and reaction updates carry who sent them
+        for action in [
+            Action::AddComment {
+                lane_id: "went-well".to_string(),
+                item_id: item_id.clone(),
+                body: "Same".to_string(),
+                reply_to: None,
+            },
+            Action::React {
+                lane_id: "went-well".to_string(),
+                item_id: item_id.clone(),
+                emoji: "🎉".to_string(),
+            },
+        ] {
+            let update = action.apply_with_update(&mut board, &ctx("dave")).unwrap();
+            assert!(!update.unwrap().to_json().contains("dave"));
+        }
+
+        apply_as("bob", show_authors(), &mut board, &limits);
+        assert!(board.settings.anonymous);
+        let context = ActionContext {
+            participant: "carol",
+            facilitator: true,
+            limits: &limits,
+        };
+        show_authors().apply(&mut board, &context).unwrap();
+        apply_as("alice", add("After"), &mut board, &limits);
+
+        // Only the item added once authors were shown reveals its author
+        let snapshot: serde_json::Value = serde_json::from_str(&board.client_snapshot()).unwrap();
+        let mut authors: Vec<_> = snapshot["lanes"]["went-well"]["items"]
+            .as_object()
+            .unwrap()
+            .values()
+            .map(|item| (item["body"].as_str(), item["author"].as_str()))
+            .collect();
+        authors.sort();
+        assert_eq!(
+            authors,
+            [(Some("After"), Some("alice")), (Some("Before"), None)]
+        );
+    }
+
+    #[test]
+    fn test_only_authors_change_items() {
+        let mut board = RetroBoard::from_template("went-well").unwrap();
+        let limits = Limits::default();
+        let add = Action::AddItem {
+            lane_id: "went-well".to_string(),
+            body: "Deploys".to_string(),
+        };
+        apply_as("alice", add, &mut board, &limits);
+        let item = board.lanes["went-well"]
+            .items
+            .keys()
+            .next()
+            .unwrap()
+            .clone();
+        let edit = |body: &str| Action::EditItem {
+            lane_id: "went-well".to_string(),
+            id: item.clone(),
+            body: body.to_string(),
+        };
+        let remove = || Action::RemoveItem {
+            lane_id: "went-well".to_string(),
+            id: item.clone(),
+        };
+
+        apply_as("bob", edit("Bob's"), &mut board, &limits);
+        apply_as("bob", remove(), &mut board, &limits);
+        assert_eq!(board.lanes["went-well"].items[&item].body, "Deploys");
+
+        apply_as("alice", edit("Alice's"), &mut board, &limits);
+        assert_eq!(board.lanes["went-well"].items[&item].body, "Alice's");
+        let context = ActionContext {
+            participant: "carol",
+            facilitator: true,
+            limits: &limits,
+        };
+        remove().apply(&mut board, &context).unwrap();
+        assert!(board.lanes["went-well"].items.is_empty());
+
+        // Items without a known author stay open to everyone
+        let imported = board.add_item("went-well", "Imported").unwrap();
+        let remove = Action::RemoveItem {
+            lane_id: "went-well".to_string(),
+            id: imported,
+        };
+        apply_as("bob", remove, &mut board, &limits);
+        assert!(board.lanes["went-well"].items.is_empty());
+    }
+
+    #[test]
+    fn test_only_authors_merge_and_unmerge_items() {
+        let mut board = RetroBoard::from_template("went-well").unwrap();
+        let limits = Limits::default();
+        for (participant, body) in [("alice", "Deploys"), ("bob", "Releases")] {
+            let add = Action::AddItem {
+                lane_id: "went-well".to_string(),
+                body: body.to_string(),
+            };
+            apply_as(participant, add, &mut board, &limits);
+        }
+        let id_of = |board: &RetroBoard, body: &str| {
+            let items = &board.lanes["went-well"].items;
+            items
+                .iter()
+                .find(|(_, item)| item.body == body)
+                .unwrap()
+                .0
+                .clone()
+        };
+        let alices = id_of(&board, "Deploys");
+        let bobs = id_of(&board, "Releases");
+        let merge = |source_id: &str, target_id: &str| Action::MergeItems {
+            lane_id: "went-well".to_string(),
+            source_id: source_id.to_string(),
+            target_id: target_id.to_string(),
+            merged_body: "Shipping".to_string(),
+        };
+        let unmerge = || Action::UnmergeItems {
+            lane_id: "went-well".to_string(),
+            id: bobs.clone(),
+        };
+
+        // Merging replaces the target's body and removes the source
+        apply_as("bob", merge(&alices, &bobs), &mut board, &limits);
+        apply_as("bob", merge(&bobs, &alices), &mut board, &limits);
+        apply_as("alice", merge(&alices, &bobs), &mut board, &limits);
+        assert_eq!(board.lanes["went-well"].items.len(), 2);
+
+        let context = ActionContext {
+            participant: "carol",
+            facilitator: true,
+            limits: &limits,
+        };
+        merge(&alices, &bobs).apply(&mut board, &context).unwrap();
+        assert_eq!(board.lanes["went-well"].items[&bobs].body, "Shipping");
+
+        apply_as("alice", unmerge(), &mut board, &limits);
+        assert_eq!(board.lanes["went-well"].items.len(), 1);
+        apply_as("bob", unmerge(), &mut board, &limits);
+        assert_eq!(board.lanes["went-well"].items.len(), 2);
+    }
+
+    #[test]
+    fn test_only_facilitators_remove_lanes_with_others_items() {
+        let mut board = RetroBoard::from_template("went-well").unwrap();
+        let limits = Limits::default();
+        for (participant, lane_id) in [("alice", "went-well"), ("bob", "went-well")] {
+            let add = Action::AddItem {
+                lane_id: lane_id.to_string(),
+                body: "Deploys".to_string(),
+            };
+            apply_as(participant, add, &mut board, &limits);
+        }
+        let add = Action::AddItem {
+            lane_id: "to-improve".to_string(),
+            body: "Flaky tests".to_string(),
+        };
+        apply_as("alice", add, &mut board, &limits);
+        let remove = |lane_id: &str| Action::RemoveLane {
+            lane_id: lane_id.to_string(),
+        };
+
+        apply_as("alice", remove("went-well"), &mut board, &limits);
+        assert!(board.lanes.contains_key("went-well"));
+
+        // Lanes with only the sender's items, or none, are fine to remove
+        apply_as("alice", remove("to-improve"), &mut board, &limits);
+        apply_as("bob", remove("action-items"), &mut board, &limits);
+        assert_eq!(board.lanes.len(), 1);
+
+        let context = ActionContext {
+            participant: "carol",
+            facilitator: true,
+            limits: &limits,
+        };
+        remove("went-well").apply(&mut board, &context).unwrap();
+        assert!(board.lanes.is_empty());
+    }
+
+    #[test]
+    fn test_only_authors_change_comments() {
+        let mut board = RetroBoard::from_template("went-well").unwrap();
+        // Updates from anonymous boards leave out who sent them
+        board.settings.anonymous = false;
+        let item = board.add_item("went-well", "Deploys").unwrap();
+        let limits = Limits::default();
+        let add = Action::AddComment {
+            lane_id: "went-well".to_string(),
+            item_id: item.clone(),
+            body: "Which ones?".to_string(),
+            reply_to: None,
+        };
+        let context = ActionContext {
+            participant: "alice",
+            facilitator: false,
+            limits: &limits,
+        };
+        let update = add.apply_with_update(&mut board, &context).unwrap();
+        let Some(Update::CommentsChanged { comments, .. }) = update else {
+            panic!("adding a comment should give an update");
+        };
+        let (comment_id, comment) = comments.first().unwrap();
+        assert_eq!(comment.author, "alice");
+
+        let edit = || Action::EditComment {
+            lane_id: "went-well".to_string(),
+            item_id: item.clone(),
+            comment_id: comment_id.clone(),
+            body: "Edited".to_string(),
+        };
+        let context = ActionContext {
+            participant: "bob",
+            facilitator: false,
+            limits: &limits,
+        };
+        assert!(edit().apply(&mut board, &context).is_err());
+        let context = ActionContext {
+            participant: "carol",
+            facilitator: true,
+            limits: &limits,
+        };
+        edit().apply(&mut board, &context).unwrap();
+
+        let delete = Action::DeleteComment {
+            lane_id: "went-well".to_string(),
+            item_id: item.clone(),
+            comment_id: comment_id.clone(),
+        };
+        apply_as("alice", delete, &mut board, &limits);
+        assert!(board.lanes["went-well"].items[&item].comments.is_empty());
+    }
+
+    #[test]
+    fn test_reactions() {
+        let mut board = RetroBoard::from_template("went-well").unwrap();
+        // Updates from anonymous boards leave out who sent them
+        board.settings.anonymous = false;
+        let item = board.add_item("went-well", "Deploys").unwrap();
+        let limits = Limits::default();
+        let react = |emoji: &str| Action::React {
+            lane_id: "went-well".to_string(),
+            item_id: item.clone(),
+            emoji: emoji.to_string(),
+        };
+        let context = ActionContext {
+            participant: "alice",
+            facilitator: false,
+            limits: &limits,
+        };
+        let update = react("👍").apply_with_update(&mut board, &context).unwrap();
+        let Some(Update::ReactionsChanged { reactions, .. }) = update else {
+            panic!("reacting should give an update");
+        };
+        assert_eq!(reactions["👍"], ["alice"]);
+        assert!(react("🦀").apply(&mut board, &context).is_err());
+
+        let settings = |reactions: &[&str]| Action::UpdateSettings {
+            vote_on_groups: None,
+            reactions: Some(reactions.iter().map(|emoji| emoji.to_string()).collect()),
+            team: None,
+            anonymous: None,
+        };
+        assert!(settings(&["🦀", "🦀"]).apply(&mut board, &context).is_err());
+        assert!(settings(&[""]).apply(&mut board, &context).is_err());
+        settings(&["🦀"]).apply(&mut board, &context).unwrap();
+        react("🦀").apply(&mut board, &context).unwrap();
+        // A reaction no longer offered can still be taken back
+        react("👍").apply(&mut board, &context).unwrap();
+        let reactions = &board.lanes["went-well"].items[&item].reactions;
+        assert_eq!(reactions.keys().collect::<Vec<_>>(), ["🦀"]);
+    }
+
+    #[test]
+    fn test_action_items() {
+        let mut board = RetroBoard::from_template("mad-sad-glad").unwrap();
+        let card = board.add_item("mad", "Slow reviews").unwrap();
+        let limits = Limits::default();
+        let add = Action::AddActionItem {
+            body: "Review within a day".to_string(),
+            assignee: Some("alice".to_string()),
+            due: NaiveDate::from_ymd_opt(2026, 3, 20),
+            source: Some(CardRef {
+                lane_id: "mad".to_string(),
+                item_id: card.clone(),
+            }),
+        };
+        apply_as("bob", add, &mut board, &limits);
+        // The board had no action items lane, so one was added
+        let lane_id = board.action_items_lane_id().unwrap().to_string();
+        let (item_id, item) = board.lanes[&lane_id].items.iter().next().unwrap();
+        let item_id = item_id.clone();
+        let details = item.action.as_ref().unwrap();
+        assert_eq!(details.assignee.as_deref(), Some("alice"));
+        assert_eq!(details.source.as_ref().unwrap().item_id, card);
+
+        let update = Action::UpdateActionItem {
+            lane_id: lane_id.clone(),
+            item_id: item_id.clone(),
+            assignee: Some(None),
+            due: None,
+            status: Some(ActionStatus::InProgress),
+        };
+        apply_as("bob", update, &mut board, &limits);
+        let details = board.lanes[&lane_id].items[&item_id]
+            .action
+            .as_ref()
+            .unwrap();
+        assert_eq!(details.assignee, None);
+        assert!(details.due.is_some());
+        assert_eq!(details.status, ActionStatus::InProgress);
+
+        let complete = Action::CompleteActionItem {
+            lane_id: lane_id.clone(),
+            item_id: item_id.clone(),
+        };
+        apply_as("bob", complete, &mut board, &limits);
+        let details = board.lanes[&lane_id].items[&item_id]
+            .action
+            .as_ref()
+            .unwrap();
+        assert_eq!(details.status, ActionStatus::Done);
+
+        // Only items of action items lanes are action items
+        board.move_item(&lane_id, "glad", &item_id);
+        assert!(board.lanes["glad"].items[&item_id].action.is_none());
+        board.move_item("glad", &lane_id, &item_id);
+        let details = board.lanes[&lane_id].items[&item_id]
+            .action
+            .as_ref()
+            .unwrap();
+        assert_eq!(details.status, ActionStatus::Open);
+
+        let missing_source = Action::AddActionItem {
+            body: "Lost".to_string(),
+            assignee: None,
+            due: None,
+            source: Some(CardRef {
+                lane_id: "mad".to_string(),
+                item_id: "missing".to_string(),
+            }),
+        };
+        let context = ActionContext {
+            participant: "bob",
+            facilitator: false,
+            limits: &limits,
+        };
+        assert!(missing_source.apply(&mut board, &context).is_err());
+    }
+
+    #[test]
+    fn test_update_action_item_json() {
+        let update: Action = serde_json::from_str(
+            r#"{"type": "UpdateActionItem", "lane_id": "l", "item_id": "i", "assignee": null}"#,
+        )
+        .unwrap();
+        let Action::UpdateActionItem { assignee, due, .. } = update else {
+            panic!("wrong action");
+        };
+        assert_eq!(assignee, Some(None));
+        assert_eq!(due, None);
+    }
+
+    #[test]
+    fn test_carry_over_and_recommit_action_items() {
+        let mut previous = RetroBoard::from_template("went-well").unwrap();
+        let open = previous.add_item("action-items", "Fix the build").unwrap();
+        let done = previous.add_item("action-items", "Update docs").unwrap();
+        previous.add_item("action-items", "Pair more").unwrap();
+        let assignee = Some(Some("alice".to_string()));
+        previous.update_action_item("action-items", &open, assignee, None, None);
+        previous.update_action_item("action-items", &done, None, None, Some(ActionStatus::Done));
+
+        let mut board = RetroBoard::from_template("went-well").unwrap();
+        assert_eq!(board.carry_over_action_items("sprint-1", &previous), 2);
+        // The review lane comes first, in the order of the previous board
+        let (review_id, review) = board.lanes.first().unwrap();
+        let review_id = review_id.clone();
+        assert!(review.is_action_items());
+        let mut carried: Vec<_> = review.items.iter().collect();
+        carried.sort_by_key(|(_, item)| item.sort_order);
+        assert_eq!(carried[0].1.body, "Fix the build");
+        assert_eq!(carried[1].1.body, "Pair more");
+        let details = carried[0].1.action.as_ref().unwrap();
+        assert_eq!(details.assignee.as_deref(), Some("alice"));
+        let origin = details.carried_from.as_ref().unwrap();
+        assert_eq!(origin.board_id, "sprint-1");
+        assert_eq!(origin.item_id, open);
+        let (recommitted, completed) = (carried[0].0.clone(), carried[1].0.clone());
+        // New action items still go to the action items lane
+        assert_eq!(board.action_items_lane_id(), Some("action-items"));
+
+        let limits = Limits::default();
+        let recommit = Action::RecommitActionItem {
+            lane_id: review_id.clone(),
+            item_id: recommitted.clone(),
+            due: NaiveDate::from_ymd_opt(2026, 4, 3),
+        };
+        apply_as("bob", recommit, &mut board, &limits);
+        let details = board.lanes["action-items"].items[&recommitted]
+            .action
+            .as_ref()
+            .unwrap();
+        assert_eq!(details.status, ActionStatus::Open);
+        assert_eq!(details.due, NaiveDate::from_ymd_opt(2026, 4, 3));
+        assert!(details.carried_from.is_some());
+
+        let complete = Action::CompleteActionItem {
+            lane_id: review_id.clone(),
+            item_id: completed.clone(),
+        };
+        apply_as("bob", complete, &mut board, &limits);
+        let details = board.lanes[&review_id].items[&completed].action.as_ref();
+        assert_eq!(details.unwrap().status, ActionStatus::Done);
+
+        // Only carried over action items can be recommitted
+        let fresh = board.add_item("action-items", "New one").unwrap();
+        let recommit = Action::RecommitActionItem {
+            lane_id: "action-items".to_string(),
+            item_id: fresh,
+            due: None,
+        };
+        let context = ActionContext {
+            participant: "bob",
+            facilitator: false,
+            limits: &limits,
+        };
+        assert!(recommit.apply(&mut board, &context).is_err());
+
+        // Nothing left open means no review lane
+        let nothing_open = RetroBoard::from_template("went-well").unwrap();
+        let mut next = RetroBoard::from_template("went-well").unwrap();
+        assert_eq!(next.carry_over_action_items("sprint-2", &nothing_open), 0);
+        assert_eq!(next.lanes.len(), 3);
+    }
+
+    #[test]
+    fn test_trash_is_purged_after_retention() {
+        let mut board = RetroBoard::from_template("went-well").unwrap();
+        let limits = Limits {
+            trash_retention_days: 7,
+            ..Limits::default()
+        };
+        board.remove_lane("went-well", "alice");
+        board.remove_lane("to-improve", "alice");
+        board.trash[0].deleted_at = Utc::now() - TimeDelta::days(8);
+
+        let add = Action::AddItem {
+            lane_id: "action-items".to_string(),
+            body: "Anything".to_string(),
+        };
+        apply_as("bob", add, &mut board, &limits);
+        assert_eq!(board.trash.len(), 1);
+    }
+
+    #[test]
+    fn test_vote_on_groups() {
+        let mut board = RetroBoard::from_template("went-well").unwrap();
+        let limits = Limits {
+            votes_per_participant: Some(2),
+            ..Limits::default()
+        };
+        let item = board.add_item("went-well", "Deploys").unwrap();
+        let group = board.add_group("went-well", "Shipping").unwrap();
+        board.group_item("went-well", &item, Some(&group));
+        let upvote_item = || Action::UpvoteItem {
+            lane_id: "went-well".to_string(),
+            id: item.clone(),
+        };
+        let upvote_group = || Action::UpvoteGroup {
+            lane_id: "went-well".to_string(),
+            group_id: group.clone(),
+        };
+
+        apply_as("alice", upvote_group(), &mut board, &limits);
+        apply_as("alice", upvote_item(), &mut board, &limits);
+        assert_eq!(board.lanes["went-well"].groups[&group].vote_count, 0);
+        assert_eq!(board.lanes["went-well"].items[&item].vote_count, 1);
+
+        let settings = Action::UpdateSettings {
+            vote_on_groups: Some(true),
+            reactions: None,
+            team: None,
+            anonymous: None,
+        };
+        apply_as("alice", settings, &mut board, &limits);
+        apply_as("alice", upvote_item(), &mut board, &limits);
+        apply_as("alice", upvote_group(), &mut board, &limits);
+        apply_as("alice", upvote_group(), &mut board, &limits);
+        assert_eq!(board.lanes["went-well"].items[&item].vote_count, 1);
+        assert_eq!(board.lanes["went-well"].groups[&group].vote_count, 1);
+        assert_eq!(board.lanes["went-well"].group_votes(&group), 2);
+        assert_eq!(board.votes_cast.get("alice"), Some(&2));
+    }
+}
@@ -0,0 +1,402 @@
+//! HTTP API for action items: the items of action items lanes, with their
+//! assignee, due date and status. They can be listed and changed per board,
+//! or tracked across every board at once.
+
+use crate::action::{present, Action};
+use crate::redis_fanout::ApplyError;
+use crate::retroboard::{ActionDetails, ActionStatus, CardRef, RetroBoard};
+use crate::AppState;
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::{get, patch},
+    Json, Router,
+};
+use chrono::{NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::sync::Arc;
+
+/// Participant recorded for changes made through this API.
+const API_PARTICIPANT: &str = "api";
+
+#[derive(Serialize)]
+pub struct ActionItem<'a> {
+    pub lane_id: &'a str,
+    pub item_id: &'a str,
+    pub body: &'a str,
+    pub votes: u64,
+    #[serde(flatten)]
+    pub details: &'a ActionDetails,
+    /// Still open past its due date
+    pub overdue: bool,
+}
+
+/// Every action item of a board, lanes in board order and items in lane
+/// order. Those still open before `today` are overdue.
+pub fn action_items(board: &RetroBoard, today: NaiveDate) -> Vec<ActionItem<'_>> {
+    let mut all = Vec::new();
+    for (lane_id, lane) in &board.lanes {
+        let mut items: Vec<_> = lane
+            .items
+            .iter()
+            .filter_map(|(id, item)| Some((id, item, item.action.as_ref()?)))
+            .collect();
+        items.sort_by_key(|(_, item, _)| item.sort_order);
+        all.extend(items.into_iter().map(|(id, item, details)| ActionItem {
+            lane_id,
+            item_id: id,
+            body: &item.body,
+            votes: item.vote_count,
+            details,
+            overdue: details.is_overdue(today),
+        }));
+    }
+    all
+}
+
+pub fn routes() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/api/action-items", get(track))
+        .route("/api/action-items/{id}/{lane_id}/{item_id}", patch(update))
+        .route("/api/boards/{id}/action-items", get(list).post(create))
+        .route(
+            "/api/boards/{id}/action-items/{lane_id}/{item_id}",
+            patch(update),
+        )
+}
+
+/// An action item as tracked across boards.
+#[derive(Serialize)]
+struct Tracked<'a> {
+    board_id: &'a str,
+    board_title: &'a str,
+    team: Option<&'a str>,
+    #[serde(flatten)]
+    item: ActionItem<'a>,
+}
+
+/// Which action items to track. Every filter given must match.
+#[derive(Deserialize)]
+struct TrackParams {
+    /// Exact assignee, ignoring case
+    assignee: Option<String>,
+    status: Option<ActionStatus>,
+    /// Due on or after this day
+    due_from: Option<NaiveDate>,
+    /// Due on or before this day
+    due_to: Option<NaiveDate>,
+    /// Team of the board, ignoring case
+    team: Option<String>,
+    /// Only action items still open past their due date
+    #[serde(default)]
+    overdue: bool,
+}
+
+impl TrackParams {
+    fn matches(&self, tracked: &Tracked) -> bool {
+        let details = tracked.item.details;
+        let same = |wanted: &Option<String>, actual: Option<&str>| match wanted {
+            Some(wanted) => actual.is_some_and(|actual| actual.eq_ignore_ascii_case(wanted)),
+            None => true,
+        };
+        same(&self.assignee, details.assignee.as_deref())
+            && same(&self.team, tracked.team)
+            && self.status.is_none_or(|status| details.status == status)
+            && self
+                .due_from
+                .is_none_or(|from| details.due.is_some_and(|due| due >= from))
+            && self
+                .due_to
+                .is_none_or(|to| details.due.is_some_and(|due| due <= to))
+            && (!self.overdue || tracked.item.overdue)
+    }
+}
+
+/// Action items of every board, soonest due first and undated last. Items
+/// carried over into a later board are only listed there.
+async fn track(State(state): State<Arc<AppState>>, Query(params): Query<TrackParams>) -> Response {
+    let boards: Vec<_> = state
+        .boards
+        .ids()
+        .into_iter()
+        .filter_map(|id| Some((state.board(&id)?, id)))
+        .collect();
+    let boards: Vec<_> = boards
+        .iter()
+        .map(|(board, id)| (id.as_str(), board.read()))
+        .collect();
+
+    let carried: HashSet<_> = boards
+        .iter()
+        .flat_map(|(_, board)| board.lanes.values())
+        .flat_map(|lane| lane.items.values())
+        .filter_map(|item| item.action.as_ref()?.carried_from.as_ref())
+        .map(|origin| (&*origin.board_id, &*origin.lane_id, &*origin.item_id))
+        .collect();
+    let today = Utc::now().date_naive();
+    let mut tracked: Vec<_> = boards
+        .iter()
+        .flat_map(|(id, board)| {
+            action_items(board, today)
+                .into_iter()
+                .map(move |item| Tracked {
+                    board_id: id,
+                    board_title: &board.title,
+                    team: board.settings.team.as_deref(),
+                    item,
+                })
+        })
+        .filter(|tracked| {
+            !carried.contains(&(tracked.board_id, tracked.item.lane_id, tracked.item.item_id))
+        })
+        .filter(|tracked| params.matches(tracked))
+        .collect();
+    tracked.sort_by_key(|tracked| (tracked.item.details.due.is_none(), tracked.item.details.due));
+    Json(tracked).into_response()
+}
+
+#[derive(Deserialize)]
+struct NewActionItem {
+    body: String,
+    #[serde(default)]
+    assignee: Option<String>,
+    #[serde(default)]
+    due: Option<NaiveDate>,
+    #[serde(default)]
+    source: Option<CardRef>,
+}
+
+/// Fields to change. Those left out stay as they are, and `null` clears
+/// the assignee or due date.
+#[derive(Deserialize)]
+struct Changes {
+    #[serde(default, deserialize_with = "present")]
+    assignee: Option<Option<String>>,
+    #[serde(default, deserialize_with = "present")]
+    due: Option<Option<NaiveDate>>,
+    #[serde(default)]
+    status: Option<ActionStatus>,
+}
+
+#[derive(Serialize)]
+struct Errors {
+    errors: Vec<String>,
+}
+
+async fn list(State(state): State<Arc<AppState>>, Path(id): Path<String>) -> Response {
+    let Some(board) = state.board(&id) else {
+        return (StatusCode::NOT_FOUND, "No such board").into_response();
+    };
+    let board = board.read();
+    Json(action_items(&board, Utc::now().date_naive())).into_response()
+}
+
+async fn create(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+    Json(new): Json<NewActionItem>,
+) -> Response {
+    if state.board(&id).is_none() {
+        return (StatusCode::NOT_FOUND, "No such board").into_response();
+    }
+    let action = Action::AddActionItem {
+        body: new.body,
+        assignee: new.assignee,
+        due: new.due,
+        source: new.source,
+    };
+    submit(&state, &id, action, StatusCode::CREATED).await
+}
+
+async fn update(
+    State(state): State<Arc<AppState>>,
+    Path((id, lane_id, item_id)): Path<(String, String, String)>,
+    Json(changes): Json<Changes>,
+) -> Response {
+    let Some(board) = state.board(&id) else {
+        return (StatusCode::NOT_FOUND, "No such board").into_response();
+    };
+    let found = board
+        .read()
+        .lanes
+        .get(&lane_id)
+        .and_then(|lane| lane.items.get(&item_id))
+        .is_some_and(|item| item.action.is_some());
+    if !found {
+        return (StatusCode::NOT_FOUND, "No such action item").into_response();
+    }
+    let action = Action::UpdateActionItem {
+        lane_id,
+        item_id,
+        assignee: changes.assignee,
+        due: changes.due,
+        status: changes.status,
+    };
+    submit(&state, &id, action, StatusCode::NO_CONTENT).await
+}
+
+async fn submit(state: &AppState, id: &str, action: Action, success: StatusCode) -> Response {
+    match state
+        .submit_action(id, API_PARTICIPANT, false, action)
+        .await
+    {
+        Ok(()) => success.into_response(),
+        Err(ApplyError::Rejected(rejection)) => (
+            StatusCode::UNPROCESSABLE_ENTITY,
+            Json(Errors {
+                errors: vec![rejection.0],
+            }),
+        )
+            .into_response(),
+        Err(ApplyError::Redis(_)) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(Errors {
+                errors: vec!["the action item could not be stored".to_string()],
+            }),
+        )
+            .into_response(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::boards::DEFAULT_BOARD;
+    use crate::{router, test_app_state};
+    use axum::body::{to_bytes, Body};
+    use axum::http::Request;
+    use tower::ServiceExt;
+
+    async fn send(
+        state: Arc<AppState>,
+        method: &str,
+        uri: &str,
+        body: &str,
+    ) -> (StatusCode, String) {
+        let request = Request::builder()
+            .method(method)
+            .uri(uri)
+            .header("content-type", "application/json")
+            .body(Body::from(body.to_string()))
+            .unwrap();
+        let response = router(state).oneshot(request).await.unwrap();
+        let status = response.status();
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, String::from_utf8(body.to_vec()).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_create_update_and_list() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut board = RetroBoard::from_template("went-well").unwrap();
+        let card = board.add_item("to-improve", "Flaky tests").unwrap();
+        let state = Arc::new(test_app_state(board, dir.path().join("board.json")));
+        let uri = format!("/api/boards/{}/action-items", DEFAULT_BOARD);
+
+        let new = format!(
+            r#"{{"body": "Fix the flaky tests", "assignee": "alice", "due": "2026-03-20",
+                "source": {{"lane_id": "to-improve", "item_id": "{}"}}}}"#,
+            card
+        );
+        let (status, _) = send(state.clone(), "POST", &uri, &new).await;
+        assert_eq!(status, StatusCode::CREATED);
+
+        let (status, body) = send(state.clone(), "GET", &uri, "").await;
+        assert_eq!(status, StatusCode::OK);
+        let items: serde_json::Value = serde_json::from_str(&body).unwrap();
+        let item = &items[0];
+        assert_eq!(item["lane_id"], "action-items");
+        assert_eq!(item["assignee"], "alice");
+        assert_eq!(item["due"], "2026-03-20");
+        assert_eq!(item["status"], "open");
+        assert_eq!(item["source"]["item_id"], card.as_str());
+
+        let item_uri = format!("{}/action-items/{}", uri, item["item_id"].as_str().unwrap());
+        let changes = r#"{"assignee": null, "status": "in_progress"}"#;
+        let (status, _) = send(state.clone(), "PATCH", &item_uri, changes).await;
+        assert_eq!(status, StatusCode::NO_CONTENT);
+        let (_, body) = send(state.clone(), "GET", &uri, "").await;
+        let items: serde_json::Value = serde_json::from_str(&body).unwrap();
+        assert_eq!(items[0]["assignee"], serde_json::Value::Null);
+        assert_eq!(items[0]["due"], "2026-03-20");
+        assert_eq!(items[0]["status"], "in_progress");
+
+        let missing = format!("{}/action-items/missing", uri);
+        let (status, _) = send(state.clone(), "PATCH", &missing, "{}").await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        let missing_source = r#"{"body": "Lost", "source": {"lane_id": "x", "item_id": "y"}}"#;
+        let (status, _) = send(state, "POST", &uri, missing_source).await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
+    #[tokio::test]
+    async fn test_track_across_boards() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut first = RetroBoard::from_template("went-well").unwrap();
+        first.settings.team = Some("Platform".to_string());
+        let late = first.add_item("action-items", "Fix the build").unwrap();
+        first.add_item("action-items", "Pair more").unwrap();
+        let due = |day| NaiveDate::from_ymd_opt(2020, 1, day).map(Some);
+        let alice = Some(Some("Alice".to_string()));
+        first.update_action_item("action-items", &late, alice, due(1), None);
+
+        // Both are carried over, so they are only listed on the second board
+        let mut second = first.empty_copy("Sprint 2");
+        second.settings.team = Some("Mobile".to_string());
+        second.carry_over_action_items(DEFAULT_BOARD, &first);
+        let docs = first.add_item("action-items", "Write docs").unwrap();
+        first.update_action_item("action-items", &docs, None, due(2), None);
+        let state = Arc::new(test_app_state(first, dir.path().join("board.json")));
+        state.boards.insert("sprint-2", second);
+
+        let list = |query: &str| {
+            let state = state.clone();
+            let uri = format!("/api/action-items{}", query);
+            async move {
+                let (status, body) = send(state, "GET", &uri, "").await;
+                assert_eq!(status, StatusCode::OK);
+                serde_json::from_str::<Vec<serde_json::Value>>(&body).unwrap()
+            }
+        };
+
+        let all = list("").await;
+        let bodies: Vec<_> = all
+            .iter()
+            .map(|item| item["body"].as_str().unwrap())
+            .collect();
+        // Soonest due first, undated last
+        assert_eq!(bodies, ["Fix the build", "Write docs", "Pair more"]);
+        assert_eq!(all[0]["board_id"], "sprint-2");
+        assert_eq!(all[0]["board_title"], "Sprint 2");
+        assert_eq!(all[0]["team"], "Mobile");
+        assert_eq!(all[0]["overdue"], true);
+        assert_eq!(all[1]["board_id"], DEFAULT_BOARD);
+        assert_eq!(all[2]["overdue"], false);
+
+        assert_eq!(list("?overdue=true").await.len(), 2);
+        assert_eq!(list("?team=platform").await.len(), 1);
+        assert_eq!(
+            list("?due_from=2019-12-01&due_to=2020-01-01").await.len(),
+            1
+        );
+        let alices = list("?assignee=alice&team=Mobile").await;
+        assert_eq!(alices.len(), 1);
+        assert_eq!(alices[0]["body"], "Fix the build");
+
+        // Status changes without going through the board
+        let item = &alices[0];
+        let uri = format!(
+            "/api/action-items/{}/{}/{}",
+            item["board_id"].as_str().unwrap(),
+            item["lane_id"].as_str().unwrap(),
+            item["item_id"].as_str().unwrap()
+        );
+        let (status, _) = send(state.clone(), "PATCH", &uri, r#"{"status": "done"}"#).await;
+        assert_eq!(status, StatusCode::NO_CONTENT);
+        let done = list("?status=done").await;
+        assert_eq!(done.len(), 1);
+        assert_eq!(done[0]["overdue"], false);
+    }
+}
@@ -0,0 +1,404 @@
+//! The boards served by this instance.
+//!
+//! Every board has its own broadcast channel, so clients only receive the
+//! snapshots of the board they joined. The board that existed before boards
+//! had IDs is served as `DEFAULT_BOARD`.
+
+use crate::retroboard::RetroBoard;
+use crate::schema;
+use crate::AppState;
+use axum::{
+    extract::State,
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::get,
+    Json, Router,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard};
+use tokio::sync::broadcast;
+
+pub const DEFAULT_BOARD: &str = "default";
+
+/// Longest board ID accepted, so IDs stay usable as file names and keys.
+const MAX_ID_LENGTH: usize = 64;
+
+/// Whether `id` may name a board: ASCII letters, digits, `-` and `_`.
+pub fn is_valid_id(id: &str) -> bool {
+    !id.is_empty()
+        && id.len() <= MAX_ID_LENGTH
+        && id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+pub fn new_id() -> String {
+    uuid::Uuid::new_v4().to_string()
+}
+
+/// Read every `<id>.json` board in `dir`, skipping files that fail to load.
+pub fn load_dir(dir: &Path) -> Vec<(String, RetroBoard)> {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Vec::new(),
+        Err(e) => {
+            tracing::error!("Failed to read boards from {}: {}", dir.display(), e);
+            return Vec::new();
+        }
+    };
+
+    let mut boards = Vec::new();
+    for path in entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+    {
+        let id = match (path.file_stem(), path.extension()) {
+            (Some(stem), Some(ext)) if ext == "json" => stem.to_string_lossy().into_owned(),
+            _ => continue,
+        };
+        if !is_valid_id(&id) {
+            continue;
+        }
+        let loaded = std::fs::read_to_string(&path)
+            .map_err(|e| e.to_string())
+            .and_then(|json| schema::from_json(&json).map_err(|e| e.to_string()));
+        match loaded {
+            Ok(board) => boards.push((id, board)),
+            Err(e) => tracing::error!("Skipping board {}: {}", path.display(), e),
+        }
+    }
+    boards
+}
+
+pub struct Board {
+    board: RwLock<RetroBoard>,
+    pub tx: broadcast::Sender<String>,
+}
+
+impl Board {
+    fn new(board: RetroBoard, capacity: usize) -> Self {
+        let (tx, _rx) = broadcast::channel(capacity);
+        Self {
+            board: RwLock::new(board),
+            tx,
+        }
+    }
+
+    /// Acquire a read lock on the board, recovering from a poisoned lock.
+    pub fn read(&self) -> RwLockReadGuard<'_, RetroBoard> {
+        match self.board.read() {
+            Ok(guard) => guard,
+            Err(poisoned) => {
+                tracing::error!("Board RwLock was poisoned — recovering");
+                poisoned.into_inner()
+            }
+        }
+    }
+
+    /// Acquire a write lock on the board, recovering from a poisoned lock.
+    pub fn write(&self) -> RwLockWriteGuard<'_, RetroBoard> {
+        match self.board.write() {
+            Ok(guard) => guard,
+            Err(poisoned) => {
+                tracing::error!("Board RwLock was poisoned — recovering");
+                poisoned.into_inner()
+            }
+        }
+    }
+}
+
+pub struct Boards {
+    boards: RwLock<HashMap<String, Arc<Board>>>,
+    /// Snapshots buffered per client before it starts missing them
+    capacity: usize,
+}
+
+impl Boards {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            boards: RwLock::new(HashMap::new()),
+            capacity,
+        }
+    }
+
+    pub fn get(&self, id: &str) -> Option<Arc<Board>> {
+        self.map().get(id).cloned()
+    }
+
+    /// IDs of every board, sorted.
+    pub fn ids(&self) -> Vec<String> {
+        let mut ids: Vec<_> = self.map().keys().cloned().collect();
+        ids.sort();
+        ids
+    }
+
+    /// Store `board` under `id`, replacing the contents of an existing board
+    /// in place so that its connected clients stay subscribed.
+    pub fn insert(&self, id: &str, board: RetroBoard) -> Arc<Board> {
+        let mut boards = match self.boards.write() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        if let Some(existing) = boards.get(id).cloned() {
+            drop(boards);
+            *existing.write() = board;
+            return existing;
+        }
+        let created = Arc::new(Board::new(board, self.capacity));
+        boards.insert(id.to_string(), created.clone());
+        created
+    }
+
+    fn map(&self) -> RwLockReadGuard<'_, HashMap<String, Arc<Board>>> {
+        match self.boards.read() {
+            Ok(guard) => guard,
+            Err(poisoned) => poisoned.into_inner(),
+        }
+    }
+}
+
+pub fn routes() -> Router<Arc<AppState>> {
+    Router::new().route("/api/boards", get(list).post(create))
+}
+
+#[derive(Serialize)]
+struct BoardSummary {
+    id: String,
+    title: String,
+}
+
+#[derive(Deserialize)]
+struct NewBoard {
+    title: Option<String>,
+    /// Built-in template to start from. Defaults to the lanes of the
+    /// previous board, or the configured default template.
+    template: Option<String>,
+    /// Board of the previous retro, whose open action items are carried over
+    previous: Option<String>,
+    /// Team holding the retro. Defaults to the team of the previous board.
+    team: Option<String>,
+}
+
+#[derive(Serialize)]
+struct Created {
+    id: String,
+    title: String,
+    /// Open action items carried over from the previous board
+    carried: usize,
+}
+
+#[derive(Serialize)]
+struct Errors {
+    errors: Vec<String>,
+}
+
+fn unprocessable(error: String) -> Response {
+    (
+        StatusCode::UNPROCESSABLE_ENTITY,
+        Json(Errors {
+            errors: vec![error],
+        }),
+    )
+        .into_response()
+}
+
+async fn create(State(state): State<Arc<AppState>>, Json(new): Json<NewBoard>) -> Response {
+    let previous = match &new.previous {
+        Some(id) => match state.board(id) {
+            Some(board) => Some((id, board)),
+            None => return unprocessable(format!("board '{}' does not exist", id)),
+        },
+        None => None,
+    };
+    let title = new
+        .title
+        .as_deref()
+        .map(str::trim)
+        .unwrap_or("My Retro Board");
+    if title.is_empty() {
+        return unprocessable("title is empty".to_string());
+    }
+    if title.chars().count() > state.limits.max_body_length {
+        return unprocessable(format!(
+            "title is longer than {} characters",
+            state.limits.max_body_length
+        ));
+    }
+
+    let mut board = match (&new.template, &previous) {
+        (Some(template), _) => match RetroBoard::from_template(template) {
+            Some(board) => board,
+            None => return unprocessable(format!("unknown template '{}'", template)),
+        },
+        (None, Some((_, previous))) => previous.read().empty_copy(title),
+        (None, None) => RetroBoard::from_template(&state.default_template).unwrap(),
+    };
+    board.title = title.to_string();
+    let team = new.team.or_else(|| {
+        let (_, previous) = previous.as_ref()?;
+        previous.read().settings.team.clone()
+    });
+    if team
+        .as_ref()
+        .is_some_and(|team| team.chars().count() > state.limits.max_body_length)
+    {
+        return unprocessable(format!(
+            "team is longer than {} characters",
+            state.limits.max_body_length
+        ));
+    }
+    board.settings.team = team;
+    let carried = match &previous {
+        Some((id, previous)) => board.carry_over_action_items(id, &previous.read()),
+        None => 0,
+    };
+    if board.lanes.len() > state.limits.max_lanes {
+        return unprocessable(format!(
+            "board would have {} lanes, at most {} are allowed",
+            board.lanes.len(),
+            state.limits.max_lanes
+        ));
+    }
+
+    let title = board.title.clone();
+    match state.create_board(board).await {
+        Some(id) => (StatusCode::CREATED, Json(Created { id, title, carried })).into_response(),
+        None => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(Errors {
+                errors: vec!["the board could not be stored".to_string()],
+            }),
+        )
+            .into_response(),
+    }
+}
+
+async fn list(State(state): State<Arc<AppState>>) -> Json<Vec<BoardSummary>> {
+    let boards = state
+        .boards
+        .ids()
+        .into_iter()
+        .filter_map(|id| {
+            let title = state.board(&id)?.read().title.clone();
+            Some(BoardSummary { id, title })
+        })
+        .collect();
+    Json(boards)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_valid_ids() {
+        assert!(is_valid_id(DEFAULT_BOARD));
+        assert!(is_valid_id(&new_id()));
+        assert!(is_valid_id("sprint_42-retro"));
+        assert!(!is_valid_id(""));
+        assert!(!is_valid_id("../etc/passwd"));
+        assert!(!is_valid_id("with space"));
+        assert!(!is_valid_id(&"a".repeat(MAX_ID_LENGTH + 1)));
+    }
+
+    #[test]
+    fn test_insert_replaces_in_place() {
+        let boards = Boards::new(10);
+        let first = boards.insert("a", RetroBoard::new("First"));
+        let mut rx = first.tx.subscribe();
+
+        let second = boards.insert("a", RetroBoard::new("Second"));
+        assert!(Arc::ptr_eq(&first, &second));
+        assert_eq!(boards.get("a").unwrap().read().title, "Second");
+
+        // Subscribers of the replaced board still receive its broadcasts
+        second.tx.send("snapshot".to_string()).unwrap();
+        assert_eq!(rx.try_recv().unwrap(), "snapshot");
+    }
+
+    #[test]
+    fn test_load_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        RetroBoard::new("Saved")
+            .save_to_file(dir.path().join("saved.json"))
+            .unwrap();
+        std::fs::write(dir.path().join("broken.json"), "{").unwrap();
+        std::fs::write(dir.path().join("notes.txt"), "").unwrap();
+
+        let boards = load_dir(dir.path());
+        assert_eq!(boards.len(), 1);
+        assert_eq!(boards[0].0, "saved");
+        assert_eq!(boards[0].1.title, "Saved");
+
+        assert!(load_dir(&dir.path().join("missing")).is_empty());
+    }
+
+    #[test]
+    fn test_ids_are_sorted() {
+        let boards = Boards::new(10);
+        boards.insert("b", RetroBoard::new("B"));
+        boards.insert("a", RetroBoard::new("A"));
+        assert_eq!(boards.ids(), ["a", "b"]);
+        assert!(boards.get("c").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_create_from_previous_board() {
+        use crate::{router, test_app_state};
+        use axum::body::{to_bytes, Body};
+        use axum::http::Request;
+        use tower::ServiceExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let mut previous = RetroBoard::from_template("mad-sad-glad").unwrap();
+        previous.add_item("action-items", "Fix the build");
+        let state = Arc::new(test_app_state(previous, dir.path().join("board.json")));
+
+        let create = |body: &str| {
+            Request::builder()
+                .method("POST")
+                .uri("/api/boards")
+                .header("content-type", "application/json")
+                .body(Body::from(body.to_string()))
+                .unwrap()
+        };
+        let body = format!(
+            r#"{{"title": "Sprint 2", "previous": "{}"}}"#,
+            DEFAULT_BOARD
+        );
+        let response = router(state.clone()).oneshot(create(&body)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let created: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(created["title"], "Sprint 2");
+        assert_eq!(created["carried"], 1);
+
+        // The lanes of the previous board, after a review of its action items
+        {
+            let board = state.board(created["id"].as_str().unwrap()).unwrap();
+            let board = board.read();
+            let lanes: Vec<_> = board.lanes.keys().collect();
+            assert_eq!(
+                lanes,
+                ["action-review", "mad", "sad", "glad", "action-items"]
+            );
+            assert!(board.lanes["action-items"].items.is_empty());
+            let carried = board.lanes["action-review"].items.values().next().unwrap();
+            assert_eq!(carried.body, "Fix the build");
+            let origin = carried.action.as_ref().unwrap().carried_from.as_ref();
+            assert_eq!(origin.unwrap().board_id, DEFAULT_BOARD);
+        }
+
+        let missing = r#"{"previous": "missing"}"#;
+        let response = router(state.clone())
+            .oneshot(create(missing))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        let unknown = r#"{"template": "unknown"}"#;
+        let response = router(state).oneshot(create(unknown)).await.unwrap();
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    }
+}
@@ -0,0 +1,613 @@
+//! Importing cards collected outside the app, from CSV or Markdown.
+//!
+//! CSV has a `lane`, `body` and optional `votes` column, either in that
+//! order or named in a header row, so CSV exports can be imported again.
+//! Markdown has one heading per lane and a bullet per item, as in the
+//! Markdown export. Cards are matched to lanes by title.
+
+use crate::action::{Action, ActionContext};
+use crate::config::Limits;
+use crate::export::Format;
+use crate::redis_fanout::ApplyError;
+use crate::retroboard::RetroBoard;
+use crate::AppState;
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::post,
+    Json, Router,
+};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// An item to add to the lane titled `lane`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Card {
+    pub lane: String,
+    pub body: String,
+    #[serde(default)]
+    pub votes: u64,
+}
+
+/// The contents of an imported file.
+#[derive(Debug, Default)]
+pub struct Parsed {
+    /// Board title, from a Markdown heading above the lanes
+    pub title: Option<String>,
+    /// Lane titles in the order they first appear, including empty lanes
+    pub lanes: Vec<String>,
+    pub cards: Vec<Card>,
+}
+
+/// Parse cards from CSV or Markdown, reporting every problem found.
+pub fn parse(text: &str, format: Format) -> Result<Parsed, Vec<String>> {
+    match format {
+        Format::Csv => parse_csv(text),
+        Format::Markdown => parse_markdown(text),
+        _ => Err(vec![
+            "cards can only be imported from csv or markdown".to_string()
+        ]),
+    }
+}
+
+/// Build a new board holding the parsed lanes and cards.
+pub fn to_board(
+    parsed: Parsed,
+    title: Option<&str>,
+    limits: &Limits,
+) -> Result<RetroBoard, String> {
+    let title = title
+        .or(parsed.title.as_deref())
+        .unwrap_or("Imported board");
+    let mut board = RetroBoard::new(title);
+    for lane in &parsed.lanes {
+        if board.lane_id_by_title(lane).is_none() {
+            board.add_imported_lane(lane);
+        }
+    }
+    let ctx = ActionContext {
+        participant: "import",
+        facilitator: false,
+        limits,
+    };
+    Action::AddCards {
+        cards: parsed.cards,
+        create_lanes: true,
+    }
+    .apply(&mut board, &ctx)
+    .map_err(|rejection| rejection.0)?;
+    Ok(board)
+}
+
+fn parse_csv(text: &str) -> Result<Parsed, Vec<String>> {
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .from_reader(text.as_bytes());
+    let mut parsed = Parsed::default();
+    let mut errors = Vec::new();
+    // Columns of lane, body and votes
+    let mut columns = None;
+
+    for (index, record) in reader.records().enumerate() {
+        let record = match record {
+            Ok(record) => record,
+            Err(e) => {
+                errors.push(e.to_string());
+                break;
+            }
+        };
+        let line = record
+            .position()
+            .map_or(index + 1, |pos| pos.line() as usize);
+        let column = |name: &str| {
+            record
+                .iter()
+                .position(|field| field.trim().eq_ignore_ascii_case(name))
+        };
+
+        let (lane, body, votes) = match columns {
+            Some(columns) => columns,
+            None if index == 0 && column("lane").is_some() && column("body").is_some() => {
+                columns = Some((
+                    column("lane").unwrap(),
+                    column("body").unwrap(),
+                    column("votes"),
+                ));
+                continue;
+            }
+            None if record.len() == 2 || record.len() == 3 => (0, 1, Some(2)),
+            None => {
+                errors.push(format!(
+                    "line {}: expected lane, body and optionally votes",
+                    line
+                ));
+                continue;
+            }
+        };
+
+        let lane = record.get(lane).unwrap_or("").trim();
+        let body = record.get(body).unwrap_or("").trim();
+        let votes = votes
+            .and_then(|votes| record.get(votes))
+            .unwrap_or("")
+            .trim();
+        if lane.is_empty() {
+            errors.push(format!("line {}: lane is empty", line));
+        }
+        if body.is_empty() {
+            errors.push(format!("line {}: body is empty", line));
+        }
+        let votes = if votes.is_empty() {
+            0
+        } else {
+            votes.parse().unwrap_or_else(|_| {
+                errors.push(format!("line {}: votes '{}' is not a number", line, votes));
+                0
+            })
+        };
+        if !parsed
+            .lanes
+            .iter()
+            .any(|known| known.eq_ignore_ascii_case(lane))
+        {
+            parsed.lanes.push(lane.to_string());
+        }
+        parsed.cards.push(Card {
+            lane: lane.to_string(),
+            body: body.to_string(),
+            votes,
+        });
+    }
+
+    if parsed.cards.is_empty() && errors.is_empty() {
+        errors.push("no cards found".to_string());
+    }
+    if errors.is_empty() {
+        Ok(parsed)
+    } else {
+        Err(errors)
+    }
+}
+
+fn parse_markdown(text: &str) -> Result<Parsed, Vec<String>> {
+    let levels: Vec<usize> = text
+        .lines()
+        .filter_map(heading)
+        .map(|(level, _)| level)
+        .collect();
+    // A single top level heading above the others titles the board
+    let lane_level = match levels.iter().min() {
+        Some(1) if levels.iter().filter(|level| **level == 1).count() == 1 => levels
+            .iter()
+            .copied()
+            .filter(|level| *level > 1)
+            .min()
+            .unwrap_or(1),
+        Some(min) => *min,
+        None => return Err(vec!["no lane headings found".to_string()]),
+    };
+
+    let mut parsed = Parsed::default();
+    let mut errors = Vec::new();
+    let mut lane: Option<String> = None;
+    // The item being read, with the line it started on
+    let mut item: Option<(usize, Card)> = None;
+
+    for (index, line) in text.lines().enumerate() {
+        let number = index + 1;
+        if let Some((level, title)) = heading(line) {
+            finish_item(&mut item, &mut parsed, &mut errors);
+            if level == lane_level {
+                parsed.lanes.push(title.to_string());
+                lane = Some(title.to_string());
+            } else if level < lane_level && parsed.title.is_none() {
+                parsed.title = Some(title.to_string());
+            }
+        } else if let Some(body) = bullet(line) {
+            finish_item(&mut item, &mut parsed, &mut errors);
+            match &lane {
+                Some(lane) => {
+                    let card = Card {
+                        lane: lane.clone(),
+                        body: body.to_string(),
+                        votes: 0,
+                    };
+                    item = Some((number, card));
+                }
+                None => errors.push(format!(
+                    "line {}: item before the first lane heading",
+                    number
+                )),
+            }
+        } else if let Some((_, card)) = item
+            .as_mut()
+            .filter(|_| line.starts_with([' ', '\t']) && !line.trim().is_empty())
+        {
+            // Indented lines continue the item above
+            card.body.push('\n');
+            card.body.push_str(line.trim());
+        } else {
+            finish_item(&mut item, &mut parsed, &mut errors);
+        }
+    }
+    finish_item(&mut item, &mut parsed, &mut errors);
+
+    if errors.is_empty() {
+        Ok(parsed)
+    } else {
+        Err(errors)
+    }
+}
+
+fn finish_item(item: &mut Option<(usize, Card)>, parsed: &mut Parsed, errors: &mut Vec<String>) {
+    let Some((line, mut card)) = item.take() else {
+        return;
+    };
+    let (body, votes) = split_votes(&card.body);
+    if body.is_empty() {
+        errors.push(format!("line {}: item is empty", line));
+        return;
+    }
+    card.body = body.to_string();
+    card.votes = votes;
+    parsed.cards.push(card);
+}
+
+/// Level and title of a Markdown heading line.
+fn heading(line: &str) -> Option<(usize, &str)> {
+    let line = line.trim_start();
+    let level = line.chars().take_while(|c| *c == '#').count();
+    let rest = &line[level..];
+    if !(1..=6).contains(&level) || !(rest.is_empty() || rest.starts_with(' ')) {
+        return None;
+    }
+    let title = rest.trim().trim_end_matches('#').trim();
+    (!title.is_empty()).then_some((level, title))
+}
+
+/// Text of a top level list item, without a task checkbox.
+fn bullet(line: &str) -> Option<&str> {
+    let trimmed = line.trim_start();
+    if line.len() - trimmed.len() > 1 {
+        return None;
+    }
+    let rest = if let Some(rest) = trimmed
+        .strip_prefix("- ")
+        .or_else(|| trimmed.strip_prefix("* "))
+        .or_else(|| trimmed.strip_prefix("+ "))
+    {
+        rest
+    } else {
+        let digits = trimmed.chars().take_while(char::is_ascii_digit).count();
+        let rest = &trimmed[digits..];
+        if digits == 0 {
+            return None;
+        }
+        rest.strip_prefix(". ")
+            .or_else(|| rest.strip_prefix(") "))?
+    };
+    let rest = ["[ ] ", "[x] ", "[X] "]
+        .iter()
+        .find_map(|checkbox| rest.strip_prefix(checkbox))
+        .unwrap_or(rest);
+    Some(rest.trim())
+}
+
+/// Split a trailing vote count such as "(3 votes)", as written by the
+/// Markdown export, from an item's text.
+fn split_votes(body: &str) -> (&str, u64) {
+    let body = body.trim();
+    let count = body
+        .strip_suffix(" votes)")
+        .or_else(|| body.strip_suffix(" vote)"))
+        .and_then(|rest| rest.rsplit_once('('))
+        .and_then(|(text, count)| Some((text, count.parse().ok()?)));
+    match count {
+        Some((text, count)) => (text.trim(), count),
+        None => (body, 0),
+    }
+}
+
+pub fn routes() -> Router<Arc<AppState>> {
+    Router::new().route("/api/boards/{id}/cards", post(import_cards))
+}
+
+#[derive(Deserialize)]
+struct CardParams {
+    format: Format,
+    /// Create lanes no existing lane title matches
+    #[serde(default)]
+    create_lanes: bool,
+}
+
+#[derive(Serialize)]
+struct Added {
+    added: usize,
+}
+
+#[derive(Serialize)]
+struct Errors {
+    errors: Vec<String>,
+}
+
+fn unprocessable(errors: Vec<String>) -> Response {
+    (StatusCode::UNPROCESSABLE_ENTITY, Json(Errors { errors })).into_response()
+}
+
+/// Add cards to an existing board.
+async fn import_cards(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+    Query(params): Query<CardParams>,
+    body: String,
+) -> Response {
+    if state.board(&id).is_none() {
+        return (StatusCode::NOT_FOUND, "No such board").into_response();
+    }
+    let parsed = match parse(&body, params.format) {
+        Ok(parsed) => parsed,
+        Err(errors) => return unprocessable(errors),
+    };
+
+    let added = parsed.cards.len();
+    let action = Action::AddCards {
+        cards: parsed.cards,
+        create_lanes: params.create_lanes,
+    };
+    match state.submit_action(&id, "import", false, action).await {
+        Ok(()) => Json(Added { added }).into_response(),
+        Err(ApplyError::Rejected(rejection)) => unprocessable(vec![rejection.0]),
+        Err(ApplyError::Redis(_)) => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(Errors {
+                errors: vec!["the cards could not be stored".to_string()],
+            }),
+        )
+            .into_response(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::boards::DEFAULT_BOARD;
+    use crate::export::{to_markdown, ItemOrder};
+    use crate::{router, test_app_state};
+    use axum::body::{to_bytes, Body};
+    use axum::http::Request;
+    use tower::ServiceExt;
+
+    fn card(lane: &str, body: &str, votes: u64) -> Card {
+        Card {
+            lane: lane.to_string(),
+            body: body.to_string(),
+            votes,
+        }
+    }
+
+    #[test]
+    fn test_parse_csv() {
+        let parsed = parse_csv("Went Well,Deploys\nTo Improve,\"Flaky, slow tests\",3\n").unwrap();
+        assert_eq!(parsed.lanes, ["Went Well", "To Improve"]);
+        assert_eq!(
+            parsed.cards,
+            [
+                card("Went Well", "Deploys", 0),
+                card("To Improve", "Flaky, slow tests", 3),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_csv_with_header() {
+        // The header of the CSV export
+        let csv = "board_id,board,lane,body,votes,sort_order,merged\n\
+                   default,Retro,Went Well,Deploys,2,0,\n";
+        let parsed = parse_csv(csv).unwrap();
+        assert_eq!(parsed.cards, [card("Went Well", "Deploys", 2)]);
+    }
+
+    #[test]
+    fn test_csv_errors() {
+        let errors = parse_csv("Went Well\nWent Well,,x\n,Body\n").unwrap_err();
+        assert_eq!(
+            errors,
+            [
+                "line 1: expected lane, body and optionally votes",
+                "line 2: body is empty",
+                "line 2: votes 'x' is not a number",
+                "line 3: lane is empty",
+            ]
+        );
+        assert_eq!(parse_csv("").unwrap_err(), ["no cards found"]);
+    }
+
+    #[test]
+    fn test_parse_markdown() {
+        let markdown = "\
+# Sprint 43
+
+Notes from the doc.
+
+## Went Well
+
+- Deploys
+* Pairing
+  on the release
+1. Numbered
+
+## To Improve
+
+## Action Items
+- [ ] Fix the flaky tests
+- [x] Done already (2 votes)
+";
+        let parsed = parse_markdown(markdown).unwrap();
+        assert_eq!(parsed.title.as_deref(), Some("Sprint 43"));
+        assert_eq!(parsed.lanes, ["Went Well", "To Improve", "Action Items"]);
+        assert_eq!(
+            parsed.cards,
+            [
+                card("Went Well", "Deploys", 0),
+                card("Went Well", "Pairing\non the release", 0),
+                card("Went Well", "Numbered", 0),
+                card("Action Items", "Fix the flaky tests", 0),
+                card("Action Items", "Done already", 2),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_markdown_export_round_trip() {
+        let mut board = RetroBoard::from_template("went-well").unwrap();
+        board.title = "Sprint 42".to_string();
+        let id = board
+            .add_item("went-well", "Pairing\non the release")
+            .unwrap();
+        board.upvote_item("went-well", &id);
+        board.add_item("action-items", "Fix the flaky tests");
+
+        let parsed = parse_markdown(&to_markdown(&board, ItemOrder::Board)).unwrap();
+        let imported = to_board(parsed, None, &Limits::default()).unwrap();
+        assert_eq!(imported.title, "Sprint 42");
+        assert_eq!(
+            to_markdown(&imported, ItemOrder::Board),
+            to_markdown(&board, ItemOrder::Board)
+        );
+    }
+
+    #[test]
+    fn test_markdown_errors() {
+        assert_eq!(
+            parse_markdown("- Loose\n").unwrap_err(),
+            ["no lane headings found"]
+        );
+        assert_eq!(
+            parse_markdown("- Loose\n# Lane\n- [ ] \n").unwrap_err(),
+            [
+                "line 1: item before the first lane heading",
+                "line 3: item is empty"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_split_votes() {
+        assert_eq!(split_votes("Deploys (1 vote)"), ("Deploys", 1));
+        assert_eq!(split_votes("Deploys (12 votes)"), ("Deploys", 12));
+        assert_eq!(
+            split_votes("Deploys (many votes)"),
+            ("Deploys (many votes)", 0)
+        );
+        assert_eq!(split_votes("Deploys"), ("Deploys", 0));
+    }
+
+    #[test]
+    fn test_lanes_are_matched_by_title() {
+        let mut board = RetroBoard::from_template("went-well").unwrap();
+        let limits = Limits::default();
+        let ctx = ActionContext {
+            participant: "import",
+            facilitator: false,
+            limits: &limits,
+        };
+
+        let missing = Action::AddCards {
+            cards: vec![card("went well", "Deploys", 1), card("Kudos", "Thanks", 0)],
+            create_lanes: false,
+        };
+        let rejection = missing.clone().apply(&mut board, &ctx).err().unwrap();
+        assert_eq!(rejection.0, "no lane is titled 'Kudos'");
+        assert!(board.lanes["went-well"].items.is_empty());
+
+        let Action::AddCards { cards, .. } = missing else {
+            unreachable!()
+        };
+        Action::AddCards {
+            cards,
+            create_lanes: true,
+        }
+        .apply(&mut board, &ctx)
+        .unwrap();
+        let item = board.lanes["went-well"].items.values().next().unwrap();
+        assert_eq!((item.body.as_str(), item.vote_count), ("Deploys", 1));
+        assert_eq!(board.lanes["kudos"].items.len(), 1);
+    }
+
+    #[test]
+    fn test_cards_respect_limits() {
+        let mut board = RetroBoard::from_template("went-well").unwrap();
+        let limits = Limits {
+            max_items_per_lane: 1,
+            ..Limits::default()
+        };
+        let ctx = ActionContext {
+            participant: "import",
+            facilitator: false,
+            limits: &limits,
+        };
+        let rejection = Action::AddCards {
+            cards: vec![card("Went Well", "One", 0), card("Went Well", "Two", 0)],
+            create_lanes: false,
+        }
+        .apply(&mut board, &ctx)
+        .err()
+        .unwrap();
+        assert_eq!(
+            rejection.0,
+            "lane 'went-well' would have 2 items, at most 1 are allowed"
+        );
+    }
+
+    async fn post(uri: &str, body: &str) -> (StatusCode, serde_json::Value, Arc<AppState>) {
+        let dir = tempfile::tempdir().unwrap();
+        let state = Arc::new(test_app_state(
+            RetroBoard::from_template("went-well").unwrap(),
+            dir.path().join("retroboard.json"),
+        ));
+        let response = router(state.clone())
+            .oneshot(
+                Request::post(uri)
+                    .body(Body::from(body.to_string()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        let status = response.status();
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body = serde_json::from_slice(&body).unwrap_or_default();
+        (status, body, state)
+    }
+
+    #[tokio::test]
+    async fn test_import_into_existing_board() {
+        let (status, body, state) = post(
+            "/api/boards/default/cards?format=csv&create_lanes=true",
+            "Went Well,Deploys,2\nKudos,Thanks\n",
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["added"], 2);
+        let board = state.board(DEFAULT_BOARD).unwrap();
+        let board = board.read();
+        assert_eq!(board.lanes["went-well"].items.len(), 1);
+        assert_eq!(board.lanes["kudos"].title, "Kudos");
+    }
+
+    #[tokio::test]
+    async fn test_import_reports_missing_lanes() {
+        let (status, body, state) = post(
+            "/api/boards/default/cards?format=markdown",
+            "# Kudos\n- Thanks\n",
+        )
+        .await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(body["errors"][0], "no lane is titled 'Kudos'");
+        assert_eq!(state.board(DEFAULT_BOARD).unwrap().read().lanes.len(), 3);
+
+        let (status, _, _) = post("/api/boards/missing/cards?format=csv", "A,B\n").await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+}
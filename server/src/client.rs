@@ -0,0 +1,188 @@
+//! Serving the compiled web client.
+//!
+//! The SvelteKit static build is read from a directory, so one binary can run
+//! the whole app. Paths that match no file fall back to `index.html` for the
+//! client-side router, except under `/_app` where a missing asset is a 404.
+
+use axum::{
+    extract::Request,
+    http::{header, HeaderValue},
+    middleware::{self, Next},
+    response::Response,
+    Router,
+};
+use std::path::Path;
+use tower_http::services::{ServeDir, ServeFile};
+
+/// SvelteKit puts content-hashed assets here, so they never change.
+const IMMUTABLE_PREFIX: &str = "/_app/immutable/";
+
+const CONTENT_SECURITY_POLICY: &str = "default-src 'self'; \
+    script-src 'self' 'unsafe-inline'; \
+    style-src 'self' 'unsafe-inline'; \
+    img-src 'self' data: blob:; \
+    connect-src 'self' ws: wss:; \
+    frame-ancestors 'none'";
+
+pub fn routes(dir: &Path) -> Router {
+    let index = ServeFile::new(dir.join("index.html"))
+        .precompressed_br()
+        .precompressed_gzip();
+    let assets = ServeDir::new(dir.join("_app"))
+        .precompressed_br()
+        .precompressed_gzip();
+    let pages = ServeDir::new(dir)
+        .precompressed_br()
+        .precompressed_gzip()
+        .fallback(index);
+
+    Router::new()
+        .nest_service("/_app", assets)
+        .fallback_service(pages)
+        .layer(middleware::from_fn(set_headers))
+}
+
+async fn set_headers(request: Request, next: Next) -> Response {
+    let cache_control = if request.uri().path().starts_with(IMMUTABLE_PREFIX) {
+        "public, max-age=31536000, immutable"
+    } else {
+        // Pages and unhashed files must be revalidated to pick up new builds
+        "no-cache"
+    };
+
+    let mut response = next.run(request).await;
+    let headers = response.headers_mut();
+    headers.insert(
+        header::CACHE_CONTROL,
+        HeaderValue::from_static(cache_control),
+    );
+    headers.insert(header::VARY, HeaderValue::from_static("Accept-Encoding"));
+    headers.insert(
+        header::X_CONTENT_TYPE_OPTIONS,
+        HeaderValue::from_static("nosniff"),
+    );
+    headers.insert(header::X_FRAME_OPTIONS, HeaderValue::from_static("DENY"));
+    headers.insert(
+        header::REFERRER_POLICY,
+        HeaderValue::from_static("strict-origin-when-cross-origin"),
+    );
+    headers.insert(
+        header::CONTENT_SECURITY_POLICY,
+        HeaderValue::from_static(CONTENT_SECURITY_POLICY),
+    );
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::retroboard::RetroBoard;
+    use crate::{router, test_app_state};
+    use axum::body::{to_bytes, Body};
+    use axum::http::StatusCode;
+    use std::sync::Arc;
+    use tempfile::TempDir;
+    use tower::ServiceExt;
+
+    fn build_dir() -> TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        let immutable = dir.path().join("_app/immutable");
+        std::fs::create_dir_all(&immutable).unwrap();
+        std::fs::write(dir.path().join("index.html"), "<html>app</html>").unwrap();
+        std::fs::write(dir.path().join("favicon.png"), "png").unwrap();
+        std::fs::write(immutable.join("start.abc123.js"), "console.log(1)").unwrap();
+        std::fs::write(immutable.join("start.abc123.js.gz"), "gzipped").unwrap();
+        dir
+    }
+
+    async fn get(app: Router, uri: &str, accept_encoding: Option<&str>) -> (Response, String) {
+        let mut request = Request::get(uri);
+        if let Some(encoding) = accept_encoding {
+            request = request.header(header::ACCEPT_ENCODING, encoding);
+        }
+        let response = app
+            .oneshot(request.body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let (parts, body) = response.into_parts();
+        let body = to_bytes(body, usize::MAX).await.unwrap();
+        (
+            Response::from_parts(parts, Body::empty()),
+            String::from_utf8_lossy(&body).into_owned(),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_index_is_served_with_headers() {
+        let dir = build_dir();
+        let (response, body) = get(routes(dir.path()), "/", None).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(body, "<html>app</html>");
+        let headers = response.headers();
+        assert_eq!(headers[header::CACHE_CONTROL], "no-cache");
+        assert_eq!(headers[header::X_CONTENT_TYPE_OPTIONS], "nosniff");
+        assert_eq!(headers[header::X_FRAME_OPTIONS], "DENY");
+        assert!(headers.contains_key(header::CONTENT_SECURITY_POLICY));
+    }
+
+    #[tokio::test]
+    async fn test_unknown_page_falls_back_to_index() {
+        let dir = build_dir();
+        let (response, body) = get(routes(dir.path()), "/boards/sprint-42", None).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(body, "<html>app</html>");
+    }
+
+    #[tokio::test]
+    async fn test_static_file() {
+        let dir = build_dir();
+        let (response, body) = get(routes(dir.path()), "/favicon.png", None).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(body, "png");
+        assert_eq!(response.headers()[header::CACHE_CONTROL], "no-cache");
+    }
+
+    #[tokio::test]
+    async fn test_immutable_assets_are_cached() {
+        let dir = build_dir();
+        let (response, body) =
+            get(routes(dir.path()), "/_app/immutable/start.abc123.js", None).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(body, "console.log(1)");
+        assert_eq!(
+            response.headers()[header::CACHE_CONTROL],
+            "public, max-age=31536000, immutable"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_precompressed_asset() {
+        let dir = build_dir();
+        let (response, body) = get(
+            routes(dir.path()),
+            "/_app/immutable/start.abc123.js",
+            Some("gzip, deflate"),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[header::CONTENT_ENCODING], "gzip");
+        assert_eq!(body, "gzipped");
+    }
+
+    #[tokio::test]
+    async fn test_missing_asset_is_not_found() {
+        let dir = build_dir();
+        let (response, _) = get(routes(dir.path()), "/_app/immutable/gone.js", None).await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_server_routes_take_precedence() {
+        let dir = build_dir();
+        let state = test_app_state(RetroBoard::default(), dir.path().join("board.json"));
+        let app = router(Arc::new(state)).merge(routes(dir.path()));
+        let (response, body) = get(app, "/healthz", None).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(body, "ok");
+    }
+}
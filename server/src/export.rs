@@ -0,0 +1,674 @@
+//! Exporting a board for use outside the app.
+
+use crate::report;
+use crate::retroboard::{
+    ActionDetails, ActionStatus, Group, Merged, RetroBoard, RetroItem, RetroLane,
+};
+use crate::schema;
+use crate::template;
+use crate::AppState;
+use axum::{
+    extract::{Path, Query, State},
+    http::{header::CONTENT_TYPE, StatusCode},
+    response::{IntoResponse, Response},
+    routing::get,
+    Router,
+};
+use chrono::NaiveDate;
+use serde::Deserialize;
+use std::fmt::Write;
+use std::sync::Arc;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Format {
+    #[default]
+    Markdown,
+    /// The whole board as a versioned document that can be imported again
+    Json,
+    /// One row per item
+    Csv,
+    /// A standalone page that can be shared without the server
+    Html,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ItemOrder {
+    /// The order items have on the board
+    #[default]
+    Board,
+    /// Most votes first, ties in board order
+    Votes,
+}
+
+/// The items of a lane with their IDs, in the requested order.
+pub fn sorted_items(lane: &RetroLane, order: ItemOrder) -> Vec<(&str, &RetroItem)> {
+    let mut items: Vec<_> = lane
+        .items
+        .iter()
+        .map(|(id, item)| (id.as_str(), item))
+        .collect();
+    match order {
+        ItemOrder::Board => items.sort_by_key(|(_, item)| item.sort_order),
+        ItemOrder::Votes => {
+            items.sort_by_key(|(_, item)| (std::cmp::Reverse(item.vote_count), item.sort_order))
+        }
+    }
+    items
+}
+
+/// Every item merged into `item`, including those merged into them before,
+/// in the order they were merged.
+pub fn constituents(item: &RetroItem) -> Vec<&Merged> {
+    let mut all = Vec::new();
+    for merged in &item.merged {
+        all.push(merged);
+        all.extend(constituents(&merged.item));
+    }
+    all
+}
+
+/// Render a board as Markdown, one section per lane.
+pub fn to_markdown(board: &RetroBoard, order: ItemOrder) -> String {
+    let mut out = String::new();
+    writeln!(out, "# {}", single_line(&board.title)).unwrap();
+
+    for lane in board.lanes.values() {
+        writeln!(out, "\n## {}\n", single_line(&lane.title)).unwrap();
+
+        let items = sorted_items(lane, order);
+        if items.is_empty() && lane.groups.is_empty() {
+            writeln!(out, "_No items_").unwrap();
+            continue;
+        }
+
+        let marker = if lane.is_action_items() {
+            "- [ ] "
+        } else {
+            "- "
+        };
+        // Ungrouped items first, then each group under its own heading
+        let (grouped, ungrouped): (Vec<_>, Vec<_>) = items
+            .into_iter()
+            .partition(|(_, item)| group_of(lane, item).is_some());
+        write_markdown_items(&mut out, marker, &ungrouped);
+        for (index, (group_id, group)) in lane.groups.iter().enumerate() {
+            if index > 0 || !ungrouped.is_empty() {
+                out.push('\n');
+            }
+            let title = single_line(&group.title);
+            let count = votes(lane.group_votes(group_id));
+            writeln!(out, "### {} ({})\n", title, count).unwrap();
+            let items: Vec<_> = grouped
+                .iter()
+                .filter(|(_, item)| item.group.as_deref() == Some(group_id))
+                .copied()
+                .collect();
+            if items.is_empty() {
+                writeln!(out, "_No items_").unwrap();
+            }
+            write_markdown_items(&mut out, marker, &items);
+        }
+    }
+    out
+}
+
+fn write_markdown_items(out: &mut String, marker: &str, items: &[(&str, &RetroItem)]) {
+    for (_, item) in items {
+        // Continuation lines are indented to stay inside the list item
+        let done = item
+            .action
+            .as_ref()
+            .is_some_and(|details| details.status == ActionStatus::Done);
+        let marker = if done { "- [x] " } else { marker };
+        let body = item.body.trim().replace('\n', "\n  ");
+        let mut count = votes(item.vote_count);
+        if !item.comments.is_empty() {
+            write!(count, ", {}", comments(item.comments.len())).unwrap();
+        }
+        writeln!(out, "{}{} ({})", marker, body, count).unwrap();
+        if let Some(summary) = item.action.as_ref().and_then(action_summary) {
+            writeln!(out, "  - {}", summary).unwrap();
+        }
+        for merged in constituents(item) {
+            let body = merged.item.body.trim().replace('\n', "\n    ");
+            let count = votes(merged.item.vote_count);
+            writeln!(out, "  - merged: {} ({})", body, count).unwrap();
+        }
+    }
+}
+
+/// Who an action item is assigned to, when it is due and, unless it is
+/// simply open or done, its status. `None` if there is nothing to say.
+pub fn action_summary(details: &ActionDetails) -> Option<String> {
+    let mut parts = Vec::new();
+    if let Some(assignee) = &details.assignee {
+        parts.push(format!("assigned to {}", single_line(assignee)));
+    }
+    if let Some(due) = details.due {
+        parts.push(format!("due {}", due));
+    }
+    if matches!(
+        details.status,
+        ActionStatus::InProgress | ActionStatus::Dropped
+    ) {
+        parts.push(details.status.label().to_string());
+    }
+    (!parts.is_empty()).then(|| parts.join(", "))
+}
+
+/// The group an item is in, if it is in one that exists.
+pub fn group_of<'a>(lane: &'a RetroLane, item: &RetroItem) -> Option<&'a Group> {
+    lane.groups.get(item.group.as_deref()?)
+}
+
+const CSV_HEADER: [&str; 14] = [
+    "board_id",
+    "board",
+    "lane",
+    "body",
+    "votes",
+    "sort_order",
+    "merged",
+    "group",
+    "comments",
+    "assignee",
+    "due",
+    "status",
+    "created",
+    "author",
+];
+
+/// Render boards as CSV with one row per item, lanes in board order and
+/// items in the requested order. The `merged` column lists the items merged
+/// into each one, one per line, `group` the title of its group and
+/// `comments` how many comments it has. Action items also fill in
+/// `assignee`, `due` and `status`. `created` is empty for items from before
+/// creation times were recorded, and `author` whenever the board keeps it
+/// hidden.
+pub fn to_csv<'a>(
+    boards: impl IntoIterator<Item = (&'a str, &'a RetroBoard)>,
+    order: ItemOrder,
+) -> String {
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer.write_record(CSV_HEADER).unwrap();
+    for (id, board) in boards {
+        for lane in board.lanes.values() {
+            for (_, item) in sorted_items(lane, order) {
+                let action = item.action.as_ref();
+                let merged: Vec<_> = constituents(item)
+                    .into_iter()
+                    .map(|merged| {
+                        let body = single_line(&merged.item.body);
+                        format!("{} ({})", body, votes(merged.item.vote_count))
+                    })
+                    .collect();
+                writer
+                    .write_record([
+                        id,
+                        &board.title,
+                        &lane.title,
+                        &item.body,
+                        &item.vote_count.to_string(),
+                        &item.sort_order.to_string(),
+                        &merged.join("\n"),
+                        group_of(lane, item).map_or("", |group| &group.title),
+                        &item.comments.len().to_string(),
+                        action
+                            .and_then(|details| details.assignee.as_deref())
+                            .unwrap_or(""),
+                        &action
+                            .and_then(|details| details.due)
+                            .map_or(String::new(), |due| due.to_string()),
+                        action.map_or("", |details| details.status.label()),
+                        &item.created_at.map_or(String::new(), |at| at.to_rfc3339()),
+                        item.shown_author(&board.settings).unwrap_or(""),
+                    ])
+                    .unwrap();
+            }
+        }
+    }
+    String::from_utf8(writer.into_inner().unwrap()).unwrap()
+}
+
+fn single_line(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+pub fn votes(count: u64) -> String {
+    if count == 1 {
+        "1 vote".to_string()
+    } else {
+        format!("{} votes", count)
+    }
+}
+
+pub fn comments(count: usize) -> String {
+    if count == 1 {
+        "1 comment".to_string()
+    } else {
+        format!("{} comments", count)
+    }
+}
+
+pub fn routes() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/api/export", get(export_many))
+        .route("/api/boards/{id}/export", get(export))
+}
+
+#[derive(Deserialize)]
+struct ExportParams {
+    #[serde(default)]
+    format: Format,
+    #[serde(default)]
+    sort: ItemOrder,
+    /// A user-defined template to render instead of `format`
+    template: Option<String>,
+}
+
+async fn export(
+    State(state): State<Arc<AppState>>,
+    Path(id): Path<String>,
+    Query(params): Query<ExportParams>,
+) -> Response {
+    let Some(board) = state.board(&id) else {
+        return (StatusCode::NOT_FOUND, "No such board").into_response();
+    };
+    let board = board.read();
+    if let Some(name) = &params.template {
+        return export_template(&state, &id, &board, name, params.sort);
+    }
+    match params.format {
+        Format::Markdown => (
+            [(CONTENT_TYPE, "text/markdown; charset=utf-8")],
+            to_markdown(&board, params.sort),
+        )
+            .into_response(),
+        Format::Json => (
+            [(CONTENT_TYPE, "application/json")],
+            schema::to_export_json(&board),
+        )
+            .into_response(),
+        Format::Csv => csv_response(to_csv([(id.as_str(), &*board)], params.sort)),
+        Format::Html => (
+            [(CONTENT_TYPE, "text/html; charset=utf-8")],
+            report::to_html(&board, params.sort),
+        )
+            .into_response(),
+    }
+}
+
+#[derive(Deserialize)]
+struct ExportManyParams {
+    #[serde(default)]
+    format: Format,
+    #[serde(default)]
+    sort: ItemOrder,
+    /// First day of the range, inclusive
+    from: Option<NaiveDate>,
+    /// Last day of the range, inclusive
+    to: Option<NaiveDate>,
+}
+
+/// Export every board created within a date range. Boards from before
+/// creation dates were recorded are only included when no range is given.
+async fn export_many(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<ExportManyParams>,
+) -> Response {
+    if params.format != Format::Csv {
+        return (
+            StatusCode::BAD_REQUEST,
+            "Only format=csv can export several boards",
+        )
+            .into_response();
+    }
+
+    let in_range = |board: &RetroBoard| {
+        if params.from.is_none() && params.to.is_none() {
+            return true;
+        }
+        let Some(created) = board.created_at.map(|created| created.date_naive()) else {
+            return false;
+        };
+        params.from.is_none_or(|from| created >= from) && params.to.is_none_or(|to| created <= to)
+    };
+
+    let boards: Vec<_> = state
+        .boards
+        .ids()
+        .into_iter()
+        .filter_map(|id| Some((state.board(&id)?, id)))
+        .collect();
+    let mut selected: Vec<_> = boards
+        .iter()
+        .map(|(board, id)| (id.as_str(), board.read()))
+        .filter(|(_, board)| in_range(board))
+        .collect();
+    selected.sort_by_key(|(id, board)| (board.created_at, *id));
+
+    let csv = to_csv(
+        selected.iter().map(|(id, board)| (*id, &**board)),
+        params.sort,
+    );
+    csv_response(csv)
+}
+
+fn export_template(
+    state: &AppState,
+    id: &str,
+    board: &RetroBoard,
+    name: &str,
+    order: ItemOrder,
+) -> Response {
+    let Some(path) = template::find(&state.templates_dir, name) else {
+        return (StatusCode::NOT_FOUND, "No such template").into_response();
+    };
+    match template::render(&path, &template::BoardView::new(id, board, order)) {
+        Ok(output) => ([(CONTENT_TYPE, template::content_type(&path))], output).into_response(),
+        Err(e) => {
+            tracing::error!("Failed to render template {}: {}", path.display(), e);
+            (
+                StatusCode::UNPROCESSABLE_ENTITY,
+                format!("Template '{}' failed: {}", name, e),
+            )
+                .into_response()
+        }
+    }
+}
+
+fn csv_response(csv: String) -> Response {
+    ([(CONTENT_TYPE, "text/csv; charset=utf-8")], csv).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{router, test_app_state};
+    use axum::body::{to_bytes, Body};
+    use axum::http::Request;
+    use tower::ServiceExt;
+
+    fn board() -> RetroBoard {
+        let mut board = RetroBoard::from_template("went-well").unwrap();
+        board.title = "Sprint 42 Retro".to_string();
+        let deploys = board.add_item("went-well", "Deploys were smooth").unwrap();
+        board.set_author("went-well", &deploys, "dave");
+        board.add_item("went-well", "Pairing\non the release");
+        board.add_item("action-items", "Fix the flaky tests");
+        let (id, _) = board
+            .lanes
+            .get("went-well")
+            .unwrap()
+            .items
+            .iter()
+            .find(|(_, item)| item.body.starts_with("Pairing"))
+            .unwrap();
+        let id = id.clone();
+        board.upvote_item("went-well", &id);
+        board.upvote_item("went-well", &id);
+        board.add_comment("went-well", &id, "alice", "More of this", None);
+        let test = board.add_item("action-items", "Add a test").unwrap();
+        let fix = board.lanes["action-items"]
+            .items
+            .iter()
+            .find(|(_, item)| item.body == "Fix the flaky tests")
+            .map(|(id, _)| id.clone())
+            .unwrap();
+        board.merge_items("action-items", &test, &fix, "Fix the flaky tests");
+        let assignee = Some(Some("alice".to_string()));
+        let status = Some(ActionStatus::InProgress);
+        board.update_action_item("action-items", &fix, assignee, None, status);
+        board
+    }
+
+    #[test]
+    fn test_markdown_in_board_order() {
+        let expected = "\
+# Sprint 42 Retro
+
+## Went Well
+
+- Deploys were smooth (0 votes)
+- Pairing
+  on the release (2 votes, 1 comment)
+
+## To Improve
+
+_No items_
+
+## Action Items
+
+- [ ] Fix the flaky tests (0 votes)
+  - assigned to alice, in progress
+  - merged: Add a test (0 votes)
+";
+        assert_eq!(to_markdown(&board(), ItemOrder::Board), expected);
+    }
+
+    #[test]
+    fn test_markdown_groups() {
+        let mut board = RetroBoard::from_template("went-well").unwrap();
+        board.title = "Grouped".to_string();
+        let deploys = board.add_item("went-well", "Deploys").unwrap();
+        let releases = board.add_item("went-well", "Releases").unwrap();
+        board.add_item("went-well", "Pairing");
+        let shipping = board.add_group("went-well", "Shipping").unwrap();
+        board.add_group("went-well", "Empty");
+        board.group_item("went-well", &deploys, Some(&shipping));
+        board.group_item("went-well", &releases, Some(&shipping));
+        board.upvote_item("went-well", &deploys);
+        board.upvote_group("went-well", &shipping);
+
+        let markdown = to_markdown(&board, ItemOrder::Board);
+        let went_well = "\
+## Went Well
+
+- Pairing (0 votes)
+
+### Shipping (2 votes)
+
+- Deploys (1 vote)
+- Releases (0 votes)
+
+### Empty (0 votes)
+
+_No items_
+";
+        assert!(markdown.contains(went_well), "{}", markdown);
+    }
+
+    #[test]
+    fn test_markdown_by_votes() {
+        let markdown = to_markdown(&board(), ItemOrder::Votes);
+        let pairing = markdown.find("- Pairing").unwrap();
+        let deploys = markdown.find("- Deploys").unwrap();
+        assert!(pairing < deploys);
+    }
+
+    #[test]
+    fn test_constituents_include_earlier_merges() {
+        let mut board = RetroBoard::from_template("went-well").unwrap();
+        let a = board.add_item("went-well", "A").unwrap();
+        let b = board.add_item("went-well", "B").unwrap();
+        let c = board.add_item("went-well", "C").unwrap();
+        board.merge_items("went-well", &b, &a, "A and B");
+        board.merge_items("went-well", &a, &c, "All three");
+
+        let item = &board.lanes["went-well"].items[&c];
+        let bodies: Vec<_> = constituents(item)
+            .into_iter()
+            .map(|merged| merged.item.body.as_str())
+            .collect();
+        assert_eq!(bodies, ["A and B", "B"]);
+    }
+
+    #[test]
+    fn test_votes_are_pluralized() {
+        assert_eq!(votes(1), "1 vote");
+        assert_eq!(votes(3), "3 votes");
+    }
+
+    async fn get(uri: &str) -> (StatusCode, String, String) {
+        let dir = tempfile::tempdir().unwrap();
+        let state = test_app_state(board(), dir.path().join("board.json"));
+        let response = router(Arc::new(state))
+            .oneshot(Request::get(uri).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let status = response.status();
+        let content_type = response
+            .headers()
+            .get(CONTENT_TYPE)
+            .map(|value| value.to_str().unwrap().to_string())
+            .unwrap_or_default();
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (
+            status,
+            content_type,
+            String::from_utf8(body.to_vec()).unwrap(),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_markdown_endpoint() {
+        let (status, content_type, body) = get("/api/boards/default/export?sort=votes").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(content_type, "text/markdown; charset=utf-8");
+        assert!(body.starts_with("# Sprint 42 Retro\n"));
+        assert!(body.contains("- [ ] Fix the flaky tests"));
+    }
+
+    #[tokio::test]
+    async fn test_json_endpoint() {
+        let (status, content_type, body) = get("/api/boards/default/export?format=json").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(content_type, "application/json");
+        let exported = schema::from_json(&body).unwrap();
+        assert_eq!(exported.title, "Sprint 42 Retro");
+        assert_eq!(exported.lanes.len(), 3);
+        // The board is anonymous, so its authors stay on the server
+        assert!(!body.contains("dave"));
+    }
+
+    #[test]
+    fn test_csv() {
+        let mut other = RetroBoard::new("Other, \"quoted\"");
+        other.settings.anonymous = false;
+        other.add_lane("Notes");
+        let note = other.add_item("Notes", "Line one\nline two").unwrap();
+        other.set_author("Notes", &note, "erin");
+
+        let csv = to_csv([("a", &board()), ("b", &other)], ItemOrder::Votes);
+        let mut reader = csv::Reader::from_reader(csv.as_bytes());
+        assert_eq!(reader.headers().unwrap(), CSV_HEADER.as_slice());
+
+        let rows: Vec<csv::StringRecord> = reader.records().map(Result::unwrap).collect();
+        assert_eq!(rows.len(), 4);
+        assert_eq!(
+            rows[0].iter().take(12).collect::<Vec<_>>(),
+            [
+                "a",
+                "Sprint 42 Retro",
+                "Went Well",
+                "Pairing\non the release",
+                "2",
+                "1",
+                "",
+                "",
+                "1",
+                "",
+                "",
+                ""
+            ]
+        );
+        assert!(chrono::DateTime::parse_from_rfc3339(&rows[0][12]).is_ok());
+        assert_eq!(&rows[1][3], "Deploys were smooth");
+        assert_eq!(&rows[2][2], "Action Items");
+        assert_eq!(&rows[2][6], "Add a test (0 votes)");
+        assert_eq!(&rows[2][9], "alice");
+        assert_eq!(&rows[2][11], "in progress");
+        assert_eq!(&rows[3][1], "Other, \"quoted\"");
+        assert_eq!(&rows[3][3], "Line one\nline two");
+        // Authors only show on boards that are not anonymous
+        assert_eq!(&rows[1][13], "");
+        assert_eq!(&rows[3][13], "erin");
+    }
+
+    #[tokio::test]
+    async fn test_csv_endpoint() {
+        let (status, content_type, body) = get("/api/boards/default/export?format=csv").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(content_type, "text/csv; charset=utf-8");
+        let rows = csv::Reader::from_reader(body.as_bytes()).records().count();
+        assert_eq!(rows, 3);
+    }
+
+    #[tokio::test]
+    async fn test_html_endpoint() {
+        let (status, content_type, body) = get("/api/boards/default/export?format=html").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(content_type, "text/html; charset=utf-8");
+        assert!(body.contains("<h1>Sprint 42 Retro</h1>"));
+    }
+
+    #[tokio::test]
+    async fn test_csv_for_date_range() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut old = board();
+        old.created_at = Some("2026-01-15T10:00:00Z".parse().unwrap());
+        let state = test_app_state(old, dir.path().join("board.json"));
+        let mut recent = board();
+        recent.title = "Recent".to_string();
+        recent.created_at = Some("2026-03-02T10:00:00Z".parse().unwrap());
+        state.boards.insert("recent", recent);
+        let mut undated = board();
+        undated.created_at = None;
+        state.boards.insert("undated", undated);
+        let app = router(Arc::new(state));
+
+        let boards_in = |uri: &'static str| {
+            let app = app.clone();
+            async move {
+                let response = app
+                    .oneshot(Request::get(uri).body(Body::empty()).unwrap())
+                    .await
+                    .unwrap();
+                let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+                let mut ids: Vec<String> = csv::Reader::from_reader(&body[..])
+                    .records()
+                    .map(|row| row.unwrap()[0].to_string())
+                    .collect();
+                ids.dedup();
+                ids
+            }
+        };
+
+        assert_eq!(
+            boards_in("/api/export?format=csv").await,
+            ["undated", "default", "recent"]
+        );
+        assert_eq!(
+            boards_in("/api/export?format=csv&from=2026-03-01&to=2026-03-02").await,
+            ["recent"]
+        );
+        assert_eq!(
+            boards_in("/api/export?format=csv&to=2026-02-28").await,
+            ["default"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_export_many_needs_csv() {
+        let (status, _, _) = get("/api/export?format=markdown").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_export_unknown_board() {
+        let (status, _, _) = get("/api/boards/missing/export").await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+    }
+}
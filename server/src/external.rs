@@ -0,0 +1,364 @@
+//! Importing boards exported by other retro tools.
+//!
+//! Each importer maps what it can onto a `RetroBoard` and lists everything
+//! else it found, so nothing is dropped without the user knowing.
+//!
+//! Trello: the JSON export of a board ("Print, export and share" →
+//! "Export as JSON"). Open lists become lanes and open cards their items,
+//! both in Trello's order. A card's description is added to its text, its
+//! labels are prefixed as `[Label]`, and its votes are kept.
+//!
+//! EasyRetro: the hosted tool has no published export schema, so this reads
+//! the shape below. Unknown fields are ignored.
+//!
+//! ```json
+//! {
+//!   "name": "Sprint 12 Retro",
+//!   "createdAt": "2024-05-02T09:00:00Z",
+//!   "columns": [
+//!     {
+//!       "title": "Went well",
+//!       "cards": [
+//!         {"text": "Deploys", "votes": 3, "author": "Ana", "comments": [{"text": "+1"}]}
+//!       ]
+//!     }
+//!   ]
+//! }
+//! ```
+
+use crate::retroboard::RetroBoard;
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use std::collections::HashMap;
+
+/// A board converted from another tool, and what could not be mapped.
+pub struct Converted {
+    pub board: RetroBoard,
+    pub unmapped: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct TrelloBoard {
+    id: Option<String>,
+    name: String,
+    #[serde(default)]
+    lists: Vec<TrelloList>,
+    #[serde(default)]
+    cards: Vec<TrelloCard>,
+}
+
+#[derive(Deserialize)]
+struct TrelloList {
+    id: String,
+    name: String,
+    #[serde(default)]
+    closed: bool,
+    #[serde(default)]
+    pos: f64,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct TrelloCard {
+    name: String,
+    #[serde(default)]
+    desc: String,
+    id_list: String,
+    #[serde(default)]
+    closed: bool,
+    #[serde(default)]
+    pos: f64,
+    #[serde(default)]
+    labels: Vec<TrelloLabel>,
+    #[serde(default)]
+    id_members_voted: Vec<String>,
+    #[serde(default)]
+    id_members: Vec<String>,
+    #[serde(default)]
+    id_checklists: Vec<String>,
+    due: Option<String>,
+    #[serde(default)]
+    badges: TrelloBadges,
+}
+
+#[derive(Deserialize)]
+struct TrelloLabel {
+    #[serde(default)]
+    name: String,
+    color: Option<String>,
+}
+
+#[derive(Default, Deserialize)]
+struct TrelloBadges {
+    #[serde(default)]
+    votes: u64,
+    #[serde(default)]
+    comments: u64,
+    #[serde(default)]
+    attachments: u64,
+}
+
+/// Convert a Trello board export.
+pub fn from_trello(json: &str) -> Result<Converted, String> {
+    let trello: TrelloBoard =
+        serde_json::from_str(json).map_err(|e| format!("not a Trello export: {}", e))?;
+    let mut board = RetroBoard::new(&trello.name);
+    // Trello IDs start with the creation time in seconds, as hex
+    board.created_at = trello
+        .id
+        .as_deref()
+        .and_then(|id| i64::from_str_radix(id.get(..8)?, 16).ok())
+        .and_then(|seconds| DateTime::from_timestamp(seconds, 0));
+    let mut unmapped = Vec::new();
+
+    let mut lists: Vec<_> = trello.lists.iter().collect();
+    lists.sort_by(|a, b| a.pos.total_cmp(&b.pos));
+    // Trello list ID -> lane ID, for open lists
+    let mut lanes = HashMap::new();
+    for list in &lists {
+        if list.closed {
+            unmapped.push(format!("list '{}': archived, skipped", list.name));
+        } else {
+            lanes.insert(list.id.as_str(), board.add_imported_lane(&list.name));
+        }
+    }
+
+    // Cards go list by list, in Trello's order
+    let list_index = |card: &TrelloCard| {
+        lists
+            .iter()
+            .position(|list| list.id == card.id_list)
+            .unwrap_or(lists.len())
+    };
+    let mut cards: Vec<_> = trello.cards.iter().collect();
+    cards.sort_by(|a, b| {
+        list_index(a)
+            .cmp(&list_index(b))
+            .then(a.pos.total_cmp(&b.pos))
+    });
+    for card in cards {
+        let path = format!("card '{}'", card.name);
+        let Some(lane_id) = lanes.get(card.id_list.as_str()) else {
+            unmapped.push(format!("{}: in an archived or unknown list, skipped", path));
+            continue;
+        };
+        if card.closed {
+            unmapped.push(format!("{}: archived, skipped", path));
+            continue;
+        }
+
+        let mut body: String = card
+            .labels
+            .iter()
+            .filter_map(|label| {
+                let name = Some(label.name.trim())
+                    .filter(|name| !name.is_empty())
+                    .or(label.color.as_deref())?;
+                Some(format!("[{}] ", name))
+            })
+            .collect();
+        body.push_str(card.name.trim());
+        if !card.desc.trim().is_empty() {
+            body.push_str("\n\n");
+            body.push_str(card.desc.trim());
+        }
+        if let Some(id) = board.add_item(lane_id, &body) {
+            let votes = card.badges.votes.max(card.id_members_voted.len() as u64);
+            board.lanes[lane_id].items.get_mut(&id).unwrap().vote_count = votes;
+        }
+
+        for (count, what) in [
+            (card.badges.comments, "comment"),
+            (card.badges.attachments, "attachment"),
+            (card.id_checklists.len() as u64, "checklist"),
+        ] {
+            if count > 0 {
+                unmapped.push(format!("{}: {} not imported", path, plural(count, what)));
+            }
+        }
+        if !card.id_members.is_empty() {
+            unmapped.push(format!("{}: members not imported", path));
+        }
+        if let Some(due) = &card.due {
+            unmapped.push(format!("{}: due date {} not imported", path, due));
+        }
+    }
+
+    Ok(Converted { board, unmapped })
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct EasyRetroBoard {
+    name: String,
+    created_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    columns: Vec<EasyRetroColumn>,
+}
+
+#[derive(Deserialize)]
+struct EasyRetroColumn {
+    title: String,
+    #[serde(default)]
+    cards: Vec<EasyRetroCard>,
+}
+
+#[derive(Deserialize)]
+struct EasyRetroCard {
+    text: String,
+    #[serde(default)]
+    votes: u64,
+    author: Option<String>,
+    #[serde(default)]
+    comments: Vec<serde_json::Value>,
+}
+
+/// Convert an EasyRetro board export, in the shape described above.
+pub fn from_easyretro(json: &str) -> Result<Converted, String> {
+    let easyretro: EasyRetroBoard =
+        serde_json::from_str(json).map_err(|e| format!("not an EasyRetro export: {}", e))?;
+    let mut board = RetroBoard::new(&easyretro.name);
+    board.created_at = easyretro.created_at;
+    let mut unmapped = Vec::new();
+
+    for column in &easyretro.columns {
+        let lane_id = board.add_imported_lane(&column.title);
+        for card in &column.cards {
+            if let Some(id) = board.add_item(&lane_id, card.text.trim()) {
+                board.lanes[&lane_id].items.get_mut(&id).unwrap().vote_count = card.votes;
+            }
+            let path = format!("card '{}'", card.text.trim());
+            if !card.comments.is_empty() {
+                let comments = plural(card.comments.len() as u64, "comment");
+                unmapped.push(format!("{}: {} not imported", path, comments));
+            }
+            if card.author.is_some() {
+                unmapped.push(format!("{}: author not imported", path));
+            }
+        }
+    }
+
+    Ok(Converted { board, unmapped })
+}
+
+fn plural(count: u64, noun: &str) -> String {
+    if count == 1 {
+        format!("1 {}", noun)
+    } else {
+        format!("{} {}s", count, noun)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn titles(board: &RetroBoard) -> Vec<&str> {
+        board
+            .lanes
+            .values()
+            .map(|lane| lane.title.as_str())
+            .collect()
+    }
+
+    fn bodies(board: &RetroBoard, lane_id: &str) -> Vec<(String, u64)> {
+        let mut items: Vec<_> = board.lanes[lane_id].items.values().collect();
+        items.sort_by_key(|item| item.sort_order);
+        items
+            .into_iter()
+            .map(|item| (item.body.clone(), item.vote_count))
+            .collect()
+    }
+
+    #[test]
+    fn test_trello() {
+        let json = r#"{
+            "id": "5f1a2b3c0000000000000000",
+            "name": "Team Retro",
+            "idOrganization": "ignored",
+            "lists": [
+                {"id": "l2", "name": "To Improve", "closed": false, "pos": 2},
+                {"id": "l1", "name": "Went Well", "closed": false, "pos": 1},
+                {"id": "l3", "name": "Old", "closed": true, "pos": 3}
+            ],
+            "cards": [
+                {"id": "c2", "name": "Pairing", "idList": "l1", "pos": 2, "closed": false,
+                 "desc": "on the release", "idMembersVoted": ["m1", "m2"],
+                 "badges": {"votes": 2, "comments": 1, "attachments": 0}},
+                {"id": "c1", "name": "Deploys", "idList": "l1", "pos": 1, "closed": false,
+                 "labels": [{"name": "Ops", "color": "green"}, {"name": "", "color": "red"}]},
+                {"id": "c3", "name": "Flaky tests", "idList": "l2", "pos": 1, "closed": false,
+                 "due": "2024-06-01T12:00:00.000Z"},
+                {"id": "c4", "name": "Gone", "idList": "l2", "pos": 2, "closed": true},
+                {"id": "c5", "name": "Hidden", "idList": "l3", "pos": 1, "closed": false}
+            ]
+        }"#;
+        let converted = from_trello(json).unwrap();
+        let board = &converted.board;
+        assert_eq!(board.title, "Team Retro");
+        assert_eq!(
+            board.created_at.unwrap().to_rfc3339(),
+            "2020-07-24T00:28:44+00:00"
+        );
+        assert_eq!(titles(board), ["Went Well", "To Improve"]);
+        assert_eq!(
+            bodies(board, "went-well"),
+            [
+                ("[Ops] [red] Deploys".to_string(), 0),
+                ("Pairing\n\non the release".to_string(), 2),
+            ]
+        );
+        assert_eq!(
+            bodies(board, "to-improve"),
+            [("Flaky tests".to_string(), 0)]
+        );
+        assert_eq!(
+            converted.unmapped,
+            [
+                "list 'Old': archived, skipped",
+                "card 'Pairing': 1 comment not imported",
+                "card 'Flaky tests': due date 2024-06-01T12:00:00.000Z not imported",
+                "card 'Gone': archived, skipped",
+                "card 'Hidden': in an archived or unknown list, skipped",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_easyretro() {
+        let json = r#"{
+            "name": "Sprint 12 Retro",
+            "createdAt": "2024-05-02T09:00:00Z",
+            "columns": [
+                {"title": "Went well", "cards": [
+                    {"text": "Deploys", "votes": 3, "author": "Ana", "comments": [{"text": "+1"}]}
+                ]},
+                {"title": "Action items", "cards": []}
+            ]
+        }"#;
+        let converted = from_easyretro(json).unwrap();
+        let board = &converted.board;
+        assert_eq!(board.title, "Sprint 12 Retro");
+        assert_eq!(
+            board.created_at.unwrap().to_rfc3339(),
+            "2024-05-02T09:00:00+00:00"
+        );
+        assert_eq!(titles(board), ["Went well", "Action items"]);
+        assert_eq!(board.lanes["action-items"].theme, "action-items");
+        assert_eq!(bodies(board, "went-well"), [("Deploys".to_string(), 3)]);
+        assert_eq!(
+            converted.unmapped,
+            [
+                "card 'Deploys': 1 comment not imported",
+                "card 'Deploys': author not imported",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_wrong_format() {
+        let error = from_trello(r#"{"columns": []}"#).err().unwrap();
+        assert!(error.starts_with("not a Trello export"), "{}", error);
+        assert!(from_easyretro("[]").is_err());
+    }
+}
@@ -0,0 +1,183 @@
+//! Probe endpoints for orchestrators, plus build information.
+
+use crate::schema;
+use crate::AppState;
+use axum::{extract::State, http::StatusCode, routing::get, Json, Router};
+use serde::Serialize;
+use std::path::Path;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::Duration;
+
+const STORAGE_CHECK_TIMEOUT: Duration = Duration::from_secs(2);
+
+pub fn routes() -> Router<Arc<AppState>> {
+    Router::new()
+        .route("/healthz", get(healthz))
+        .route("/readyz", get(readyz))
+        .route("/version", get(version))
+}
+
+/// Liveness: the process is up and answering requests.
+async fn healthz() -> &'static str {
+    "ok"
+}
+
+#[derive(Serialize)]
+struct Readiness {
+    ready: bool,
+    /// Each check is `"ok"` or a description of what is wrong
+    storage: String,
+    board: String,
+    shutdown: String,
+}
+
+/// Readiness: storage is reachable, the board is loaded and the server is
+/// not shutting down. Answers 503 when any check fails.
+async fn readyz(State(state): State<Arc<AppState>>) -> (StatusCode, Json<Readiness>) {
+    let storage = check_storage(&state).await;
+    let board = if state.board_loaded.load(Ordering::Relaxed) {
+        Ok(())
+    } else {
+        Err("board is not in sync with storage".to_string())
+    };
+    let shutdown = if *state.shutdown.borrow() {
+        Err("server is shutting down".to_string())
+    } else {
+        Ok(())
+    };
+
+    let ready = storage.is_ok() && board.is_ok() && shutdown.is_ok();
+    let status = if ready {
+        StatusCode::OK
+    } else {
+        StatusCode::SERVICE_UNAVAILABLE
+    };
+    let describe = |check: Result<(), String>| check.err().unwrap_or_else(|| "ok".to_string());
+    (
+        status,
+        Json(Readiness {
+            ready,
+            storage: describe(storage),
+            board: describe(board),
+            shutdown: describe(shutdown),
+        }),
+    )
+}
+
+async fn check_storage(state: &AppState) -> Result<(), String> {
+    if let Some(redis) = &state.redis {
+        return match tokio::time::timeout(STORAGE_CHECK_TIMEOUT, redis.ping()).await {
+            Ok(Ok(())) => Ok(()),
+            Ok(Err(e)) => Err(format!("redis: {}", e)),
+            Err(_) => Err("redis: ping timed out".to_string()),
+        };
+    }
+
+    let dir = match state.board_file.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    match std::fs::metadata(dir) {
+        Ok(meta) if !meta.is_dir() => Err(format!("{} is not a directory", dir.display())),
+        Ok(meta) if meta.permissions().readonly() => Err(format!("{} is read-only", dir.display())),
+        Ok(_) => Ok(()),
+        Err(e) => Err(format!("{}: {}", dir.display(), e)),
+    }
+}
+
+#[derive(Serialize)]
+struct BuildInfo {
+    name: &'static str,
+    version: &'static str,
+    /// Commit the binary was built from, when `GIT_SHA` was set at build time
+    git_sha: &'static str,
+    schema_version: u64,
+}
+
+async fn version() -> Json<BuildInfo> {
+    Json(BuildInfo {
+        name: env!("CARGO_PKG_NAME"),
+        version: env!("CARGO_PKG_VERSION"),
+        git_sha: option_env!("GIT_SHA").unwrap_or("unknown"),
+        schema_version: schema::CURRENT_VERSION,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::retroboard::RetroBoard;
+    use crate::{router, test_app_state};
+    use axum::body::{to_bytes, Body};
+    use axum::http::Request;
+    use tower::ServiceExt;
+
+    async fn get_json(state: Arc<AppState>, uri: &str) -> (StatusCode, serde_json::Value) {
+        let response = router(state)
+            .oneshot(Request::get(uri).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let status = response.status();
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (status, serde_json::from_slice(&body).unwrap_or_default())
+    }
+
+    #[tokio::test]
+    async fn test_healthz() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = test_app_state(RetroBoard::default(), dir.path().join("board.json"));
+        let (status, _) = get_json(Arc::new(state), "/healthz").await;
+        assert_eq!(status, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_readyz_ok() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = test_app_state(RetroBoard::default(), dir.path().join("board.json"));
+        let (status, body) = get_json(Arc::new(state), "/readyz").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["ready"], true);
+        assert_eq!(body["storage"], "ok");
+    }
+
+    #[tokio::test]
+    async fn test_readyz_missing_data_dir() {
+        let state = test_app_state(RetroBoard::default(), "/nonexistent/dir/board.json".into());
+        let (status, body) = get_json(Arc::new(state), "/readyz").await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(body["ready"], false);
+        assert_ne!(body["storage"], "ok");
+    }
+
+    #[tokio::test]
+    async fn test_readyz_board_not_loaded() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = test_app_state(RetroBoard::default(), dir.path().join("board.json"));
+        state.board_loaded.store(false, Ordering::Relaxed);
+        let (status, body) = get_json(Arc::new(state), "/readyz").await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert_ne!(body["board"], "ok");
+    }
+
+    #[tokio::test]
+    async fn test_readyz_during_shutdown() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = test_app_state(RetroBoard::default(), dir.path().join("board.json"));
+        state.shutdown.send_replace(true);
+        let (status, body) = get_json(Arc::new(state), "/readyz").await;
+        assert_eq!(status, StatusCode::SERVICE_UNAVAILABLE);
+        assert_ne!(body["shutdown"], "ok");
+    }
+
+    #[tokio::test]
+    async fn test_version() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = test_app_state(RetroBoard::default(), dir.path().join("board.json"));
+        let (status, body) = get_json(Arc::new(state), "/version").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["name"], "server");
+        assert_eq!(body["version"], env!("CARGO_PKG_VERSION"));
+        assert_eq!(body["schema_version"], schema::CURRENT_VERSION);
+    }
+}
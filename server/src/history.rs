@@ -0,0 +1,826 @@
+//! Undo and redo of board actions.
+//!
+//! Every action that changes the board is recorded with the lanes, items,
+//! vote counts, settings and trash entries it touched, as they were before
+//! and after. Each action names what it may touch as a `Scope`, so only that
+//! is copied before it is applied. Undoing an action puts the "before" state
+//! back and redoing it the "after" state, but only while nothing it touched
+//! has changed since, so an undo never overwrites someone else's newer
+//! change. The history is stored with the board, so it survives restarts and
+//! is shared through Redis, and keeps the last `MAX_ENTRIES` actions.
+
+use crate::action::Rejection;
+use crate::retroboard::{BoardSettings, Group, RetroBoard, RetroItem, RetroLane, Trashed};
+use chrono::{DateTime, Utc};
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
+
+/// Actions kept per board. Older ones can no longer be undone.
+pub const MAX_ENTRIES: usize = 100;
+
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct History {
+    entries: VecDeque<Entry>,
+    /// Number of undos so far, so the latest undone action is redone first
+    undo_count: u64,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Entry {
+    pub participant: String,
+    /// The action's `type` tag
+    pub action: String,
+    changes: Vec<Change>,
+    /// While undone, the value of `undo_count` when it was undone
+    undone: Option<u64>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(tag = "kind")]
+enum Change {
+    Lane {
+        lane_id: String,
+        before: Option<Box<LaneState>>,
+        after: Option<Box<LaneState>>,
+    },
+    Item {
+        lane_id: String,
+        item_id: String,
+        before: Option<Box<RetroItem>>,
+        after: Option<Box<RetroItem>>,
+    },
+    Votes {
+        participant: String,
+        before: u64,
+        after: u64,
+    },
+    Settings {
+        before: BoardSettings,
+        after: BoardSettings,
+    },
+    Trash {
+        trash_id: String,
+        before: Option<Box<Trashed>>,
+        after: Option<Box<Trashed>>,
+    },
+}
+
+/// A lane without its items, which are recorded one by one.
+#[derive(Clone, Serialize, Deserialize)]
+struct LaneState {
+    title: String,
+    theme: String,
+    /// Where the lane was shown, to put it back there
+    position: usize,
+    #[serde(default)]
+    groups: IndexMap<String, Group>,
+    #[serde(default)]
+    created_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    updated_at: Option<DateTime<Utc>>,
+}
+
+impl LaneState {
+    fn of(position: usize, lane: &RetroLane) -> Self {
+        Self {
+            title: lane.title.clone(),
+            theme: lane.theme.clone(),
+            position,
+            groups: lane.groups.clone(),
+            created_at: lane.created_at,
+            updated_at: lane.updated_at,
+        }
+    }
+
+    fn same(a: Option<&LaneState>, b: Option<&LaneState>) -> bool {
+        match (a, b) {
+            (Some(a), Some(b)) => a.title == b.title && a.theme == b.theme && a.groups == b.groups,
+            (None, None) => true,
+            _ => false,
+        }
+    }
+}
+
+/// What an action may change, so only that is copied before applying it.
+#[derive(Default)]
+pub struct Scope {
+    /// Lanes whose title, theme or groups may change, or that may be added
+    /// or removed
+    pub lanes: Vec<String>,
+    /// Lanes any of whose items may change, be added or be removed
+    pub lane_items: Vec<String>,
+    /// Single items that may change, by lane and item ID
+    pub items: Vec<(String, String)>,
+    /// Whether lanes may be added under IDs not known beforehand
+    pub new_lanes: bool,
+    /// Participants whose vote counts may change
+    pub votes: Vec<String>,
+    pub settings: bool,
+    /// Trash entries that may be restored or purged
+    pub trash: Vec<String>,
+    /// Whether anything may be moved to the trash
+    pub new_trash: bool,
+}
+
+#[cfg(test)]
+impl Scope {
+    /// Everything on the board, to check narrower scopes against.
+    pub fn board(board: &RetroBoard) -> Self {
+        let lanes: Vec<String> = board.lanes.keys().cloned().collect();
+        Self {
+            lane_items: lanes.clone(),
+            lanes,
+            items: Vec::new(),
+            new_lanes: true,
+            votes: board.votes_cast.keys().cloned().collect(),
+            settings: true,
+            trash: board.trash.keys().cloned().collect(),
+            new_trash: true,
+        }
+    }
+}
+
+/// What the `Scope` of an action covers, taken before applying it.
+pub struct Snapshot {
+    lanes: Vec<(String, Option<LaneState>)>,
+    lane_items: Vec<(String, HashMap<String, RetroItem>)>,
+    items: Vec<(String, String, Option<RetroItem>)>,
+    /// All lane IDs, if lanes may be added
+    lane_ids: Option<HashSet<String>>,
+    votes: Vec<(String, u64)>,
+    settings: Option<BoardSettings>,
+    trash: Vec<(String, Option<Trashed>)>,
+    /// All trash IDs, if anything may be trashed
+    trash_ids: Option<HashSet<String>>,
+}
+
+impl Snapshot {
+    pub fn of(board: &RetroBoard, scope: Scope) -> Self {
+        let lane_items = unique(scope.lane_items);
+        // Items of lanes copied as a whole need no copy of their own
+        let items = unique(scope.items)
+            .into_iter()
+            .filter(|(lane_id, _)| !lane_items.contains(lane_id))
+            .map(|(lane_id, item_id)| {
+                let item = item_of(board, &lane_id, &item_id).cloned();
+                (lane_id, item_id, item)
+            })
+            .collect();
+        Self {
+            lanes: unique(scope.lanes)
+                .into_iter()
+                .map(|lane_id| {
+                    let state = lane_state(board, &lane_id);
+                    (lane_id, state)
+                })
+                .collect(),
+            lane_items: lane_items
+                .into_iter()
+                .map(|lane_id| {
+                    let items = board
+                        .lanes
+                        .get(&lane_id)
+                        .map(|lane| lane.items.clone())
+                        .unwrap_or_default();
+                    (lane_id, items)
+                })
+                .collect(),
+            items,
+            lane_ids: scope
+                .new_lanes
+                .then(|| board.lanes.keys().cloned().collect()),
+            votes: unique(scope.votes)
+                .into_iter()
+                .map(|participant| {
+                    let votes = board.votes_cast.get(&participant).copied().unwrap_or(0);
+                    (participant, votes)
+                })
+                .collect(),
+            settings: scope.settings.then(|| board.settings.clone()),
+            trash: unique(scope.trash)
+                .into_iter()
+                .map(|trash_id| {
+                    let trashed = board.trash.get(&trash_id).cloned();
+                    (trash_id, trashed)
+                })
+                .collect(),
+            trash_ids: scope
+                .new_trash
+                .then(|| board.trash.keys().cloned().collect()),
+        }
+    }
+}
+
+fn unique<T: Ord>(mut values: Vec<T>) -> Vec<T> {
+    values.sort();
+    values.dedup();
+    values
+}
+
+fn lane_state(board: &RetroBoard, lane_id: &str) -> Option<LaneState> {
+    board
+        .lanes
+        .get_full(lane_id)
+        .map(|(position, _, lane)| LaneState::of(position, lane))
+}
+
+fn item_of<'a>(board: &'a RetroBoard, lane_id: &str, item_id: &str) -> Option<&'a RetroItem> {
+    board
+        .lanes
+        .get(lane_id)
+        .and_then(|lane| lane.items.get(item_id))
+}
+
+impl History {
+    /// Recorded actions, oldest first.
+    #[cfg(test)]
+    pub fn entries(&self) -> impl Iterator<Item = &Entry> {
+        self.entries.iter()
+    }
+}
+
+#[cfg(test)]
+impl Entry {
+    /// The recorded changes as JSON, sorted, to compare them in any order.
+    pub fn changes_json(&self) -> Vec<String> {
+        sorted_json(&self.changes)
+    }
+}
+
+/// What changed on `board` since `before`, like `Entry::changes_json`.
+#[cfg(test)]
+pub fn changes_json_since(before: Snapshot, board: &RetroBoard) -> Vec<String> {
+    sorted_json(&diff(before, board))
+}
+
+#[cfg(test)]
+fn sorted_json(changes: &[Change]) -> Vec<String> {
+    let mut changes: Vec<String> = changes
+        .iter()
+        .map(|change| serde_json::to_string(change).unwrap())
+        .collect();
+    changes.sort();
+    changes
+}
+
+/// Record what an action by `participant` changed since `before`. A new
+/// action means the participant's undone actions can no longer be redone.
+pub fn record(board: &mut RetroBoard, participant: &str, action: &str, before: Snapshot) {
+    let changes = diff(before, board);
+    if changes.is_empty() {
+        return;
+    }
+    let history = &mut board.history;
+    history
+        .entries
+        .retain(|entry| entry.undone.is_none() || entry.participant != participant);
+    history.entries.push_back(Entry {
+        participant: participant.to_string(),
+        action: action.to_string(),
+        changes,
+        undone: None,
+    });
+    while history.entries.len() > MAX_ENTRIES {
+        history.entries.pop_front();
+    }
+}
+
+/// Undo the latest action of `participant`, or of anyone if `everyone`.
+pub fn undo(board: &mut RetroBoard, participant: &str, everyone: bool) -> Result<(), Rejection> {
+    let index = board
+        .history
+        .entries
+        .iter()
+        .rposition(|entry| entry.undone.is_none() && (everyone || entry.participant == participant))
+        .ok_or_else(|| Rejection("there is nothing to undo".to_string()))?;
+
+    let changes = board.history.entries[index].changes.clone();
+    restore(board, &changes, Side::Before)?;
+    let history = &mut board.history;
+    history.undo_count += 1;
+    history.entries[index].undone = Some(history.undo_count);
+    Ok(())
+}
+
+/// Redo the latest action undone by `participant`, or by anyone if
+/// `everyone`.
+pub fn redo(board: &mut RetroBoard, participant: &str, everyone: bool) -> Result<(), Rejection> {
+    let index = board
+        .history
+        .entries
+        .iter()
+        .enumerate()
+        .filter(|(_, entry)| everyone || entry.participant == participant)
+        .filter_map(|(index, entry)| Some((index, entry.undone?)))
+        .max_by_key(|(_, undone)| *undone)
+        .map(|(index, _)| index)
+        .ok_or_else(|| Rejection("there is nothing to redo".to_string()))?;
+
+    let changes = board.history.entries[index].changes.clone();
+    restore(board, &changes, Side::After)?;
+    board.history.entries[index].undone = None;
+    Ok(())
+}
+
+fn diff(before: Snapshot, board: &RetroBoard) -> Vec<Change> {
+    let mut changes = Vec::new();
+    let item_change = |lane_id: &str, item_id: &str, old: Option<RetroItem>| {
+        let new = item_of(board, lane_id, item_id);
+        (old.as_ref() != new).then(|| Change::Item {
+            lane_id: lane_id.to_string(),
+            item_id: item_id.to_string(),
+            before: old.map(Box::new),
+            after: new.cloned().map(Box::new),
+        })
+    };
+
+    // Added lanes nobody named beforehand are recorded whole
+    let added: Vec<&String> = match &before.lane_ids {
+        Some(lane_ids) => board
+            .lanes
+            .keys()
+            .filter(|lane_id| !lane_ids.contains(*lane_id))
+            .collect(),
+        None => Vec::new(),
+    };
+    for lane_id in added {
+        if !before.lanes.iter().any(|(id, _)| id == lane_id) {
+            changes.push(Change::Lane {
+                lane_id: lane_id.clone(),
+                before: None,
+                after: lane_state(board, lane_id).map(Box::new),
+            });
+        }
+        if !before.lane_items.iter().any(|(id, _)| id == lane_id) {
+            let mut item_ids: Vec<&String> = board.lanes[lane_id].items.keys().collect();
+            item_ids.sort();
+            changes.extend(
+                item_ids
+                    .into_iter()
+                    .filter_map(|item_id| item_change(lane_id, item_id, None)),
+            );
+        }
+    }
+
+    for (lane_id, old) in before.lanes {
+        let new = lane_state(board, &lane_id);
+        if !LaneState::same(old.as_ref(), new.as_ref()) {
+            changes.push(Change::Lane {
+                lane_id,
+                before: old.map(Box::new),
+                after: new.map(Box::new),
+            });
+        }
+    }
+
+    for (lane_id, mut old_items) in before.lane_items {
+        let new_items = board.lanes.get(&lane_id).map(|lane| &lane.items);
+        let item_ids: HashSet<String> = old_items
+            .keys()
+            .chain(new_items.into_iter().flat_map(|items| items.keys()))
+            .cloned()
+            .collect();
+        let mut item_ids: Vec<_> = item_ids.into_iter().collect();
+        item_ids.sort();
+        for item_id in item_ids {
+            let old = old_items.remove(&item_id);
+            changes.extend(item_change(&lane_id, &item_id, old));
+        }
+    }
+
+    for (lane_id, item_id, old) in before.items {
+        changes.extend(item_change(&lane_id, &item_id, old));
+    }
+
+    for (participant, old) in before.votes {
+        let new = board.votes_cast.get(&participant).copied().unwrap_or(0);
+        if old != new {
+            changes.push(Change::Votes {
+                participant,
+                before: old,
+                after: new,
+            });
+        }
+    }
+
+    if let Some(old) = before.settings {
+        if old != board.settings {
+            changes.push(Change::Settings {
+                before: old,
+                after: board.settings.clone(),
+            });
+        }
+    }
+
+    for (trash_id, old) in before.trash {
+        let new = board.trash.get(&trash_id);
+        if old.as_ref() != new {
+            changes.push(Change::Trash {
+                before: old.map(Box::new),
+                after: new.cloned().map(Box::new),
+                trash_id,
+            });
+        }
+    }
+    if let Some(trash_ids) = &before.trash_ids {
+        for (trash_id, trashed) in &board.trash {
+            if !trash_ids.contains(trash_id) {
+                changes.push(Change::Trash {
+                    trash_id: trash_id.clone(),
+                    before: None,
+                    after: Some(Box::new(trashed.clone())),
+                });
+            }
+        }
+    }
+    changes
+}
+
+#[derive(Clone, Copy)]
+enum Side {
+    Before,
+    After,
+}
+
+impl Side {
+    /// The state a change should be in now, and the state to restore.
+    fn pick<'a, T>(self, before: &'a T, after: &'a T) -> (&'a T, &'a T) {
+        match self {
+            Side::Before => (after, before),
+            Side::After => (before, after),
+        }
+    }
+}
+
+/// Put the board into the `side` state of `changes`, refusing if anything
+/// they touch has changed since.
+fn restore(board: &mut RetroBoard, changes: &[Change], side: Side) -> Result<(), Rejection> {
+    let changed_since = |what: String| Rejection(format!("{} has changed since", what));
+    for change in changes {
+        match change {
+            Change::Lane {
+                lane_id,
+                before,
+                after,
+            } => {
+                let (expected, _) = side.pick(before, after);
+                let current = board
+                    .lanes
+                    .get_full(lane_id)
+                    .map(|(position, _, lane)| LaneState::of(position, lane));
+                if !LaneState::same(current.as_ref(), expected.as_deref()) {
+                    return Err(changed_since(format!("lane '{}'", lane_id)));
+                }
+            }
+            Change::Item {
+                lane_id,
+                item_id,
+                before,
+                after,
+            } => {
+                let (expected, _) = side.pick(before, after);
+                let current = board
+                    .lanes
+                    .get(lane_id)
+                    .and_then(|lane| lane.items.get(item_id));
+                if current != expected.as_deref() {
+                    return Err(changed_since(format!("item '{}'", item_id)));
+                }
+            }
+            Change::Votes {
+                participant,
+                before,
+                after,
+            } => {
+                let (expected, _) = side.pick(before, after);
+                if board.votes_cast.get(participant).copied().unwrap_or(0) != *expected {
+                    return Err(changed_since(format!("the votes of '{}'", participant)));
+                }
+            }
+            Change::Settings { before, after } => {
+                let (expected, _) = side.pick(before, after);
+                if board.settings != *expected {
+                    return Err(changed_since("the board settings".to_string()));
+                }
+            }
+            Change::Trash {
+                trash_id,
+                before,
+                after,
+            } => {
+                let (expected, _) = side.pick(before, after);
+                if board.trash.get(trash_id) != expected.as_deref() {
+                    return Err(changed_since(format!("trash entry '{}'", trash_id)));
+                }
+            }
+        }
+    }
+
+    // Lanes that end up removed must not hold items other than ours
+    for change in changes {
+        if let Change::Lane {
+            lane_id,
+            before,
+            after,
+        } = change
+        {
+            let (_, target) = side.pick(before, after);
+            let ours = changes
+                .iter()
+                .filter(|other| matches!(other, Change::Item { lane_id: id, .. } if id == lane_id))
+                .count();
+            let items = board.lanes.get(lane_id).map_or(0, |lane| lane.items.len());
+            if target.is_none() && items > ours {
+                return Err(changed_since(format!("lane '{}'", lane_id)));
+            }
+        }
+    }
+
+    // Lanes first, so restored items have somewhere to go
+    for change in changes {
+        if let Change::Lane {
+            lane_id,
+            before,
+            after,
+        } = change
+        {
+            if let (_, Some(target)) = side.pick(before, after) {
+                match board.lanes.get_mut(lane_id) {
+                    Some(lane) => {
+                        lane.title = target.title.clone();
+                        lane.theme = target.theme.clone();
+                        lane.groups = target.groups.clone();
+                    }
+                    None => {
+                        let position = target.position.min(board.lanes.len());
+                        let lane = RetroLane {
+                            title: target.title.clone(),
+                            theme: target.theme.clone(),
+                            items: HashMap::new(),
+                            groups: target.groups.clone(),
+                            created_at: target.created_at,
+                            updated_at: target.updated_at,
+                        };
+                        board.lanes.shift_insert(position, lane_id.clone(), lane);
+                    }
+                }
+            }
+        }
+    }
+    for change in changes {
+        match change {
+            Change::Item {
+                lane_id,
+                item_id,
+                before,
+                after,
+            } => {
+                let (_, target) = side.pick(before, after);
+                if let Some(lane) = board.lanes.get_mut(lane_id) {
+                    match target {
+                        Some(item) => lane.items.insert(item_id.clone(), (**item).clone()),
+                        None => lane.items.remove(item_id),
+                    };
+                }
+            }
+            Change::Votes {
+                participant,
+                before,
+                after,
+            } => {
+                let (_, target) = side.pick(before, after);
+                if *target == 0 {
+                    board.votes_cast.remove(participant);
+                } else {
+                    board.votes_cast.insert(participant.clone(), *target);
+                }
+            }
+            Change::Settings { before, after } => {
+                let (_, target) = side.pick(before, after);
+                board.settings = target.clone();
+            }
+            Change::Trash {
+                trash_id,
+                before,
+                after,
+            } => {
+                let (_, target) = side.pick(before, after);
+                match target {
+                    Some(trashed) => board.trash.insert(trash_id.clone(), (**trashed).clone()),
+                    None => board.trash.shift_remove(trash_id),
+                };
+            }
+            Change::Lane { .. } => (),
+        }
+    }
+    for change in changes {
+        if let Change::Lane {
+            lane_id,
+            before,
+            after,
+        } = change
+        {
+            if let (_, None) = side.pick(before, after) {
+                board.lanes.shift_remove(lane_id);
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn apply(
+        board: &mut RetroBoard,
+        participant: &str,
+        scope: Scope,
+        change: impl FnOnce(&mut RetroBoard),
+    ) {
+        let before = Snapshot::of(board, scope);
+        change(board);
+        record(board, participant, "Test", before);
+    }
+
+    fn lane(lane_id: &str) -> Scope {
+        Scope {
+            lanes: vec![lane_id.to_string()],
+            lane_items: vec![lane_id.to_string()],
+            ..Scope::default()
+        }
+    }
+
+    fn items(lane_id: &str, item_ids: &[&str]) -> Scope {
+        Scope {
+            items: item_ids
+                .iter()
+                .map(|item_id| (lane_id.to_string(), item_id.to_string()))
+                .collect(),
+            ..Scope::default()
+        }
+    }
+
+    fn bodies(board: &RetroBoard, lane_id: &str) -> Vec<String> {
+        let mut items: Vec<_> = board.lanes[lane_id].items.values().collect();
+        items.sort_by_key(|item| item.sort_order);
+        items.into_iter().map(|item| item.body.clone()).collect()
+    }
+
+    #[test]
+    fn test_undo_and_redo_remove() {
+        let mut board = RetroBoard::from_template("went-well").unwrap();
+        let id = board.add_item("went-well", "Deploys").unwrap();
+        let scope = Scope {
+            new_trash: true,
+            ..items("went-well", &[&id])
+        };
+        apply(&mut board, "alice", scope, |board| {
+            board.remove_item("went-well", &id, "alice")
+        });
+        assert!(bodies(&board, "went-well").is_empty());
+
+        assert_eq!(board.trash.len(), 1);
+
+        undo(&mut board, "alice", false).unwrap();
+        assert_eq!(bodies(&board, "went-well"), ["Deploys"]);
+        assert!(board.lanes["went-well"].items.contains_key(&id));
+        assert!(board.trash.is_empty());
+
+        redo(&mut board, "alice", false).unwrap();
+        assert!(bodies(&board, "went-well").is_empty());
+        assert_eq!(board.trash.len(), 1);
+        assert_eq!(
+            redo(&mut board, "alice", false).err().unwrap().0,
+            "there is nothing to redo"
+        );
+    }
+
+    #[test]
+    fn test_undo_merge_restores_both_items() {
+        let mut board = RetroBoard::from_template("went-well").unwrap();
+        let a = board.add_item("went-well", "Deploys").unwrap();
+        let b = board.add_item("went-well", "Releases").unwrap();
+        board.upvote_item("went-well", &a);
+        apply(
+            &mut board,
+            "alice",
+            items("went-well", &[&a, &b]),
+            |board| board.merge_items("went-well", &a, &b, "Deploys and releases"),
+        );
+        assert_eq!(bodies(&board, "went-well"), ["Deploys and releases"]);
+
+        undo(&mut board, "alice", false).unwrap();
+        assert_eq!(bodies(&board, "went-well"), ["Deploys", "Releases"]);
+        assert_eq!(board.lanes["went-well"].items[&a].vote_count, 1);
+        assert_eq!(board.lanes["went-well"].items[&b].vote_count, 0);
+    }
+
+    #[test]
+    fn test_undo_is_per_participant() {
+        let mut board = RetroBoard::from_template("went-well").unwrap();
+        apply(&mut board, "alice", lane("went-well"), |board| {
+            board.add_item("went-well", "From alice");
+        });
+        apply(&mut board, "bob", lane("to-improve"), |board| {
+            board.add_item("to-improve", "From bob");
+        });
+
+        undo(&mut board, "alice", false).unwrap();
+        assert!(bodies(&board, "went-well").is_empty());
+        assert_eq!(bodies(&board, "to-improve"), ["From bob"]);
+        assert!(undo(&mut board, "alice", false).is_err());
+
+        // A facilitator can undo anyone's latest action
+        undo(&mut board, "carol", true).unwrap();
+        assert!(bodies(&board, "to-improve").is_empty());
+        redo(&mut board, "carol", true).unwrap();
+        assert_eq!(bodies(&board, "to-improve"), ["From bob"]);
+    }
+
+    #[test]
+    fn test_undo_refuses_to_overwrite_newer_changes() {
+        let mut board = RetroBoard::from_template("went-well").unwrap();
+        let id = board.add_item("went-well", "Deploys").unwrap();
+        apply(&mut board, "alice", items("went-well", &[&id]), |board| {
+            board.edit_item("went-well", &id, "Deploys were smooth")
+        });
+        apply(&mut board, "bob", items("went-well", &[&id]), |board| {
+            board.edit_item("went-well", &id, "Deploys were very smooth")
+        });
+
+        let rejection = undo(&mut board, "alice", false).err().unwrap();
+        assert_eq!(rejection.0, format!("item '{}' has changed since", id));
+        assert_eq!(bodies(&board, "went-well"), ["Deploys were very smooth"]);
+    }
+
+    #[test]
+    fn test_undo_lane_and_votes() {
+        let mut board = RetroBoard::from_template("went-well").unwrap();
+        let id = board.add_item("went-well", "Deploys").unwrap();
+        apply(&mut board, "alice", lane("Kudos"), |board| {
+            board.add_lane("Kudos")
+        });
+        let scope = Scope {
+            votes: vec!["alice".to_string()],
+            ..items("went-well", &[&id])
+        };
+        apply(&mut board, "alice", scope, |board| {
+            board.upvote_item("went-well", &id);
+            *board.votes_cast.entry("alice".to_string()).or_default() += 1;
+        });
+
+        undo(&mut board, "alice", false).unwrap();
+        assert_eq!(board.lanes["went-well"].items[&id].vote_count, 0);
+        assert!(board.votes_cast.is_empty());
+
+        undo(&mut board, "alice", false).unwrap();
+        assert!(!board.lanes.contains_key("Kudos"));
+        redo(&mut board, "alice", false).unwrap();
+        assert_eq!(board.lanes.get_index_of("Kudos"), Some(3));
+    }
+
+    #[test]
+    fn test_undo_group_removal() {
+        let mut board = RetroBoard::from_template("went-well").unwrap();
+        let item = board.add_item("went-well", "Deploys").unwrap();
+        let group = board.add_group("went-well", "Shipping").unwrap();
+        board.group_item("went-well", &item, Some(&group));
+        apply(&mut board, "alice", lane("went-well"), |board| {
+            board.remove_group("went-well", &group)
+        });
+        assert!(board.lanes["went-well"].groups.is_empty());
+
+        undo(&mut board, "alice", false).unwrap();
+        let lane = &board.lanes["went-well"];
+        assert_eq!(lane.groups[&group].title, "Shipping");
+        assert_eq!(lane.items[&item].group.as_deref(), Some(group.as_str()));
+    }
+
+    #[test]
+    fn test_new_action_clears_redo() {
+        let mut board = RetroBoard::from_template("went-well").unwrap();
+        apply(&mut board, "alice", lane("went-well"), |board| {
+            board.add_item("went-well", "First");
+        });
+        undo(&mut board, "alice", false).unwrap();
+        apply(&mut board, "alice", lane("went-well"), |board| {
+            board.add_item("went-well", "Second");
+        });
+        assert!(redo(&mut board, "alice", false).is_err());
+        assert_eq!(board.history.entries().count(), 1);
+    }
+
+    #[test]
+    fn test_history_is_bounded() {
+        let mut board = RetroBoard::from_template("went-well").unwrap();
+        for n in 0..MAX_ENTRIES + 5 {
+            apply(&mut board, "alice", lane("went-well"), |board| {
+                board.add_item("went-well", &n.to_string());
+            });
+        }
+        assert_eq!(board.history.entries().count(), MAX_ENTRIES);
+        assert_eq!(board.history.entries().next().unwrap().action, "Test");
+    }
+}
@@ -0,0 +1,647 @@
+//! Importing boards exported from this or another instance or from other
+//! retro tools, or new boards from cards in CSV or Markdown.
+//!
+//! An import always creates a new board. The document is checked as a whole
+//! before anything is stored, and every problem found is reported at once so
+//! a broken export can be fixed in one go.
+
+use crate::cards;
+use crate::config::Limits;
+use crate::export::Format;
+use crate::external;
+use crate::history::History;
+use crate::retroboard::{Deleted, RetroBoard, RetroItem};
+use crate::schema;
+use crate::AppState;
+use axum::{
+    extract::{Query, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    routing::post,
+    Json, Router,
+};
+use serde::de::{DeserializeSeed, Deserializer, MapAccess, SeqAccess, Visitor};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::sync::Arc;
+
+pub fn routes() -> Router<Arc<AppState>> {
+    Router::new().route("/api/boards/import", post(import))
+}
+
+#[derive(Debug, Serialize)]
+pub struct ImportReport {
+    pub errors: Vec<String>,
+}
+
+#[derive(Serialize)]
+struct Imported {
+    id: String,
+    title: String,
+    /// What an export from another tool had that the board cannot hold
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    unmapped: Vec<String>,
+}
+
+#[derive(Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum ImportFormat {
+    /// A board exported from this app
+    #[default]
+    Json,
+    Csv,
+    Markdown,
+    Trello,
+    EasyRetro,
+}
+
+#[derive(Deserialize)]
+struct ImportParams {
+    #[serde(default)]
+    format: ImportFormat,
+    /// Title of a board made from cards
+    title: Option<String>,
+}
+
+async fn import(
+    State(state): State<Arc<AppState>>,
+    Query(params): Query<ImportParams>,
+    body: String,
+) -> Response {
+    let title = params.title.as_deref();
+    let limits = &state.limits;
+    let mut unmapped = Vec::new();
+    let parsed = match params.format {
+        ImportFormat::Json => parse_board(&body, limits),
+        ImportFormat::Csv => parse_cards(&body, Format::Csv, title, limits),
+        ImportFormat::Markdown => parse_cards(&body, Format::Markdown, title, limits),
+        ImportFormat::Trello => parse_external(external::from_trello(&body), limits, &mut unmapped),
+        ImportFormat::EasyRetro => {
+            parse_external(external::from_easyretro(&body), limits, &mut unmapped)
+        }
+    };
+    let board = match parsed {
+        Ok(board) => board,
+        Err(report) => return (StatusCode::UNPROCESSABLE_ENTITY, Json(report)).into_response(),
+    };
+
+    let title = board.title.clone();
+    match state.create_board(board).await {
+        Some(id) => (
+            StatusCode::CREATED,
+            Json(Imported {
+                id,
+                title,
+                unmapped,
+            }),
+        )
+            .into_response(),
+        None => (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ImportReport {
+                errors: vec!["the board could not be stored".to_string()],
+            }),
+        )
+            .into_response(),
+    }
+}
+
+/// Parse and check an exported board document.
+pub fn parse_board(json: &str, limits: &Limits) -> Result<RetroBoard, ImportReport> {
+    let fail = |error: String| ImportReport {
+        errors: vec![error],
+    };
+
+    let duplicates = duplicate_keys(json).map_err(|e| fail(format!("invalid JSON: {}", e)))?;
+    let mut board = schema::from_json(json).map_err(|e| fail(e.to_string()))?;
+    forget_participants(&mut board);
+
+    let mut errors: Vec<String> = duplicates
+        .into_iter()
+        .map(|path| format!("{}: duplicate key", path))
+        .collect();
+    errors.extend(validate(&board, limits));
+    if errors.is_empty() {
+        Ok(board)
+    } else {
+        Err(ImportReport { errors })
+    }
+}
+
+/// Build a board from cards in CSV or Markdown.
+fn parse_cards(
+    text: &str,
+    format: Format,
+    title: Option<&str>,
+    limits: &Limits,
+) -> Result<RetroBoard, ImportReport> {
+    let parsed = cards::parse(text, format).map_err(|errors| ImportReport { errors })?;
+    let board = cards::to_board(parsed, title, limits).map_err(|error| ImportReport {
+        errors: vec![error],
+    })?;
+    let errors = validate(&board, limits);
+    if errors.is_empty() {
+        Ok(board)
+    } else {
+        Err(ImportReport { errors })
+    }
+}
+
+/// Check a board converted from another tool, keeping what it could not map.
+fn parse_external(
+    converted: Result<external::Converted, String>,
+    limits: &Limits,
+    unmapped: &mut Vec<String>,
+) -> Result<RetroBoard, ImportReport> {
+    let converted = converted.map_err(|error| ImportReport {
+        errors: vec![error],
+    })?;
+    let errors = validate(&converted.board, limits);
+    if !errors.is_empty() {
+        return Err(ImportReport { errors });
+    }
+    *unmapped = converted.unmapped;
+    Ok(converted.board)
+}
+
+/// Drop what an upload could use to act as someone: the undo history, and
+/// who wrote each item and comment, which decides who may change them.
+fn forget_participants(board: &mut RetroBoard) {
+    fn forget(item: &mut RetroItem) {
+        item.author = None;
+        item.anonymous = false;
+        for comment in item.comments.values_mut() {
+            comment.author.clear();
+            comment.anonymous = false;
+        }
+        for merged in &mut item.merged {
+            forget(&mut merged.item);
+        }
+    }
+
+    board.history = History::default();
+    for lane in board.lanes.values_mut() {
+        lane.items.values_mut().for_each(forget);
+    }
+    for trashed in board.trash.values_mut() {
+        match &mut trashed.deleted {
+            Deleted::Item { item, .. } => forget(item),
+            Deleted::Lane { lane, .. } => lane.items.values_mut().for_each(forget),
+        }
+    }
+}
+
+/// Everything wrong with a board that would otherwise load.
+pub fn validate(board: &RetroBoard, limits: &Limits) -> Vec<String> {
+    let mut errors = Vec::new();
+
+    if board.title.trim().is_empty() {
+        errors.push("title: must not be empty".to_string());
+    }
+    if board.lanes.is_empty() {
+        errors.push("lanes: the board has no lanes".to_string());
+    }
+    if board.lanes.len() > limits.max_lanes {
+        errors.push(format!(
+            "lanes: {} lanes, at most {} are allowed",
+            board.lanes.len(),
+            limits.max_lanes
+        ));
+    }
+
+    // Item ID -> lane it was first seen in
+    let mut item_lanes: HashMap<&str, &str> = HashMap::new();
+    for (lane_id, lane) in &board.lanes {
+        let path = format!("lanes.{}", lane_id);
+        if lane.title.trim().is_empty() {
+            errors.push(format!("{}.title: must not be empty", path));
+        }
+        if lane.items.len() > limits.max_items_per_lane {
+            errors.push(format!(
+                "{}.items: {} items, at most {} are allowed",
+                path,
+                lane.items.len(),
+                limits.max_items_per_lane
+            ));
+        }
+
+        // Sort item IDs so errors come out in a stable order
+        let mut item_ids: Vec<_> = lane.items.keys().collect();
+        item_ids.sort();
+        let mut sort_orders: HashMap<u64, &str> = HashMap::new();
+        for item_id in item_ids {
+            let item = &lane.items[item_id];
+            let item_path = format!("{}.items.{}", path, item_id);
+            if let Some(other_lane) = item_lanes.insert(item_id, lane_id) {
+                errors.push(format!(
+                    "{}: item ID is also used in lane '{}'",
+                    item_path, other_lane
+                ));
+            }
+            if item.body.trim().is_empty() {
+                errors.push(format!("{}.body: must not be empty", item_path));
+            }
+            if item.body.chars().count() > limits.max_body_length {
+                errors.push(format!(
+                    "{}.body: longer than {} characters",
+                    item_path, limits.max_body_length
+                ));
+            }
+            if let Some(other_item) = sort_orders.insert(item.sort_order, item_id) {
+                errors.push(format!(
+                    "{}.sort_order: {} is also used by item '{}'",
+                    item_path, item.sort_order, other_item
+                ));
+            }
+            if let Some(group) = item
+                .group
+                .as_ref()
+                .filter(|g| !lane.groups.contains_key(*g))
+            {
+                errors.push(format!(
+                    "{}.group: no group '{}' in the lane",
+                    item_path, group
+                ));
+            }
+        }
+    }
+
+    // A trashed item can only be restored into a lane that is on the board
+    // or in the trash itself
+    for (trash_id, trashed) in &board.trash {
+        let Deleted::Item { lane_id, .. } = &trashed.deleted else {
+            continue;
+        };
+        let lane_trashed = board.trash.values().any(
+            |other| matches!(&other.deleted, Deleted::Lane { lane_id: id, .. } if id == lane_id),
+        );
+        if !board.lanes.contains_key(lane_id) && !lane_trashed {
+            errors.push(format!(
+                "trash.{}.lane_id: no lane '{}' on the board or in the trash",
+                trash_id, lane_id
+            ));
+        }
+    }
+    errors
+}
+
+/// Paths of object keys that appear more than once in the same object.
+/// Parsing into a map silently keeps the last one, losing data.
+fn duplicate_keys(json: &str) -> serde_json::Result<Vec<String>> {
+    let mut duplicates = Vec::new();
+    let mut deserializer = serde_json::Deserializer::from_str(json);
+    KeyCheck {
+        path: String::new(),
+        duplicates: &mut duplicates,
+    }
+    .deserialize(&mut deserializer)?;
+    deserializer.end()?;
+    Ok(duplicates)
+}
+
+/// Walks a JSON document without building it, recording duplicate keys.
+struct KeyCheck<'a> {
+    path: String,
+    duplicates: &'a mut Vec<String>,
+}
+
+impl KeyCheck<'_> {
+    fn child(&mut self, key: &str) -> KeyCheck<'_> {
+        let path = if self.path.is_empty() {
+            key.to_string()
+        } else {
+            format!("{}.{}", self.path, key)
+        };
+        KeyCheck {
+            path,
+            duplicates: self.duplicates,
+        }
+    }
+}
+
+impl<'de> DeserializeSeed<'de> for KeyCheck<'_> {
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        deserializer.deserialize_any(self)
+    }
+}
+
+impl<'de> Visitor<'de> for KeyCheck<'_> {
+    type Value = ();
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "any JSON value")
+    }
+
+    fn visit_bool<E>(self, _: bool) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_i64<E>(self, _: i64) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_u64<E>(self, _: u64) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_f64<E>(self, _: f64) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_str<E>(self, _: &str) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_unit<E>(self) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(mut self, mut seq: A) -> Result<(), A::Error> {
+        let mut index = 0;
+        while seq
+            .next_element_seed(self.child(&index.to_string()))?
+            .is_some()
+        {
+            index += 1;
+        }
+        Ok(())
+    }
+
+    fn visit_map<A: MapAccess<'de>>(mut self, mut map: A) -> Result<(), A::Error> {
+        let mut seen = HashSet::new();
+        while let Some(key) = map.next_key::<String>()? {
+            let child = self.child(&key);
+            if !seen.insert(key) {
+                child.duplicates.push(child.path.clone());
+            }
+            map.next_value_seed(child)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::boards::DEFAULT_BOARD;
+    use crate::{router, test_app_state};
+    use axum::body::{to_bytes, Body};
+    use axum::http::Request;
+    use tower::ServiceExt;
+
+    fn fixture() -> String {
+        std::fs::read_to_string(format!(
+            "{}/fixtures/schema/v{}.json",
+            env!("CARGO_MANIFEST_DIR"),
+            schema::CURRENT_VERSION
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_valid_export_is_accepted() {
+        let board = parse_board(&fixture(), &Limits::default()).unwrap();
+        assert_eq!(board.title, "Sprint 42 Retro");
+    }
+
+    #[test]
+    fn test_import_forgets_history_and_authors() {
+        let board = parse_board(&fixture(), &Limits::default()).unwrap();
+        assert_eq!(board.history.entries().count(), 0);
+        let lanes = || board.lanes.values().flat_map(|lane| lane.items.values());
+        assert!(lanes().all(|item| item.author.is_none()));
+        let comments = lanes().flat_map(|item| item.comments.values());
+        assert!(comments.clone().count() > 0);
+        assert!(comments
+            .into_iter()
+            .all(|comment| comment.author.is_empty()));
+    }
+
+    #[test]
+    fn test_unresolved_references_are_reported() {
+        let json = r#"{
+            "schema_version": 4,
+            "title": "Dangling",
+            "votes_cast": {},
+            "history": {"entries": [], "undo_count": 0},
+            "lanes": {
+                "a": {"title": "A", "theme": "went-well", "items": {
+                    "x": {"body": "Grouped", "vote_count": 0, "sort_order": 0, "group": "g9"}
+                }}
+            },
+            "trash": {
+                "t1": {"deleted_by": "bob", "deleted_at": "2026-03-06T10:15:00Z", "kind": "Item",
+                       "lane_id": "gone", "item_id": "y",
+                       "item": {"body": "Lost", "vote_count": 0, "sort_order": 1}}
+            }
+        }"#;
+        let report = parse_board(json, &Limits::default()).err().unwrap();
+        assert_eq!(
+            report.errors,
+            [
+                "lanes.a.items.x.group: no group 'g9' in the lane",
+                "trash.t1.lane_id: no lane 'gone' on the board or in the trash",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let json = schema::to_json(&RetroBoard::default());
+        let board = parse_board(&json, &Limits::default()).unwrap();
+        assert_eq!(board.lanes.len(), 3);
+    }
+
+    #[test]
+    fn test_invalid_json() {
+        let report = parse_board("{", &Limits::default()).err().unwrap();
+        assert_eq!(report.errors.len(), 1);
+        assert!(report.errors[0].starts_with("invalid JSON"));
+    }
+
+    #[test]
+    fn test_all_problems_are_reported() {
+        let json = r#"{
+            "schema_version": 2,
+            "title": "Broken",
+            "votes_cast": {},
+            "lanes": {
+                "a": {"title": "A", "theme": "went-well", "items": {
+                    "x": {"body": "First", "vote_count": 0, "sort_order": 0},
+                    "y": {"body": "Second", "vote_count": 0, "sort_order": 0}
+                }},
+                "b": {"title": "", "theme": "to-improve", "items": {
+                    "x": {"body": "Copy", "vote_count": 0, "sort_order": 0},
+                    "z": {"body": "Once", "vote_count": 0, "sort_order": 1},
+                    "z": {"body": "Twice", "vote_count": 0, "sort_order": 2}
+                }}
+            }
+        }"#;
+        let report = parse_board(json, &Limits::default()).err().unwrap();
+        assert_eq!(
+            report.errors,
+            [
+                "lanes.b.items.z: duplicate key",
+                "lanes.a.items.y.sort_order: 0 is also used by item 'x'",
+                "lanes.b.title: must not be empty",
+                "lanes.b.items.x: item ID is also used in lane 'a'",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_board_without_lanes() {
+        let json = schema::to_json(&RetroBoard::new("Empty"));
+        let report = parse_board(&json, &Limits::default()).err().unwrap();
+        assert_eq!(report.errors, ["lanes: the board has no lanes"]);
+    }
+
+    #[test]
+    fn test_limits_are_enforced() {
+        let limits = Limits {
+            max_lanes: 2,
+            ..Limits::default()
+        };
+        let errors = validate(&RetroBoard::default(), &limits);
+        assert_eq!(errors, ["lanes: 3 lanes, at most 2 are allowed"]);
+    }
+
+    #[tokio::test]
+    async fn test_import_creates_new_board() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = Arc::new(test_app_state(
+            RetroBoard::default(),
+            dir.path().join("retroboard.json"),
+        ));
+
+        let response = router(state.clone())
+            .oneshot(
+                Request::post("/api/boards/import")
+                    .body(Body::from(fixture()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        let id = body["id"].as_str().unwrap();
+        assert_ne!(id, DEFAULT_BOARD);
+        assert_eq!(body["title"], "Sprint 42 Retro");
+
+        // The default board is untouched and the import is stored on its own
+        assert_eq!(
+            state.board(DEFAULT_BOARD).unwrap().read().title,
+            "My Retro Board"
+        );
+        assert_eq!(state.board(id).unwrap().read().title, "Sprint 42 Retro");
+        let stored =
+            RetroBoard::load_from_file(dir.path().join(format!("boards/{}.json", id))).unwrap();
+        assert_eq!(stored.title, "Sprint 42 Retro");
+    }
+
+    #[tokio::test]
+    async fn test_import_new_board_from_markdown() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = Arc::new(test_app_state(
+            RetroBoard::default(),
+            dir.path().join("retroboard.json"),
+        ));
+
+        let response = router(state.clone())
+            .oneshot(
+                Request::post("/api/boards/import?format=markdown")
+                    .body(Body::from(
+                        "# Feedback
+## Kudos
+- Thanks (2 votes)
+## Ideas
+",
+                    ))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["title"], "Feedback");
+
+        let board = state.board(body["id"].as_str().unwrap()).unwrap();
+        let board = board.read();
+        let lanes: Vec<_> = board
+            .lanes
+            .values()
+            .map(|lane| lane.title.as_str())
+            .collect();
+        assert_eq!(lanes, ["Kudos", "Ideas"]);
+        assert_eq!(
+            board.lanes["kudos"]
+                .items
+                .values()
+                .next()
+                .unwrap()
+                .vote_count,
+            2
+        );
+    }
+
+    #[tokio::test]
+    async fn test_import_from_trello_reports_unmapped() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = Arc::new(test_app_state(
+            RetroBoard::default(),
+            dir.path().join("retroboard.json"),
+        ));
+        let trello = r#"{
+            "name": "Trello Retro",
+            "lists": [{"id": "l1", "name": "Went Well", "pos": 1}],
+            "cards": [{"name": "Deploys", "idList": "l1", "badges": {"votes": 1, "comments": 2}}]
+        }"#;
+
+        let response = router(state.clone())
+            .oneshot(
+                Request::post("/api/boards/import?format=trello")
+                    .body(Body::from(trello))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["title"], "Trello Retro");
+        assert_eq!(
+            body["unmapped"],
+            serde_json::json!(["card 'Deploys': 2 comments not imported"])
+        );
+        let board = state.board(body["id"].as_str().unwrap()).unwrap();
+        assert_eq!(board.read().lanes["went-well"].items.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_import_reports_errors() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = Arc::new(test_app_state(
+            RetroBoard::default(),
+            dir.path().join("retroboard.json"),
+        ));
+
+        let response = router(state.clone())
+            .oneshot(
+                Request::post("/api/boards/import")
+                    .body(Body::from(schema::to_json(&RetroBoard::new("Empty"))))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["errors"][0], "lanes: the board has no lanes");
+        assert_eq!(state.boards.ids(), [DEFAULT_BOARD]);
+    }
+}
@@ -1,208 +1,456 @@
+mod action;
+mod action_items;
+mod boards;
+mod cards;
+mod client;
+mod config;
+mod export;
+mod external;
+mod health;
+mod history;
+mod import;
+mod metrics;
+mod redis_fanout;
+mod report;
 mod retroboard;
+mod schema;
+mod template;
 mod tofile;
+use crate::action::{Action, ActionContext, Rejection, Update};
+use crate::boards::{Board, Boards, DEFAULT_BOARD};
+use crate::config::{Config, Limits, Storage};
+use crate::metrics::Metrics;
+use crate::redis_fanout::{ApplyError, RedisFanout};
 use crate::retroboard::RetroBoard;
 
 use axum::{
     extract::{
-        ws::{Message, WebSocket, WebSocketUpgrade},
-        State,
+        ws::{close_code, CloseFrame, Message, WebSocket, WebSocketUpgrade},
+        Query, State,
     },
-    response::IntoResponse,
+    http::StatusCode,
+    response::{IntoResponse, Response},
     routing::get,
     Router,
 };
 use futures::{sink::SinkExt, stream::StreamExt};
 use serde::{Deserialize, Serialize};
-use std::sync::{Arc, RwLock};
-use tokio::sync::broadcast;
+use sha2::{Digest, Sha256};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::{broadcast, mpsc, watch};
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
-#[derive(Serialize, Deserialize)]
-#[serde(tag = "type")]
-enum Action {
-    AddLane {
-        title: String,
-    },
-    AddItem {
-        lane_id: String,
-        body: String,
-    },
-    RemoveItem {
-        lane_id: String,
-        id: String,
-    },
-    UpvoteItem {
-        lane_id: String,
-        id: String,
-    },
-    MoveItem {
-        from_lane_id: String,
-        to_lane_id: String,
-        item_id: String,
-    },
-    ReorderItem {
-        lane_id: String,
-        item_id: String,
-        new_position: u64,
-    },
-    EditItem {
-        lane_id: String,
-        id: String,
-        body: String,
-    },
-    MergeItems {
-        lane_id: String,
-        source_id: String,
-        target_id: String,
-        merged_body: String,
-    },
+struct AppState {
+    boards: Boards,
+    /// Set when running with shared Redis boards. The local boards are then
+    /// only a cache of the latest snapshots published through Redis.
+    redis: Option<RedisFanout>,
+    /// File the default board is kept in
+    board_file: PathBuf,
+    /// Directory every other board is kept in, one JSON file each
+    boards_dir: PathBuf,
+    /// Directory user-defined export templates are read from
+    templates_dir: PathBuf,
+    /// Built-in template new boards start from when none is chosen
+    default_template: String,
+    limits: Limits,
+    /// Connections presenting this token act as facilitators
+    facilitator_token: Option<String>,
+    metrics: Metrics,
+    /// Whether the local boards reflect storage. Cleared while the Redis
+    /// subscription is down, since snapshots may be missed.
+    board_loaded: AtomicBool,
+    /// Flipped to `true` on shutdown. Every websocket holds a receiver, so
+    /// `shutdown.closed()` resolves once all of them have disconnected.
+    shutdown: watch::Sender<bool>,
 }
 
-struct AppState {
-    board: RwLock<RetroBoard>,
-    tx: broadcast::Sender<String>,
+/// Messages sent to clients other than board snapshots.
+#[derive(Serialize)]
+#[serde(tag = "type")]
+enum ServerMessage {
+    /// Sent before the server closes connections on shutdown, telling the
+    /// client how long to wait before reconnecting.
+    ServerRestarting { retry_after_ms: u64 },
+    /// Sent only to the client whose action was refused. The board is
+    /// unchanged, so nothing is broadcast.
+    ActionRejected { reason: String },
 }
 
-const BOARD_FILE: &str = "./retroboard.json";
+const REDIS_RETRY_DELAY: Duration = Duration::from_secs(1);
+/// How long clients and in-flight actions get to finish during shutdown.
+const SHUTDOWN_GRACE: Duration = Duration::from_secs(5);
+const RECONNECT_AFTER: Duration = Duration::from_secs(3);
 
 impl AppState {
-    /// Acquire a read lock on the board, recovering from a poisoned lock.
-    fn read_board(&self) -> std::sync::RwLockReadGuard<'_, RetroBoard> {
-        match self.board.read() {
-            Ok(guard) => guard,
-            Err(poisoned) => {
-                tracing::error!("Board RwLock was poisoned — recovering");
-                poisoned.into_inner()
-            }
+    fn board(&self, id: &str) -> Option<Arc<Board>> {
+        self.boards.get(id)
+    }
+
+    fn board_path(&self, id: &str) -> PathBuf {
+        if id == DEFAULT_BOARD {
+            self.board_file.clone()
+        } else {
+            self.boards_dir.join(format!("{}.json", id))
         }
     }
 
-    /// Acquire a write lock on the board, recovering from a poisoned lock.
-    fn write_board(&self) -> std::sync::RwLockWriteGuard<'_, RetroBoard> {
-        match self.board.write() {
-            Ok(guard) => guard,
-            Err(poisoned) => {
-                tracing::error!("Board RwLock was poisoned — recovering");
-                poisoned.into_inner()
-            }
+    fn action_context<'a>(&'a self, participant: &'a str, facilitator: bool) -> ActionContext<'a> {
+        ActionContext {
+            participant,
+            facilitator,
+            limits: &self.limits,
         }
     }
 
-    fn process_action(&self, action: Action) {
-        match action {
-            Action::AddLane { title } => {
-                tracing::debug!("Adding lane: {}", title);
-                let mut board = self.write_board();
-                board.add_lane(&title);
-                board.save_to_file(BOARD_FILE);
-            }
-            Action::AddItem { lane_id, body } => {
-                tracing::debug!("Adding item to lane {}: {}", lane_id, body);
-                let mut board = self.write_board();
-                board.add_item(&lane_id, &body);
-                board.save_to_file(BOARD_FILE);
-            }
-            Action::RemoveItem { lane_id, id } => {
-                tracing::debug!("Removing item from lane {}: {}", lane_id, id);
-                let mut board = self.write_board();
-                board.remove_item(&lane_id, &id);
-                board.save_to_file(BOARD_FILE);
-            }
-            Action::UpvoteItem { lane_id, id } => {
-                tracing::debug!("Upvoting item in lane {}: {}", lane_id, id);
-                let mut board = self.write_board();
-                board.upvote_item(&lane_id, &id);
-                board.save_to_file(BOARD_FILE);
+    /// Apply an action to a local board. Returns the update to broadcast
+    /// instead of a snapshot, if there is one.
+    fn process_action(
+        &self,
+        board_id: &str,
+        participant: &str,
+        facilitator: bool,
+        action: Action,
+    ) -> Result<Option<Update>, Rejection> {
+        self.metrics
+            .actions
+            .with_label_values(&[action.name()])
+            .inc();
+        let Some(board) = self.board(board_id) else {
+            tracing::error!("Board '{}' not found", board_id);
+            return Err(Rejection(format!("board '{}' does not exist", board_id)));
+        };
+        let mut board = board.write();
+        let update = action
+            .apply_with_update(&mut board, &self.action_context(participant, facilitator))
+            .inspect_err(|_| {
+                self.metrics
+                    .action_errors
+                    .with_label_values(&["rejected"])
+                    .inc();
+            })?;
+        let _ = self.save(board_id, &board);
+        Ok(update)
+    }
+
+    /// Apply an action to the board shared through Redis. The relay task
+    /// broadcasts the result once Redis publishes it.
+    async fn process_shared_action(
+        &self,
+        redis: &RedisFanout,
+        board_id: &str,
+        participant: &str,
+        facilitator: bool,
+        action: Action,
+    ) -> Result<(), Rejection> {
+        self.metrics
+            .actions
+            .with_label_values(&[action.name()])
+            .inc();
+        let timer = self
+            .metrics
+            .persist_seconds
+            .with_label_values(&["redis"])
+            .start_timer();
+        let result = redis
+            .apply(
+                board_id,
+                action,
+                &self.action_context(participant, facilitator),
+            )
+            .await;
+        timer.observe_duration();
+
+        match result {
+            Ok(_) => (),
+            Err(ApplyError::Rejected(rejection)) => {
+                tracing::debug!("Action rejected: {}", rejection);
+                self.metrics
+                    .action_errors
+                    .with_label_values(&["rejected"])
+                    .inc();
+                return Err(rejection);
             }
-            Action::MoveItem {
-                from_lane_id,
-                to_lane_id,
-                item_id,
-            } => {
-                tracing::debug!(
-                    "Moving item {} from lane {} to lane {}",
-                    item_id,
-                    from_lane_id,
-                    to_lane_id
-                );
-                let mut board = self.write_board();
-                board.move_item(&from_lane_id, &to_lane_id, &item_id);
-                board.save_to_file(BOARD_FILE);
+            Err(ApplyError::Redis(e)) => {
+                tracing::error!("Failed to apply action through Redis: {:?}", e);
+                self.metrics
+                    .action_errors
+                    .with_label_values(&["storage"])
+                    .inc();
+                self.metrics
+                    .persist_failures
+                    .with_label_values(&["redis"])
+                    .inc();
             }
-            Action::ReorderItem {
-                lane_id,
-                item_id,
-                new_position,
-            } => {
-                tracing::debug!(
-                    "Reordering item {} in lane {} to position {}",
-                    item_id,
-                    lane_id,
-                    new_position
-                );
-                let mut board = self.write_board();
-                board.reorder_item(&lane_id, &item_id, new_position);
-                board.save_to_file(BOARD_FILE);
+        }
+        Ok(())
+    }
+
+    /// Apply an action that did not come from a websocket, such as one made
+    /// through the HTTP API, and send the result to the board's clients.
+    async fn submit_action(
+        &self,
+        board_id: &str,
+        participant: &str,
+        facilitator: bool,
+        action: Action,
+    ) -> Result<(), ApplyError> {
+        self.metrics
+            .actions
+            .with_label_values(&[action.name()])
+            .inc();
+        let result = self
+            .apply_submitted(board_id, participant, facilitator, action)
+            .await;
+        let reason = match &result {
+            Ok(()) => return result,
+            Err(ApplyError::Rejected(_)) => "rejected",
+            Err(ApplyError::Redis(e)) => {
+                tracing::error!("Failed to apply action through Redis: {:?}", e);
+                "storage"
             }
-            Action::EditItem { lane_id, id, body } => {
-                tracing::debug!("Editing item {} in lane {}: {}", id, lane_id, body);
-                let mut board = self.write_board();
-                board.edit_item(&lane_id, &id, &body);
-                board.save_to_file(BOARD_FILE);
+        };
+        self.metrics
+            .action_errors
+            .with_label_values(&[reason])
+            .inc();
+        result
+    }
+
+    async fn apply_submitted(
+        &self,
+        board_id: &str,
+        participant: &str,
+        facilitator: bool,
+        action: Action,
+    ) -> Result<(), ApplyError> {
+        let ctx = self.action_context(participant, facilitator);
+        if let Some(redis) = &self.redis {
+            // The relay task broadcasts the result once Redis publishes it
+            return redis.apply(board_id, action, &ctx).await.map(|_| ());
+        }
+
+        let Some(board) = self.board(board_id) else {
+            return Err(ApplyError::Rejected(Rejection(format!(
+                "board '{}' does not exist",
+                board_id
+            ))));
+        };
+        let update = {
+            let mut guard = board.write();
+            let update = action
+                .apply_with_update(&mut guard, &ctx)
+                .map_err(ApplyError::Rejected)?;
+            let _ = self.save(board_id, &guard);
+            update
+        };
+        let _ = board.tx.send(self.message(&board, update));
+        Ok(())
+    }
+
+    fn save(&self, board_id: &str, board: &RetroBoard) -> std::io::Result<()> {
+        let path = self.board_path(board_id);
+        let timer = self
+            .metrics
+            .persist_seconds
+            .with_label_values(&["json"])
+            .start_timer();
+        let mut result = Ok(());
+        if board_id != DEFAULT_BOARD {
+            result = std::fs::create_dir_all(&self.boards_dir);
+        }
+        let result = result.and_then(|_| board.save_to_file(&path));
+        timer.observe_duration();
+
+        if let Err(e) = &result {
+            tracing::error!("Failed to save board to {}: {}", path.display(), e);
+            self.metrics
+                .persist_failures
+                .with_label_values(&["json"])
+                .inc();
+        }
+        result
+    }
+
+    /// Store a new board under a fresh ID and start serving it. Returns
+    /// `None` if it could not be stored.
+    async fn create_board(&self, board: RetroBoard) -> Option<String> {
+        let id = boards::new_id();
+        if let Some(redis) = &self.redis {
+            if let Err(e) = redis.create(&id, &board).await {
+                tracing::error!("Failed to create board in Redis: {:?}", e);
+                self.metrics
+                    .persist_failures
+                    .with_label_values(&["redis"])
+                    .inc();
+                return None;
             }
-            Action::MergeItems {
-                lane_id,
-                source_id,
-                target_id,
-                merged_body,
-            } => {
-                tracing::debug!(
-                    "Merging item {} into {} in lane {}",
-                    source_id,
-                    target_id,
-                    lane_id
-                );
-                let mut board = self.write_board();
-                board.merge_items(&lane_id, &source_id, &target_id, &merged_body);
-                board.save_to_file(BOARD_FILE);
+        } else if self.save(&id, &board).is_err() {
+            return None;
+        }
+        tracing::info!("Created board '{}'", id);
+        self.boards.insert(&id, board);
+        Some(id)
+    }
+
+    /// Serialize a board for a broadcast, recording the snapshot size.
+    fn snapshot(&self, board: &Board) -> String {
+        let snapshot = board.read().client_snapshot();
+        self.metrics.snapshot_bytes.observe(snapshot.len() as f64);
+        snapshot
+    }
+
+    /// What to broadcast after an action: its update if it has one, and a
+    /// snapshot of the board otherwise.
+    fn message(&self, board: &Board, update: Option<Update>) -> String {
+        match update {
+            Some(update) => update.to_json(),
+            None => self.snapshot(board),
+        }
+    }
+
+    /// Write every board to disk. With Redis every action is already stored
+    /// before it is broadcast, so there is nothing to flush.
+    fn flush(&self) {
+        if self.redis.is_some() {
+            return;
+        }
+        for id in self.boards.ids() {
+            if let Some(board) = self.board(&id) {
+                let _ = self.save(&id, &board.read());
             }
         }
     }
 }
 
+#[derive(Deserialize)]
+struct ConnectParams {
+    /// Board to join, the default board if not given
+    board: Option<String>,
+    /// Secret key chosen by the client so that its identity survives
+    /// reconnects. The ID other clients see is derived from it, see
+    /// `participant_id`. Clients that don't send one get a new ID for every
+    /// connection.
+    participant: Option<String>,
+    /// Makes the connection a facilitator if it matches the configured token
+    facilitator_token: Option<String>,
+}
+
 async fn websocket_handler(
     ws: WebSocketUpgrade,
+    Query(params): Query<ConnectParams>,
     State(state): State<Arc<AppState>>,
-) -> impl IntoResponse {
-    ws.on_upgrade(|socket| websocket(socket, state))
+) -> Response {
+    let board_id = params.board.unwrap_or_else(|| DEFAULT_BOARD.to_string());
+    let Some(board) = state.board(&board_id) else {
+        return (StatusCode::NOT_FOUND, "No such board").into_response();
+    };
+    let participant = params.participant.map_or_else(
+        || uuid::Uuid::new_v4().to_string(),
+        |key| participant_id(&key),
+    );
+    let facilitator =
+        state.facilitator_token.is_some() && params.facilitator_token == state.facilitator_token;
+    ws.on_upgrade(move |socket| websocket(socket, state, board_id, board, participant, facilitator))
+}
+
+/// The ID a participant is known by, derived from the key their client
+/// connects with. IDs are sent to every client as comment authors, voters
+/// and the like, so they must not work as keys: connecting with someone's ID
+/// gives a different ID.
+fn participant_id(key: &str) -> String {
+    let digest = Sha256::digest(format!("tinyretro participant:{}", key));
+    digest[..16]
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
 }
 
-async fn websocket(stream: WebSocket, state: Arc<AppState>) {
+async fn websocket(
+    stream: WebSocket,
+    state: Arc<AppState>,
+    board_id: String,
+    board: Arc<Board>,
+    participant: String,
+    facilitator: bool,
+) {
     let (mut sender, mut receiver) = stream.split();
 
-    let mut rx = state.tx.subscribe();
+    let mut rx = board.tx.subscribe();
+    // Messages for this client alone, such as why its action was rejected
+    let (own_tx, mut own_rx) = mpsc::unbounded_channel::<String>();
+    let mut shutdown = state.shutdown.subscribe();
 
-    tracing::debug!("New client connected");
-    let board = {
-        let board = state.read_board();
-        serde_json::to_string(&*board).unwrap()
+    let snapshot = {
+        let board = board.read();
+        // Anonymous boards keep who connected out of the logs
+        if board.settings.anonymous {
+            tracing::debug!("New client connected to '{}'", board_id);
+        } else {
+            tracing::debug!("New client connected to '{}': {}", board_id, participant);
+        }
+        board.client_snapshot()
     };
-    sender.send(Message::text(board)).await.unwrap();
+    if sender.send(Message::text(snapshot)).await.is_err() {
+        return;
+    }
+    let connected_sockets = state
+        .metrics
+        .connected_sockets
+        .with_label_values(&[&board_id]);
+    connected_sockets.inc();
 
     // Spawn the first task that will receive broadcast messages and send messages over the websocket to our client
+    let mut send_shutdown = shutdown.clone();
+    let broadcast_lagged = state.metrics.broadcast_lagged.clone();
     let mut send_task = tokio::spawn(async move {
-        while let Ok(msg) = rx.recv().await {
-            if sender.send(Message::text(msg)).await.is_err() {
-                break;
+        loop {
+            tokio::select! {
+                msg = rx.recv() => {
+                    let msg = match msg {
+                        Ok(msg) => msg,
+                        Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                            tracing::warn!("Client fell {} updates behind, disconnecting", skipped);
+                            broadcast_lagged.inc();
+                            break;
+                        }
+                        Err(broadcast::error::RecvError::Closed) => break,
+                    };
+                    if sender.send(Message::text(msg)).await.is_err() {
+                        break;
+                    }
+                }
+                Some(msg) = own_rx.recv() => {
+                    if sender.send(Message::text(msg)).await.is_err() {
+                        break;
+                    }
+                }
+                _ = wait_for_shutdown(&mut send_shutdown) => {
+                    let notice = ServerMessage::ServerRestarting {
+                        retry_after_ms: RECONNECT_AFTER.as_millis() as u64,
+                    };
+                    let notice = serde_json::to_string(&notice).unwrap();
+                    let _ = sender.send(Message::text(notice)).await;
+                    let _ = sender
+                        .send(Message::Close(Some(CloseFrame {
+                            code: close_code::RESTART,
+                            reason: "Server restarting".into(),
+                        })))
+                        .await;
+                    break;
+                }
             }
         }
     });
 
     // Clone things we want to pass (move) to the receiving task
-    let tx = state.tx.clone();
+    let tx = board.tx.clone();
 
-    // Spawn a task that takes messages from the websocket,
-    // and sends them to all broadcast subscribers
+    // Spawn a task that takes messages from the websocket, and sends the
+    // result to all broadcast subscribers, or a rejection to this client alone
     let mut recv_task = tokio::spawn(async move {
         while let Some(Ok(Message::Text(text))) = receiver.next().await {
             let action_result: Result<Action, serde_json::Error> = serde_json::from_str(&text);
@@ -210,22 +458,41 @@ async fn websocket(stream: WebSocket, state: Arc<AppState>) {
                 Ok(action) => action,
                 Err(e) => {
                     tracing::error!("Failed to parse action: {:?}", e);
+                    state
+                        .metrics
+                        .action_errors
+                        .with_label_values(&["invalid"])
+                        .inc();
                     continue;
                 }
             };
 
-            let board = {
-                state.process_action(action);
-                let board = state.read_board();
-                serde_json::to_string(&*board).unwrap()
+            let result = match &state.redis {
+                // The relay task broadcasts the result once Redis publishes it
+                Some(redis) => state
+                    .process_shared_action(redis, &board_id, &participant, facilitator, action)
+                    .await
+                    .map(|()| None),
+                None => state
+                    .process_action(&board_id, &participant, facilitator, action)
+                    .map(|update| Some(state.message(&board, update))),
             };
-
-            let broadcast_result = tx.send(board);
-            match broadcast_result {
-                Ok(_) => (),
-                Err(e) => {
-                    tracing::error!("Failed to broadcast message: {:?}", e);
-                    break;
+            match result {
+                Ok(None) => (),
+                Ok(Some(message)) => {
+                    if let Err(e) = tx.send(message) {
+                        tracing::error!("Failed to broadcast message: {:?}", e);
+                        break;
+                    }
+                }
+                Err(Rejection(reason)) => {
+                    let notice = ServerMessage::ActionRejected { reason };
+                    if own_tx
+                        .send(serde_json::to_string(&notice).unwrap())
+                        .is_err()
+                    {
+                        break;
+                    }
                 }
             }
         }
@@ -233,186 +500,436 @@ async fn websocket(stream: WebSocket, state: Arc<AppState>) {
 
     // If any one of the tasks runs to completion, we abort the other.
     tokio::select! {
-        _ = &mut send_task => recv_task.abort(),
+        _ = &mut send_task => {
+            // On shutdown, let an action that is being applied finish. The
+            // client answering our close frame ends the receive loop.
+            if *shutdown.borrow_and_update() {
+                let _ = tokio::time::timeout(SHUTDOWN_GRACE, &mut recv_task).await;
+            }
+            recv_task.abort();
+        }
         _ = &mut recv_task => send_task.abort(),
     }
+    connected_sockets.dec();
 
     tracing::debug!("Client disconnected");
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use serde_json;
+/// Forward snapshots and updates published through Redis to this instance's
+/// clients, resubscribing if the connection to Redis drops.
+async fn relay_redis_updates(state: Arc<AppState>) {
+    let Some(redis) = &state.redis else {
+        return;
+    };
 
-    #[test]
-    fn test_action_serialization() {
-        // Test AddLane action
-        let add_lane = Action::AddLane {
-            title: "Test Lane".to_string(),
+    loop {
+        let mut updates = match redis.subscribe().await {
+            Ok(updates) => Box::pin(updates),
+            Err(e) => {
+                tracing::error!("Failed to subscribe to Redis updates: {:?}", e);
+                tokio::time::sleep(REDIS_RETRY_DELAY).await;
+                continue;
+            }
         };
-        let json = serde_json::to_string(&add_lane).unwrap();
-        let parsed: Action = serde_json::from_str(&json).unwrap();
-        match parsed {
-            Action::AddLane { title } => assert_eq!(title, "Test Lane"),
-            _ => panic!("Wrong action type"),
+
+        // Catch up on anything published while we were not subscribed
+        match load_shared_boards(&state, redis).await {
+            Ok(()) => state.board_loaded.store(true, Ordering::Relaxed),
+            Err(e) => tracing::error!("Failed to load boards from Redis: {:?}", e),
         }
 
-        // Test AddItem action
-        let add_item = Action::AddItem {
-            lane_id: "lane1".to_string(),
-            body: "Test item".to_string(),
-        };
-        let json = serde_json::to_string(&add_item).unwrap();
-        let parsed: Action = serde_json::from_str(&json).unwrap();
-        match parsed {
-            Action::AddItem { lane_id, body } => {
-                assert_eq!(lane_id, "lane1");
-                assert_eq!(body, "Test item");
+        while let Some((id, message)) = updates.next().await {
+            if let Ok(update) = serde_json::from_str::<Update>(&message) {
+                match state.board(&id) {
+                    Some(board) => {
+                        update.apply_to(&mut board.write());
+                        let _ = board.tx.send(message);
+                    }
+                    None => tracing::error!("Ignoring update for unknown board '{}'", id),
+                }
+                continue;
             }
-            _ => panic!("Wrong action type"),
+            let board = match redis_fanout::parse_board(&message) {
+                Ok(board) => state.boards.insert(&id, board),
+                Err(e) => {
+                    tracing::error!("Ignoring invalid snapshot from Redis: {:?}", e);
+                    continue;
+                }
+            };
+            // Sending only fails when this instance has no connected clients
+            let _ = board.tx.send(state.snapshot(&board));
         }
 
-        // Test RemoveItem action
-        let remove_item = Action::RemoveItem {
-            lane_id: "lane1".to_string(),
-            id: "item1".to_string(),
-        };
-        let json = serde_json::to_string(&remove_item).unwrap();
-        let parsed: Action = serde_json::from_str(&json).unwrap();
-        match parsed {
-            Action::RemoveItem { lane_id, id } => {
-                assert_eq!(lane_id, "lane1");
-                assert_eq!(id, "item1");
-            }
-            _ => panic!("Wrong action type"),
+        state.board_loaded.store(false, Ordering::Relaxed);
+        tracing::error!("Lost Redis subscription, reconnecting");
+        tokio::time::sleep(REDIS_RETRY_DELAY).await;
+    }
+}
+
+/// Replace the local copy of every shared board with the one in Redis.
+async fn load_shared_boards(state: &AppState, redis: &RedisFanout) -> redis::RedisResult<()> {
+    for id in redis.board_ids().await? {
+        if let Some(board) = redis.load(&id).await? {
+            let board = state.boards.insert(&id, board);
+            let _ = board.tx.send(board.read().client_snapshot());
         }
+    }
+    Ok(())
+}
 
-        // Test UpvoteItem action
-        let upvote_item = Action::UpvoteItem {
-            lane_id: "lane1".to_string(),
-            id: "item1".to_string(),
-        };
-        let json = serde_json::to_string(&upvote_item).unwrap();
-        let parsed: Action = serde_json::from_str(&json).unwrap();
-        match parsed {
-            Action::UpvoteItem { lane_id, id } => {
-                assert_eq!(lane_id, "lane1");
-                assert_eq!(id, "item1");
-            }
-            _ => panic!("Wrong action type"),
+fn router(state: Arc<AppState>) -> Router {
+    Router::new()
+        .route("/ws", get(websocket_handler))
+        .merge(boards::routes())
+        .merge(health::routes())
+        .merge(export::routes())
+        .merge(import::routes())
+        .merge(cards::routes())
+        .merge(action_items::routes())
+        .merge(metrics::routes())
+        .merge(template::routes())
+        .with_state(state)
+}
+
+/// An `AppState` backed by a JSON file, with default limits.
+#[cfg(test)]
+fn test_app_state(board: RetroBoard, board_file: PathBuf) -> AppState {
+    let boards = Boards::new(100);
+    boards.insert(DEFAULT_BOARD, board);
+    let boards_dir = board_file.with_file_name("boards");
+    let templates_dir = board_file.with_file_name("templates");
+    AppState {
+        boards,
+        redis: None,
+        board_file,
+        boards_dir,
+        templates_dir,
+        default_template: "went-well".to_string(),
+        limits: Limits::default(),
+        facilitator_token: None,
+        metrics: Metrics::new(),
+        board_loaded: AtomicBool::new(true),
+        shutdown: watch::Sender::new(false),
+    }
+}
+
+/// Connect to Redis and share the default board through it, replacing
+/// `board` with the one stored there if another instance got there first.
+async fn connect_redis(url: &str, board: &mut RetroBoard) -> redis::RedisResult<RedisFanout> {
+    let redis = RedisFanout::connect(url).await?;
+    redis.seed(DEFAULT_BOARD, board).await?;
+    if let Some(stored) = redis.load(DEFAULT_BOARD).await? {
+        *board = stored;
+    }
+    Ok(redis)
+}
+
+#[tokio::main]
+async fn main() {
+    let config = match Config::load() {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("Configuration error: {}", e);
+            std::process::exit(2);
         }
+    };
 
-        // Test MoveItem action
-        let move_item = Action::MoveItem {
-            from_lane_id: "lane1".to_string(),
-            to_lane_id: "lane2".to_string(),
-            item_id: "item1".to_string(),
-        };
-        let json = serde_json::to_string(&move_item).unwrap();
-        let parsed: Action = serde_json::from_str(&json).unwrap();
-        match parsed {
-            Action::MoveItem {
-                from_lane_id,
-                to_lane_id,
-                item_id,
-            } => {
-                assert_eq!(from_lane_id, "lane1");
-                assert_eq!(to_lane_id, "lane2");
-                assert_eq!(item_id, "item1");
+    tracing_subscriber::registry()
+        .with(tracing_subscriber::EnvFilter::new(&config.log))
+        .with(tracing_subscriber::fmt::layer())
+        .init();
+    tracing::debug!("Using configuration: {:?}", config);
+
+    // Setup app state
+    let board_file = config.board_file();
+    let mut board = if board_file.exists() {
+        match RetroBoard::load_from_file(&board_file) {
+            Ok(board) => board,
+            Err(e) => {
+                tracing::error!("Refusing to start: {}", e);
+                std::process::exit(1);
             }
-            _ => panic!("Wrong action type"),
         }
-
-        // Test ReorderItem action
-        let reorder_item = Action::ReorderItem {
-            lane_id: "lane1".to_string(),
-            item_id: "item1".to_string(),
-            new_position: 5,
-        };
-        let json = serde_json::to_string(&reorder_item).unwrap();
-        let parsed: Action = serde_json::from_str(&json).unwrap();
-        match parsed {
-            Action::ReorderItem {
-                lane_id,
-                item_id,
-                new_position,
-            } => {
-                assert_eq!(lane_id, "lane1");
-                assert_eq!(item_id, "item1");
-                assert_eq!(new_position, 5);
+    } else {
+        tracing::info!(
+            "No saved board, starting from the '{}' template",
+            config.default_template
+        );
+        RetroBoard::from_template(&config.default_template).unwrap()
+    };
+    let boards = Boards::new(config.broadcast_capacity);
+    let boards_dir = config.boards_dir();
+    let redis = match &config.storage {
+        Storage::Redis { url } => {
+            tracing::info!(
+                "Sharing boards through Redis at {}",
+                config::without_credentials(url)
+            );
+            let redis = match connect_redis(url, &mut board).await {
+                Ok(redis) => redis,
+                Err(e) => {
+                    tracing::error!("Refusing to start: cannot use Redis: {}", e);
+                    std::process::exit(1);
+                }
+            };
+            // The relay task loads the other boards once subscribed
+            Some(redis)
+        }
+        Storage::Json => {
+            for (id, board) in boards::load_dir(&boards_dir) {
+                boards.insert(&id, board);
             }
-            _ => panic!("Wrong action type"),
+            None
         }
+    };
+    boards.insert(DEFAULT_BOARD, board);
+    let app_state = Arc::new(AppState {
+        boards,
+        // With Redis, the relay task marks the boards loaded once subscribed
+        board_loaded: AtomicBool::new(redis.is_none()),
+        redis,
+        board_file,
+        boards_dir,
+        templates_dir: config.templates_dir(),
+        default_template: config.default_template,
+        limits: config.limits,
+        facilitator_token: config.facilitator_token,
+        metrics: Metrics::new(),
+        shutdown: watch::Sender::new(false),
+    });
 
-        // Test EditItem action
-        let edit_item = Action::EditItem {
-            lane_id: "lane1".to_string(),
-            id: "item1".to_string(),
-            body: "Updated body".to_string(),
+    if app_state.redis.is_some() {
+        tokio::spawn(relay_redis_updates(app_state.clone()));
+    }
+
+    let mut app = router(app_state.clone());
+    if let Some(dir) = &config.static_dir {
+        tracing::info!("Serving the web client from {}", dir.display());
+        app = app.merge(client::routes(dir));
+    }
+
+    let listener = tokio::net::TcpListener::bind(config.listen).await.unwrap();
+
+    tracing::debug!("Listening on: {}", listener.local_addr().unwrap());
+    let shutdown_state = app_state.clone();
+    axum::serve(listener, app)
+        .with_graceful_shutdown(async move {
+            shutdown_signal().await;
+            tracing::info!("Shutting down, closing client connections");
+            shutdown_state.shutdown.send_replace(true);
+        })
+        .await
+        .unwrap();
+
+    // axum does not track upgraded connections, so wait for the websockets here
+    if tokio::time::timeout(SHUTDOWN_GRACE, app_state.shutdown.closed())
+        .await
+        .is_err()
+    {
+        tracing::warn!("Some clients did not disconnect in time");
+    }
+    app_state.flush();
+    tracing::info!("Shutdown complete");
+}
+
+async fn wait_for_shutdown(shutdown: &mut watch::Receiver<bool>) {
+    let _ = shutdown.wait_for(|stopping| *stopping).await;
+}
+
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        tokio::signal::ctrl_c()
+            .await
+            .expect("failed to install Ctrl+C handler");
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+            .expect("failed to install SIGTERM handler")
+            .recv()
+            .await;
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => (),
+        _ = terminate => (),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_server_restarting_message() {
+        let message = ServerMessage::ServerRestarting {
+            retry_after_ms: 3000,
         };
-        let json = serde_json::to_string(&edit_item).unwrap();
-        let parsed: Action = serde_json::from_str(&json).unwrap();
-        match parsed {
-            Action::EditItem { lane_id, id, body } => {
-                assert_eq!(lane_id, "lane1");
-                assert_eq!(id, "item1");
-                assert_eq!(body, "Updated body");
-            }
-            _ => panic!("Wrong action type"),
-        }
+        let json: serde_json::Value =
+            serde_json::from_str(&serde_json::to_string(&message).unwrap()).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!({"type": "ServerRestarting", "retry_after_ms": 3000})
+        );
+    }
 
-        // Test MergeItems action
-        let merge_items = Action::MergeItems {
-            lane_id: "lane1".to_string(),
-            source_id: "item1".to_string(),
-            target_id: "item2".to_string(),
-            merged_body: "Merged text".to_string(),
+    #[test]
+    fn test_rejection_goes_to_sender() {
+        let dir = tempfile::tempdir().unwrap();
+        let app_state = test_app_state(RetroBoard::default(), dir.path().join("retroboard.json"));
+
+        let result = app_state.process_action(DEFAULT_BOARD, "tester", false, Action::EmptyTrash);
+        let Err(Rejection(reason)) = result else {
+            panic!("only a facilitator can empty the trash");
         };
-        let json = serde_json::to_string(&merge_items).unwrap();
-        let parsed: Action = serde_json::from_str(&json).unwrap();
-        match parsed {
-            Action::MergeItems {
-                lane_id,
-                source_id,
-                target_id,
-                merged_body,
-            } => {
-                assert_eq!(lane_id, "lane1");
-                assert_eq!(source_id, "item1");
-                assert_eq!(target_id, "item2");
-                assert_eq!(merged_body, "Merged text");
-            }
-            _ => panic!("Wrong action type"),
-        }
+        let message = ServerMessage::ActionRejected { reason };
+        let json: serde_json::Value =
+            serde_json::from_str(&serde_json::to_string(&message).unwrap()).unwrap();
+        assert_eq!(json["type"], "ActionRejected");
+        assert_eq!(json["reason"], "only a facilitator can empty the trash");
     }
 
     #[test]
-    fn test_invalid_action_json() {
-        let invalid_json = r#"{"type": "InvalidAction"}"#;
-        let result: Result<Action, _> = serde_json::from_str(invalid_json);
-        assert!(result.is_err());
+    fn test_participant_ids_are_not_keys() {
+        let alice = participant_id("a7f3c9e2-key");
+        assert_eq!(alice, participant_id("a7f3c9e2-key"));
+        assert_eq!(alice.len(), 32);
+        assert!(!alice.contains("a7f3c9e2"));
+        // Connecting with someone's published ID does not make you them
+        assert_ne!(participant_id(&alice), alice);
     }
 
     #[test]
-    fn test_app_state_process_action() {
-        use std::sync::RwLock;
-        use tokio::sync::broadcast;
+    fn test_flush_writes_board() {
+        let dir = tempfile::tempdir().unwrap();
+        let board_file = dir.path().join("retroboard.json");
+        let app_state = test_app_state(RetroBoard::new("Flushed"), board_file.clone());
 
-        let board = RetroBoard::default();
-        let (tx, _rx) = broadcast::channel(100);
-        let app_state = AppState {
-            board: RwLock::new(board),
-            tx,
+        app_state.flush();
+        assert_eq!(
+            RetroBoard::load_from_file(&board_file).unwrap().title,
+            "Flushed"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_created_boards_are_kept_apart() {
+        let dir = tempfile::tempdir().unwrap();
+        let app_state = test_app_state(RetroBoard::default(), dir.path().join("retroboard.json"));
+
+        let id = app_state
+            .create_board(RetroBoard::from_template("went-well").unwrap())
+            .await
+            .unwrap();
+        let action = Action::AddItem {
+            lane_id: "went-well".to_string(),
+            body: "Only on the new board".to_string(),
+        };
+        app_state
+            .process_action(&id, "tester", false, action)
+            .unwrap();
+
+        let default_board = app_state.board(DEFAULT_BOARD).unwrap();
+        assert_eq!(default_board.read().lanes["went-well"].items.len(), 2);
+        let stored = RetroBoard::load_from_file(app_state.board_path(&id)).unwrap();
+        assert_eq!(stored.lanes["went-well"].items.len(), 1);
+        assert_eq!(app_state.boards.ids().len(), 2);
+    }
+
+    #[test]
+    fn test_undo_is_broadcast_without_history() {
+        let dir = tempfile::tempdir().unwrap();
+        let board = RetroBoard::from_template("went-well").unwrap();
+        let app_state = test_app_state(board, dir.path().join("retroboard.json"));
+        let action = Action::AddItem {
+            lane_id: "went-well".to_string(),
+            body: "Undo me".to_string(),
+        };
+        app_state
+            .process_action(DEFAULT_BOARD, "tester", false, action)
+            .unwrap();
+        app_state
+            .process_action(
+                DEFAULT_BOARD,
+                "tester",
+                false,
+                Action::Undo { everyone: false },
+            )
+            .unwrap();
+
+        let board = app_state.board(DEFAULT_BOARD).unwrap();
+        assert!(board.read().lanes["went-well"].items.is_empty());
+        let stored = RetroBoard::load_from_file(app_state.board_path(DEFAULT_BOARD)).unwrap();
+        assert!(stored.lanes["went-well"].items.is_empty());
+
+        let snapshot: serde_json::Value =
+            serde_json::from_str(&app_state.snapshot(&board)).unwrap();
+        assert!(snapshot.get("lanes").is_some());
+        assert!(snapshot.get("history").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_comment_is_broadcast_as_update() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut board = RetroBoard::from_template("went-well").unwrap();
+        // Anonymous boards blank the authors, which the copy below would miss
+        board.settings.anonymous = false;
+        let item_id = board.add_item("went-well", "Deploys").unwrap();
+        let app_state = test_app_state(board, dir.path().join("retroboard.json"));
+        let board = app_state.board(DEFAULT_BOARD).unwrap();
+        let mut rx = board.tx.subscribe();
+
+        let action = Action::AddComment {
+            lane_id: "went-well".to_string(),
+            item_id: item_id.clone(),
+            body: "Which ones?".to_string(),
+            reply_to: None,
         };
+        app_state
+            .submit_action(DEFAULT_BOARD, "tester", false, action)
+            .await
+            .unwrap();
+
+        let message: serde_json::Value = serde_json::from_str(&rx.recv().await.unwrap()).unwrap();
+        assert_eq!(message["type"], "CommentsChanged");
+        assert_eq!(message["item_id"], item_id.as_str());
+        let comments = message["comments"].as_object().unwrap();
+        assert_eq!(comments.len(), 1);
+
+        // Another copy of the board catches up from the update alone
+        let mut copy = RetroBoard::from_template("went-well").unwrap();
+        copy.lanes["went-well"] = board.read().lanes["went-well"].clone();
+        copy.lanes["went-well"]
+            .items
+            .get_mut(&item_id)
+            .unwrap()
+            .comments
+            .clear();
+        let update: Update = serde_json::from_value(message).unwrap();
+        update.apply_to(&mut copy);
+        assert!(copy.lanes["went-well"] == board.read().lanes["went-well"]);
+    }
+
+    #[test]
+    fn test_app_state_process_action() {
+        let dir = tempfile::tempdir().unwrap();
+        let board = RetroBoard::default();
+        let app_state = test_app_state(board, dir.path().join("retroboard.json"));
 
         // Test AddLane action
         let action = Action::AddLane {
             title: "New Lane".to_string(),
         };
-        app_state.process_action(action);
+        app_state
+            .process_action(DEFAULT_BOARD, "tester", false, action)
+            .unwrap();
 
-        let board = app_state.board.read().unwrap();
+        let board = app_state.board(DEFAULT_BOARD).unwrap();
+        let board = board.read();
         assert!(board.lanes.contains_key("New Lane"));
 
         drop(board); // Release the read lock
@@ -422,9 +939,12 @@ mod tests {
             lane_id: "New Lane".to_string(),
             body: "Test Item".to_string(),
         };
-        app_state.process_action(action);
+        app_state
+            .process_action(DEFAULT_BOARD, "tester", false, action)
+            .unwrap();
 
-        let board = app_state.board.read().unwrap();
+        let board = app_state.board(DEFAULT_BOARD).unwrap();
+        let board = board.read();
         let lane = board.lanes.get("New Lane").unwrap();
         assert_eq!(lane.items.len(), 1);
         let item = lane.items.values().next().unwrap();
@@ -433,7 +953,8 @@ mod tests {
         drop(board); // Release the read lock
 
         // Test RemoveItem action
-        let board = app_state.board.read().unwrap();
+        let board = app_state.board(DEFAULT_BOARD).unwrap();
+        let board = board.read();
         let lane = board.lanes.get("New Lane").unwrap();
         let item_id = lane.items.keys().next().unwrap().clone();
         drop(board); // Release the read lock
@@ -442,9 +963,12 @@ mod tests {
             lane_id: "New Lane".to_string(),
             id: item_id,
         };
-        app_state.process_action(action);
+        app_state
+            .process_action(DEFAULT_BOARD, "tester", false, action)
+            .unwrap();
 
-        let board = app_state.board.read().unwrap();
+        let board = app_state.board(DEFAULT_BOARD).unwrap();
+        let board = board.read();
         let lane = board.lanes.get("New Lane").unwrap();
         assert_eq!(lane.items.len(), 0);
 
@@ -455,9 +979,12 @@ mod tests {
             lane_id: "went-well".to_string(),
             id: "1".to_string(),
         };
-        app_state.process_action(action);
+        app_state
+            .process_action(DEFAULT_BOARD, "tester", false, action)
+            .unwrap();
 
-        let board = app_state.board.read().unwrap();
+        let board = app_state.board(DEFAULT_BOARD).unwrap();
+        let board = board.read();
         let lane = board.lanes.get("went-well").unwrap();
         let item = lane.items.get("1").unwrap();
         assert_eq!(item.vote_count, 1);
@@ -470,9 +997,12 @@ mod tests {
             to_lane_id: "to-improve".to_string(),
             item_id: "1".to_string(),
         };
-        app_state.process_action(action);
+        app_state
+            .process_action(DEFAULT_BOARD, "tester", false, action)
+            .unwrap();
 
-        let board = app_state.board.read().unwrap();
+        let board = app_state.board(DEFAULT_BOARD).unwrap();
+        let board = board.read();
         let went_well_lane = board.lanes.get("went-well").unwrap();
         let to_improve_lane = board.lanes.get("to-improve").unwrap();
         assert!(!went_well_lane.items.contains_key("1"));
@@ -486,9 +1016,12 @@ mod tests {
             item_id: "1".to_string(),
             new_position: 0,
         };
-        app_state.process_action(action);
+        app_state
+            .process_action(DEFAULT_BOARD, "tester", false, action)
+            .unwrap();
 
-        let board = app_state.board.read().unwrap();
+        let board = app_state.board(DEFAULT_BOARD).unwrap();
+        let board = board.read();
         let lane = board.lanes.get("to-improve").unwrap();
         let item = lane.items.get("1").unwrap();
         assert_eq!(item.sort_order, 0);
@@ -501,9 +1034,12 @@ mod tests {
             id: "3".to_string(),
             body: "Edited body text".to_string(),
         };
-        app_state.process_action(action);
+        app_state
+            .process_action(DEFAULT_BOARD, "tester", false, action)
+            .unwrap();
 
-        let board = app_state.board.read().unwrap();
+        let board = app_state.board(DEFAULT_BOARD).unwrap();
+        let board = board.read();
         let lane = board.lanes.get("to-improve").unwrap();
         let item = lane.items.get("3").unwrap();
         assert_eq!(item.body, "Edited body text");
@@ -517,9 +1053,12 @@ mod tests {
             target_id: "3".to_string(),
             merged_body: "Combined text".to_string(),
         };
-        app_state.process_action(action);
+        app_state
+            .process_action(DEFAULT_BOARD, "tester", false, action)
+            .unwrap();
 
-        let board = app_state.board.read().unwrap();
+        let board = app_state.board(DEFAULT_BOARD).unwrap();
+        let board = board.read();
         let lane = board.lanes.get("to-improve").unwrap();
         assert!(!lane.items.contains_key("1")); // source removed
         let target = lane.items.get("3").unwrap();
@@ -527,31 +1066,3 @@ mod tests {
         assert_eq!(target.vote_count, 1); // inherited from source (upvoted earlier)
     }
 }
-
-#[tokio::main]
-async fn main() {
-    tracing_subscriber::registry()
-        .with(
-            tracing_subscriber::EnvFilter::try_from_default_env()
-                .unwrap_or_else(|_| format!("{}=trace", env!("CARGO_CRATE_NAME")).into()),
-        )
-        .with(tracing_subscriber::fmt::layer())
-        .init();
-
-    // Setup app state
-    let board = RetroBoard::load_from_file(BOARD_FILE);
-    let (tx, _rx) = broadcast::channel(100);
-    let app_state = Arc::new(AppState {
-        board: RwLock::new(board),
-        tx,
-    });
-
-    let app = Router::new()
-        .route("/ws", get(websocket_handler))
-        .with_state(app_state);
-
-    let listener = tokio::net::TcpListener::bind("0.0.0.0:3000").await.unwrap();
-
-    tracing::debug!("Listening on: {}", listener.local_addr().unwrap());
-    axum::serve(listener, app).await.unwrap();
-}
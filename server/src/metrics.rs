@@ -0,0 +1,216 @@
+//! Prometheus metrics, served as text on `/metrics`.
+
+use crate::AppState;
+use axum::{
+    extract::State, http::header::CONTENT_TYPE, response::IntoResponse, routing::get, Router,
+};
+use prometheus::{
+    exponential_buckets, Histogram, HistogramOpts, HistogramVec, IntCounter, IntCounterVec,
+    IntGaugeVec, Opts, Registry, TextEncoder, TEXT_FORMAT,
+};
+use std::sync::Arc;
+
+pub struct Metrics {
+    registry: Registry,
+    /// Websocket clients, by board
+    pub connected_sockets: IntGaugeVec,
+    /// Actions received, by action type
+    pub actions: IntCounterVec,
+    /// Actions that failed, by reason: `invalid`, `rejected` or `storage`
+    pub action_errors: IntCounterVec,
+    /// Times a client fell so far behind that it missed broadcasts
+    pub broadcast_lagged: IntCounter,
+    /// Time taken to persist the board, by backend
+    pub persist_seconds: HistogramVec,
+    pub persist_failures: IntCounterVec,
+    /// Size of the board snapshots broadcast to clients
+    pub snapshot_bytes: Histogram,
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        let registry = Registry::new_custom(Some("tinyretro".to_string()), None).unwrap();
+
+        let connected_sockets = IntGaugeVec::new(
+            Opts::new("connected_sockets", "Websocket clients currently connected"),
+            &["board"],
+        )
+        .unwrap();
+        let actions = IntCounterVec::new(
+            Opts::new("actions_total", "Actions received from clients"),
+            &["type"],
+        )
+        .unwrap();
+        let action_errors = IntCounterVec::new(
+            Opts::new("action_errors_total", "Actions that could not be applied"),
+            &["reason"],
+        )
+        .unwrap();
+        let broadcast_lagged = IntCounter::new(
+            "broadcast_lagged_total",
+            "Clients disconnected for falling behind the broadcast channel",
+        )
+        .unwrap();
+        let persist_seconds = HistogramVec::new(
+            HistogramOpts::new(
+                "persist_duration_seconds",
+                "Time taken to persist the board",
+            )
+            .buckets(exponential_buckets(0.0005, 2.5, 10).unwrap()),
+            &["backend"],
+        )
+        .unwrap();
+        let persist_failures = IntCounterVec::new(
+            Opts::new(
+                "persist_failures_total",
+                "Failed attempts to persist the board",
+            ),
+            &["backend"],
+        )
+        .unwrap();
+        let snapshot_bytes = Histogram::with_opts(
+            HistogramOpts::new("snapshot_bytes", "Size of serialized board snapshots")
+                .buckets(exponential_buckets(256.0, 4.0, 8).unwrap()),
+        )
+        .unwrap();
+
+        registry
+            .register(Box::new(connected_sockets.clone()))
+            .unwrap();
+        registry.register(Box::new(actions.clone())).unwrap();
+        registry.register(Box::new(action_errors.clone())).unwrap();
+        registry
+            .register(Box::new(broadcast_lagged.clone()))
+            .unwrap();
+        registry
+            .register(Box::new(persist_seconds.clone()))
+            .unwrap();
+        registry
+            .register(Box::new(persist_failures.clone()))
+            .unwrap();
+        registry.register(Box::new(snapshot_bytes.clone())).unwrap();
+
+        Self {
+            registry,
+            connected_sockets,
+            actions,
+            action_errors,
+            broadcast_lagged,
+            persist_seconds,
+            persist_failures,
+            snapshot_bytes,
+        }
+    }
+
+    pub fn render(&self) -> String {
+        TextEncoder::new()
+            .encode_to_string(&self.registry.gather())
+            .unwrap()
+    }
+}
+
+pub fn routes() -> Router<Arc<AppState>> {
+    Router::new().route("/metrics", get(metrics))
+}
+
+async fn metrics(State(state): State<Arc<AppState>>) -> impl IntoResponse {
+    ([(CONTENT_TYPE, TEXT_FORMAT)], state.metrics.render())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::action::Action;
+    use crate::boards::DEFAULT_BOARD;
+    use crate::retroboard::RetroBoard;
+    use crate::{router, test_app_state};
+    use axum::body::{to_bytes, Body};
+    use axum::http::{Request, StatusCode};
+    use tower::ServiceExt;
+
+    #[tokio::test]
+    async fn test_metrics_endpoint() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = test_app_state(RetroBoard::default(), dir.path().join("board.json"));
+        state
+            .metrics
+            .connected_sockets
+            .with_label_values(&["default"])
+            .inc();
+
+        let response = router(Arc::new(state))
+            .oneshot(Request::get("/metrics").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[CONTENT_TYPE], TEXT_FORMAT);
+
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body = String::from_utf8(body.to_vec()).unwrap();
+        assert!(body.contains(r#"tinyretro_connected_sockets{board="default"} 1"#));
+    }
+
+    #[test]
+    fn test_process_action_is_instrumented() {
+        let dir = tempfile::tempdir().unwrap();
+        let state = test_app_state(RetroBoard::default(), dir.path().join("board.json"));
+
+        state
+            .process_action(
+                DEFAULT_BOARD,
+                "alice",
+                false,
+                Action::AddItem {
+                    lane_id: "went-well".to_string(),
+                    body: "Counted".to_string(),
+                },
+            )
+            .unwrap();
+        assert_eq!(
+            state.metrics.actions.with_label_values(&["AddItem"]).get(),
+            1
+        );
+        assert_eq!(
+            state
+                .metrics
+                .persist_seconds
+                .with_label_values(&["json"])
+                .get_sample_count(),
+            1
+        );
+
+        let too_long = "x".repeat(state.limits.max_body_length + 1);
+        let rejected = state.process_action(
+            DEFAULT_BOARD,
+            "alice",
+            false,
+            Action::AddItem {
+                lane_id: "went-well".to_string(),
+                body: too_long,
+            },
+        );
+        assert!(rejected.is_err());
+        assert_eq!(
+            state
+                .metrics
+                .action_errors
+                .with_label_values(&["rejected"])
+                .get(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_persist_failures_are_counted() {
+        let state = test_app_state(RetroBoard::default(), "/nonexistent/dir/board.json".into());
+        state.flush();
+        assert_eq!(
+            state
+                .metrics
+                .persist_failures
+                .with_label_values(&["json"])
+                .get(),
+            1
+        );
+    }
+}
@@ -0,0 +1,347 @@
+//! Optional Redis backend that lets several server instances share boards.
+//!
+//! Actions are applied inside a `WATCH`/`MULTI` transaction on the board key,
+//! and the resulting snapshot is published in that same transaction. Actions
+//! with an [`Update`] publish that instead, and instances make the same change
+//! to their copy of the board. Redis therefore decides the order of
+//! concurrent actions, and every instance sees them in that order on its
+//! subscription. Each board publishes on
+//! its own channel, and the IDs of all boards are kept in a set.
+
+use crate::action::{Action, ActionContext, Rejection, Update};
+use crate::boards::DEFAULT_BOARD;
+use crate::retroboard::RetroBoard;
+use crate::schema;
+use futures::stream::{Stream, StreamExt};
+use redis::{AsyncCommands, ErrorKind, RedisError, RedisResult};
+use tokio::sync::Mutex;
+
+const DEFAULT_PREFIX: &str = "tinyretro";
+
+#[derive(Debug)]
+pub enum ApplyError {
+    Rejected(Rejection),
+    Redis(RedisError),
+}
+
+impl From<RedisError> for ApplyError {
+    fn from(e: RedisError) -> Self {
+        ApplyError::Redis(e)
+    }
+}
+
+pub struct RedisFanout {
+    client: redis::Client,
+    /// Connection used for transactions. `WATCH` state belongs to the
+    /// connection, so transactions must not interleave on it.
+    conn: Mutex<redis::aio::MultiplexedConnection>,
+    prefix: String,
+}
+
+impl RedisFanout {
+    pub async fn connect(url: &str) -> RedisResult<Self> {
+        Self::connect_with_prefix(url, DEFAULT_PREFIX).await
+    }
+
+    /// Connect using keys namespaced under `prefix`, so several deployments
+    /// (or test runs) can share one Redis.
+    pub async fn connect_with_prefix(url: &str, prefix: &str) -> RedisResult<Self> {
+        let client = redis::Client::open(url)?;
+        let conn = client.get_multiplexed_async_connection().await?;
+        Ok(Self {
+            client,
+            conn: Mutex::new(conn),
+            prefix: prefix.to_string(),
+        })
+    }
+
+    fn board_key(&self, id: &str) -> String {
+        // The default board keeps the key it had before boards had IDs
+        if id == DEFAULT_BOARD {
+            format!("{}:board", self.prefix)
+        } else {
+            format!("{}:board:{}", self.prefix, id)
+        }
+    }
+
+    fn board_ids_key(&self) -> String {
+        format!("{}:boards", self.prefix)
+    }
+
+    fn channel(&self, id: &str) -> String {
+        format!("{}:updates:{}", self.prefix, id)
+    }
+
+    /// Store `board` under `id` unless another instance already did.
+    pub async fn seed(&self, id: &str, board: &RetroBoard) -> RedisResult<()> {
+        let json = schema::to_json(board);
+        let mut conn = self.conn.lock().await;
+        let _: bool = conn.set_nx(self.board_key(id), json).await?;
+        let _: () = conn.sadd(self.board_ids_key(), id).await?;
+        Ok(())
+    }
+
+    /// Store a new board and announce it to every instance.
+    pub async fn create(&self, id: &str, board: &RetroBoard) -> RedisResult<()> {
+        let json = schema::to_json(board);
+        let mut conn = self.conn.lock().await;
+        redis::pipe()
+            .atomic()
+            .set(self.board_key(id), &json)
+            .ignore()
+            .sadd(self.board_ids_key(), id)
+            .ignore()
+            .publish(self.channel(id), &json)
+            .ignore()
+            .exec_async(&mut *conn)
+            .await
+    }
+
+    /// IDs of every shared board.
+    pub async fn board_ids(&self) -> RedisResult<Vec<String>> {
+        let mut conn = self.conn.lock().await;
+        conn.smembers(self.board_ids_key()).await
+    }
+
+    /// Fetch a shared board, or `None` if it has not been stored.
+    pub async fn load(&self, id: &str) -> RedisResult<Option<RetroBoard>> {
+        let mut conn = self.conn.lock().await;
+        let json: Option<String> = conn.get(self.board_key(id)).await?;
+        json.map(|json| parse_board(&json)).transpose()
+    }
+
+    pub async fn ping(&self) -> RedisResult<()> {
+        let mut conn = self.conn.lock().await;
+        redis::cmd("PING").exec_async(&mut *conn).await
+    }
+
+    /// Apply an action to a shared board and publish the new snapshot, or
+    /// the action's update, to every subscribed instance. Returns the stored
+    /// snapshot.
+    pub async fn apply(
+        &self,
+        id: &str,
+        action: Action,
+        ctx: &ActionContext<'_>,
+    ) -> Result<String, ApplyError> {
+        let board_key = self.board_key(id);
+        let mut conn = self.conn.lock().await;
+        loop {
+            redis::cmd("WATCH")
+                .arg(&board_key)
+                .exec_async(&mut *conn)
+                .await?;
+
+            let json: Option<String> = conn.get(&board_key).await?;
+            let Some(json) = json else {
+                redis::cmd("UNWATCH").exec_async(&mut *conn).await?;
+                return Err(ApplyError::Rejected(Rejection(format!(
+                    "board '{}' does not exist",
+                    id
+                ))));
+            };
+            let mut board = parse_board(&json)?;
+            let update = match action.clone().apply_with_update(&mut board, ctx) {
+                Ok(update) => update,
+                Err(rejection) => {
+                    redis::cmd("UNWATCH").exec_async(&mut *conn).await?;
+                    return Err(ApplyError::Rejected(rejection));
+                }
+            };
+            let snapshot = schema::to_json(&board);
+            let published = update
+                .as_ref()
+                .map_or_else(|| snapshot.clone(), Update::to_json);
+
+            // A nil reply means another instance changed the board after our
+            // WATCH, so the transaction was discarded and we try again.
+            let committed: Option<()> = redis::pipe()
+                .atomic()
+                .set(&board_key, &snapshot)
+                .ignore()
+                .publish(self.channel(id), &published)
+                .ignore()
+                .query_async(&mut *conn)
+                .await?;
+            if committed.is_some() {
+                return Ok(snapshot);
+            }
+            tracing::debug!("Shared board changed during transaction, retrying");
+        }
+    }
+
+    /// Subscribe to the snapshots and updates published for every board, as
+    /// pairs of board ID and message. The stream ends when the connection to Redis is
+    /// lost.
+    pub async fn subscribe(&self) -> RedisResult<impl Stream<Item = (String, String)>> {
+        let channel_prefix = self.channel("");
+        let mut pubsub = self.client.get_async_pubsub().await?;
+        pubsub.psubscribe(format!("{}*", channel_prefix)).await?;
+        Ok(pubsub.into_on_message().filter_map(move |msg| {
+            let id = msg
+                .get_channel_name()
+                .strip_prefix(&channel_prefix)
+                .map(str::to_string);
+            let snapshot = msg.get_payload::<String>().ok();
+            async move { id.zip(snapshot) }
+        }))
+    }
+}
+
+pub fn parse_board(json: &str) -> RedisResult<RetroBoard> {
+    schema::from_json(json).map_err(|e| {
+        (
+            ErrorKind::Parse,
+            "Invalid board stored in Redis",
+            e.to_string(),
+        )
+            .into()
+    })
+}
+
+/// These tests need a running Redis. Start one locally and run
+/// `REDIS_URL=redis://127.0.0.1/ cargo test -- --ignored`.
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Limits;
+    use std::time::Duration;
+    use uuid::Uuid;
+
+    async fn connect_pair() -> (RedisFanout, RedisFanout) {
+        let url = std::env::var("REDIS_URL").unwrap_or_else(|_| "redis://127.0.0.1/".to_string());
+        let prefix = format!("tinyretro-test-{}", Uuid::new_v4());
+        let a = RedisFanout::connect_with_prefix(&url, &prefix)
+            .await
+            .unwrap();
+        let b = RedisFanout::connect_with_prefix(&url, &prefix)
+            .await
+            .unwrap();
+        (a, b)
+    }
+
+    #[tokio::test]
+    #[ignore = "requires a local Redis"]
+    async fn test_seed_does_not_overwrite() {
+        let (a, b) = connect_pair().await;
+
+        let mut first = RetroBoard::default();
+        first.title = "First".to_string();
+        a.seed(DEFAULT_BOARD, &first).await.unwrap();
+
+        let mut second = RetroBoard::default();
+        second.title = "Second".to_string();
+        b.seed(DEFAULT_BOARD, &second).await.unwrap();
+
+        let board = b.load(DEFAULT_BOARD).await.unwrap().unwrap();
+        assert_eq!(board.title, "First");
+        assert_eq!(b.board_ids().await.unwrap(), [DEFAULT_BOARD]);
+    }
+
+    #[tokio::test]
+    #[ignore = "requires a local Redis"]
+    async fn test_update_reaches_other_instance() {
+        let (a, b) = connect_pair().await;
+        a.seed(DEFAULT_BOARD, &RetroBoard::default()).await.unwrap();
+
+        let mut updates = Box::pin(b.subscribe().await.unwrap());
+        let limits = Limits::default();
+        let ctx = ActionContext {
+            participant: "alice",
+            facilitator: false,
+            limits: &limits,
+        };
+        a.apply(
+            DEFAULT_BOARD,
+            Action::AddLane {
+                title: "Shared".to_string(),
+            },
+            &ctx,
+        )
+        .await
+        .unwrap();
+
+        let (id, snapshot) = tokio::time::timeout(Duration::from_secs(5), updates.next())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(id, DEFAULT_BOARD);
+        let board = parse_board(&snapshot).unwrap();
+        assert!(board.lanes.contains_key("Shared"));
+    }
+
+    #[tokio::test]
+    #[ignore = "requires a local Redis"]
+    async fn test_created_board_reaches_other_instance() {
+        let (a, b) = connect_pair().await;
+        let mut updates = Box::pin(b.subscribe().await.unwrap());
+
+        a.create("imported", &RetroBoard::new("Imported"))
+            .await
+            .unwrap();
+
+        let (id, snapshot) = tokio::time::timeout(Duration::from_secs(5), updates.next())
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(id, "imported");
+        assert_eq!(parse_board(&snapshot).unwrap().title, "Imported");
+        assert_eq!(b.board_ids().await.unwrap(), ["imported"]);
+        assert_eq!(b.load("imported").await.unwrap().unwrap().title, "Imported");
+    }
+
+    #[tokio::test]
+    #[ignore = "requires a local Redis"]
+    async fn test_action_on_missing_board_is_rejected() {
+        let (a, _) = connect_pair().await;
+        let limits = Limits::default();
+        let ctx = ActionContext {
+            participant: "alice",
+            facilitator: false,
+            limits: &limits,
+        };
+        let result = a
+            .apply(
+                "missing",
+                Action::AddLane {
+                    title: "Lost".to_string(),
+                },
+                &ctx,
+            )
+            .await;
+        assert!(matches!(result, Err(ApplyError::Rejected(_))));
+    }
+
+    #[tokio::test]
+    #[ignore = "requires a local Redis"]
+    async fn test_concurrent_actions_are_not_lost() {
+        let (a, b) = connect_pair().await;
+        a.seed(DEFAULT_BOARD, &RetroBoard::default()).await.unwrap();
+
+        let limits = Limits::default();
+        let ctx = ActionContext {
+            participant: "alice",
+            facilitator: false,
+            limits: &limits,
+        };
+        let add = |n: usize| Action::AddItem {
+            lane_id: "went-well".to_string(),
+            body: format!("Item {}", n),
+        };
+        let from_a = async {
+            for n in 0..20 {
+                a.apply(DEFAULT_BOARD, add(n), &ctx).await.unwrap();
+            }
+        };
+        let from_b = async {
+            for n in 20..40 {
+                b.apply(DEFAULT_BOARD, add(n), &ctx).await.unwrap();
+            }
+        };
+        tokio::join!(from_a, from_b);
+
+        let board = a.load(DEFAULT_BOARD).await.unwrap().unwrap();
+        // The default board starts with two items in this lane
+        assert_eq!(board.lanes.get("went-well").unwrap().items.len(), 42);
+    }
+}
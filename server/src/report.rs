@@ -0,0 +1,294 @@
+//! Self-contained HTML report of a board, for sharing a finished retro.
+//!
+//! The report is a single file with inline CSS and no scripts or external
+//! resources, so it can be emailed and opened without the server. Lane
+//! themes use the colors and icons of the client's `v2-theme.ts`.
+
+use crate::export::{action_summary, comments, constituents, sorted_items, votes, ItemOrder};
+use crate::retroboard::{ActionStatus, RetroBoard};
+use chrono::Utc;
+use std::fmt::Write;
+
+/// Themes the report has styles for. Others fall back to the first, as in
+/// the client.
+const THEME_ICONS: [(&str, &str); 4] = [
+    ("went-well", "🎉"),
+    ("to-improve", "🔧"),
+    ("action-items", "🚀"),
+    ("action-review", "🔁"),
+];
+
+const STYLE: &str = "
+body { margin: 0; padding: 2rem; font-family: system-ui, -apple-system, 'Segoe UI', Roboto, sans-serif; color: #1f2937; background: #f9fafb; }
+header { margin-bottom: 2rem; }
+h1 { margin: 0 0 0.25rem; font-size: 1.75rem; }
+.meta { margin: 0 0 1rem; color: #6b7280; font-size: 0.875rem; }
+.summary { display: flex; flex-wrap: wrap; gap: 0.75rem; margin: 0; padding: 0; list-style: none; }
+.summary li { padding: 0.5rem 0.875rem; border: 1px solid #e5e7eb; border-radius: 0.5rem; background: #fff; }
+.summary strong { display: block; font-size: 1.25rem; }
+.top { margin: 1rem 0 0; }
+.lanes { display: grid; grid-template-columns: repeat(auto-fit, minmax(16rem, 1fr)); gap: 1rem; align-items: start; }
+.lane { border: 1px solid; border-top-width: 4px; border-radius: 0.75rem; overflow: hidden; }
+.lane h2 { margin: 0; padding: 0.75rem 1rem; font-size: 1rem; display: flex; gap: 0.5rem; align-items: center; }
+.lane h2 .count { margin-left: auto; font-size: 0.75rem; font-weight: normal; opacity: 0.8; }
+.lane ul { margin: 0; padding: 0.75rem; list-style: none; display: grid; gap: 0.5rem; }
+.item { display: flex; gap: 0.75rem; justify-content: space-between; padding: 0.625rem 0.75rem; border: 1px solid; border-radius: 0.5rem; background: #fff; white-space: pre-wrap; overflow-wrap: anywhere; }
+.lane ul.merged { margin: 0.375rem 0 0; padding: 0 0 0 1rem; display: block; list-style: disc; font-size: 0.8125rem; color: #6b7280; }
+.action-details { display: block; margin-top: 0.25rem; font-size: 0.75rem; color: #1e40af; }
+.item.done > span:first-child { text-decoration: line-through; color: #6b7280; }
+.comment-count { display: block; margin-top: 0.25rem; font-size: 0.75rem; color: #6b7280; }
+.votes { flex: none; align-self: start; padding: 0.125rem 0.5rem; border-radius: 999px; font-size: 0.75rem; font-weight: 600; }
+.empty { padding: 0.75rem 1rem; color: #9ca3af; font-style: italic; }
+.theme-went-well { border-color: rgb(74 222 128 / 0.3); background: rgb(34 197 94 / 0.03); border-top-color: #22c55e; }
+.theme-went-well h2 { background: rgb(34 197 94 / 0.1); color: #15803d; }
+.theme-went-well .item { border-color: rgb(74 222 128 / 0.4); background: rgb(34 197 94 / 0.05); }
+.theme-went-well .votes { background: #bbf7d0; color: #166534; }
+.theme-to-improve { border-color: rgb(251 191 36 / 0.3); background: rgb(245 158 11 / 0.03); border-top-color: #f59e0b; }
+.theme-to-improve h2 { background: rgb(245 158 11 / 0.1); color: #b45309; }
+.theme-to-improve .item { border-color: rgb(251 191 36 / 0.4); background: rgb(245 158 11 / 0.05); }
+.theme-to-improve .votes { background: #fde68a; color: #92400e; }
+.theme-action-items { border-color: rgb(96 165 250 / 0.3); background: rgb(59 130 246 / 0.03); border-top-color: #3b82f6; }
+.theme-action-items h2 { background: rgb(59 130 246 / 0.1); color: #1d4ed8; }
+.theme-action-items .item { border-color: rgb(96 165 250 / 0.4); background: rgb(59 130 246 / 0.05); }
+.theme-action-items .votes { background: #bfdbfe; color: #1e40af; }
+.theme-action-review { border-color: rgb(167 139 250 / 0.3); background: rgb(139 92 246 / 0.03); border-top-color: #8b5cf6; }
+.theme-action-review h2 { background: rgb(139 92 246 / 0.1); color: #6d28d9; }
+.theme-action-review .item { border-color: rgb(167 139 250 / 0.4); background: rgb(139 92 246 / 0.05); }
+.theme-action-review .votes { background: #ddd6fe; color: #5b21b6; }
+footer { margin-top: 2rem; color: #9ca3af; font-size: 0.75rem; }
+";
+
+/// Render a board as a standalone HTML page.
+pub fn to_html(board: &RetroBoard, order: ItemOrder) -> String {
+    let items: Vec<_> = board
+        .lanes
+        .values()
+        .flat_map(|lane| lane.items.values())
+        .collect();
+    let total_votes: u64 = items.iter().map(|item| item.vote_count).sum();
+    let action_items: usize = board
+        .lanes
+        .values()
+        .filter(|lane| lane.is_action_items())
+        .map(|lane| lane.items.len())
+        .sum();
+    let top = items
+        .iter()
+        .filter(|item| item.vote_count > 0)
+        .max_by_key(|item| item.vote_count);
+
+    let mut out = String::new();
+    writeln!(
+        out,
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n\
+         <title>{}</title>\n<style>{}</style>\n</head>\n<body>",
+        escape(&board.title),
+        STYLE
+    )
+    .unwrap();
+
+    writeln!(out, "<header>\n<h1>{}</h1>", escape(&board.title)).unwrap();
+    if let Some(created) = board.created_at {
+        writeln!(
+            out,
+            "<p class=\"meta\">Retro of {}</p>",
+            created.format("%B %-d, %Y")
+        )
+        .unwrap();
+    }
+    writeln!(out, "<ul class=\"summary\">").unwrap();
+    for (count, label) in [
+        (board.lanes.len(), "lanes"),
+        (items.len(), "items"),
+        (total_votes as usize, "votes"),
+        (action_items, "action items"),
+    ] {
+        writeln!(out, "<li><strong>{}</strong>{}</li>", count, label).unwrap();
+    }
+    writeln!(out, "</ul>").unwrap();
+    if let Some(top) = top {
+        writeln!(
+            out,
+            "<p class=\"top\">Most voted: {} ({})</p>",
+            escape(&top.body),
+            votes(top.vote_count)
+        )
+        .unwrap();
+    }
+    writeln!(out, "</header>\n<main class=\"lanes\">").unwrap();
+
+    for lane in board.lanes.values() {
+        let (theme, icon) = THEME_ICONS
+            .iter()
+            .find(|(theme, _)| *theme == lane.theme)
+            .unwrap_or(&THEME_ICONS[0]);
+        writeln!(
+            out,
+            "<section class=\"lane theme-{}\">\n<h2><span>{}</span> {} <span class=\"count\">{}</span></h2>",
+            theme,
+            icon,
+            escape(&lane.title),
+            lane.items.len()
+        )
+        .unwrap();
+
+        let items = sorted_items(lane, order);
+        if items.is_empty() {
+            writeln!(out, "<p class=\"empty\">No items</p>").unwrap();
+        } else {
+            writeln!(out, "<ul>").unwrap();
+            for (_, item) in items {
+                // No line breaks inside, items keep their whitespace
+                let merged: String = constituents(item)
+                    .into_iter()
+                    .map(|merged| {
+                        let count = votes(merged.item.vote_count);
+                        format!("<li>{} ({})</li>", escape(merged.item.body.trim()), count)
+                    })
+                    .collect();
+                let merged = if merged.is_empty() {
+                    merged
+                } else {
+                    format!("<ul class=\"merged\">{}</ul>", merged)
+                };
+                let details = match item.action.as_ref().and_then(action_summary) {
+                    Some(summary) => {
+                        format!("<span class=\"action-details\">{}</span>", escape(&summary))
+                    }
+                    None => String::new(),
+                };
+                let done = item
+                    .action
+                    .as_ref()
+                    .is_some_and(|details| details.status == ActionStatus::Done);
+                let class = if done { "item done" } else { "item" };
+                let comment_count = if item.comments.is_empty() {
+                    String::new()
+                } else {
+                    let count = comments(item.comments.len());
+                    format!("<span class=\"comment-count\">{}</span>", count)
+                };
+                writeln!(
+                    out,
+                    "<li class=\"{}\"><span>{}{}{}{}</span><span class=\"votes\" title=\"{}\">{}</span></li>",
+                    class,
+                    escape(item.body.trim()),
+                    details,
+                    merged,
+                    comment_count,
+                    votes(item.vote_count),
+                    item.vote_count
+                )
+                .unwrap();
+            }
+            writeln!(out, "</ul>").unwrap();
+        }
+        writeln!(out, "</section>").unwrap();
+    }
+
+    writeln!(
+        out,
+        "</main>\n<footer>Exported from tinyretro on {}</footer>\n</body>\n</html>",
+        Utc::now().format("%Y-%m-%d %H:%M UTC")
+    )
+    .unwrap();
+    out
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn board() -> RetroBoard {
+        let mut board = RetroBoard::from_template("went-well").unwrap();
+        board.title = "Sprint <42> Retro".to_string();
+        board.created_at = Some("2026-03-06T09:30:00Z".parse().unwrap());
+        board.add_item("went-well", "Deploys were smooth");
+        board.add_item("to-improve", "Flaky <script>alert(1)</script> tests");
+        board.add_item("action-items", "Fix the flaky tests");
+        let id = board.lanes["to-improve"]
+            .items
+            .keys()
+            .next()
+            .unwrap()
+            .clone();
+        board.upvote_item("to-improve", &id);
+        board.upvote_item("to-improve", &id);
+        board
+    }
+
+    #[test]
+    fn test_report_is_self_contained() {
+        let html = to_html(&board(), ItemOrder::Board);
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("<style>"));
+        for external in ["<script", "<link", "src=", "href=", "http://", "https://"] {
+            assert!(!html.contains(external), "report contains {}", external);
+        }
+    }
+
+    #[test]
+    fn test_summary_header() {
+        let html = to_html(&board(), ItemOrder::Board);
+        assert!(html.contains("<h1>Sprint &lt;42&gt; Retro</h1>"));
+        assert!(html.contains("Retro of March 6, 2026"));
+        assert!(html.contains("<li><strong>3</strong>items</li>"));
+        assert!(html.contains("<li><strong>2</strong>votes</li>"));
+        assert!(html.contains("<li><strong>1</strong>action items</li>"));
+        assert!(html.contains("Most voted: Flaky &lt;script&gt;"));
+    }
+
+    #[test]
+    fn test_lanes_use_client_themes() {
+        let mut board = board();
+        board.add_lane("Custom");
+        board.lanes["Custom"].theme = "unknown".to_string();
+
+        let html = to_html(&board, ItemOrder::Board);
+        assert!(html.contains("class=\"lane theme-went-well\">\n<h2><span>🎉</span> Went Well"));
+        assert!(html.contains("class=\"lane theme-to-improve\">\n<h2><span>🔧</span> To Improve"));
+        assert!(
+            html.contains("class=\"lane theme-action-items\">\n<h2><span>🚀</span> Action Items")
+        );
+        // Unknown themes fall back to went-well, as in the client
+        assert!(html.contains("class=\"lane theme-went-well\">\n<h2><span>🎉</span> Custom"));
+        assert!(html.contains("<p class=\"empty\">No items</p>"));
+    }
+
+    #[test]
+    fn test_merged_items_are_listed() {
+        let mut board = board();
+        let a = board.add_item("went-well", "Deploys").unwrap();
+        let b = board.add_item("went-well", "Releases <fast>").unwrap();
+        board.merge_items("went-well", &b, &a, "Shipping");
+
+        let html = to_html(&board, ItemOrder::Board);
+        assert!(html.contains(
+            "<span>Shipping<ul class=\"merged\"><li>Releases &lt;fast&gt; (0 votes)</li></ul></span>"
+        ));
+    }
+
+    #[test]
+    fn test_escape() {
+        assert_eq!(
+            escape(r#"<a href="x">Tom & 'Jerry'</a>"#),
+            "&lt;a href=&quot;x&quot;&gt;Tom &amp; &#39;Jerry&#39;&lt;/a&gt;"
+        );
+    }
+}
@@ -1,30 +1,262 @@
+use crate::history::History;
+use crate::schema;
+use chrono::{DateTime, NaiveDate, Utc};
+use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fs::File;
+use std::path::Path;
 use uuid::Uuid;
 
-#[derive(Serialize, Deserialize)]
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
 pub struct RetroItem {
     pub body: String,
     pub vote_count: u64,
     pub sort_order: u64,
+    /// Items merged into this one, oldest first
+    #[serde(default)]
+    pub merged: Vec<Merged>,
+    /// ID of the group the item is in, within its lane
+    #[serde(default)]
+    pub group: Option<String>,
+    /// Discussion of the item, in the order it was written
+    #[serde(default)]
+    pub comments: IndexMap<String, Comment>,
+    /// Participants who reacted with each emoji. Unlike votes these do not
+    /// rank the item.
+    #[serde(default)]
+    pub reactions: IndexMap<String, Vec<String>>,
+    /// Participants who reacted with each emoji while the board was
+    /// anonymous, who stay hidden even once the board shows participants
+    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+    pub anonymous_reactions: IndexMap<String, Vec<String>>,
+    /// Set for the items of action items lanes, and only for those
+    #[serde(default)]
+    pub action: Option<ActionDetails>,
+    /// Unknown for items stored before this was recorded
+    #[serde(default)]
+    pub created_at: Option<DateTime<Utc>>,
+    /// When the body last changed
+    #[serde(default)]
+    pub updated_at: Option<DateTime<Utc>>,
+    /// Bodies the item had before it was edited, oldest first. Only the
+    /// latest `MAX_REVISIONS` are kept.
+    #[serde(default)]
+    pub revisions: Vec<Revision>,
+    /// Participant who added the item. Unknown for imported items and those
+    /// stored before this was recorded.
+    #[serde(default)]
+    pub author: Option<String>,
+    /// Whether the item was added while the board was anonymous, which keeps
+    /// its author hidden even once the board shows authors
+    #[serde(default)]
+    pub anonymous: bool,
+}
+
+/// Earlier bodies kept per item.
+pub const MAX_REVISIONS: usize = 20;
+
+/// A body an item had before an edit replaced it.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Revision {
+    pub body: String,
+    pub replaced_at: DateTime<Utc>,
+}
+
+/// Lanes with this theme hold action items, which export as a checklist.
+pub const ACTION_ITEMS_THEME: &str = "action-items";
+/// Lanes with this theme hold the open action items carried over from the
+/// previous retro, to be marked done or re-committed.
+pub const ACTION_REVIEW_THEME: &str = "action-review";
+
+/// What makes an item an action item: who does it, by when, and how far
+/// along it is.
+#[derive(Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ActionDetails {
+    pub assignee: Option<String>,
+    pub due: Option<NaiveDate>,
+    pub status: ActionStatus,
+    /// Card the action item came out of, on the same board
+    pub source: Option<CardRef>,
+    /// Action item of an earlier board this one was carried over from
+    #[serde(default)]
+    pub carried_from: Option<Origin>,
+}
+
+impl ActionDetails {
+    /// Whether the action item is still open past its due date.
+    pub fn is_overdue(&self, today: NaiveDate) -> bool {
+        self.status.is_open() && self.due.is_some_and(|due| due < today)
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ActionStatus {
+    #[default]
+    Open,
+    InProgress,
+    Done,
+    Dropped,
+}
+
+impl ActionStatus {
+    /// Whether the action item still needs doing.
+    pub fn is_open(self) -> bool {
+        matches!(self, ActionStatus::Open | ActionStatus::InProgress)
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ActionStatus::Open => "open",
+            ActionStatus::InProgress => "in progress",
+            ActionStatus::Done => "done",
+            ActionStatus::Dropped => "dropped",
+        }
+    }
+}
+
+/// Where an item is on a board.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CardRef {
+    pub lane_id: String,
+    pub item_id: String,
+}
+
+/// Where an item is on another board.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Origin {
+    pub board_id: String,
+    pub lane_id: String,
+    pub item_id: String,
+}
+
+/// An item merged into another, kept so that the merge can be reversed.
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct Merged {
+    /// ID the item had, and gets back when unmerged
+    pub id: String,
+    /// The item as it was, with its own body and votes
+    pub item: RetroItem,
+    /// Body of the item it was merged into, from before the merge
+    pub target_body: String,
+}
+
+/// A comment on an item. Replies point at the comment they answer.
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct Comment {
+    /// Participant who wrote it
+    pub author: String,
+    pub body: String,
+    pub created_at: DateTime<Utc>,
+    /// When the body was last changed, if it was
+    pub edited_at: Option<DateTime<Utc>>,
+    /// ID of the comment this one replies to, on the same item
+    pub reply_to: Option<String>,
+    /// Whether it was written while the board was anonymous, which keeps
+    /// its author hidden even once the board shows authors
+    #[serde(default)]
+    pub anonymous: bool,
 }
 
 impl RetroItem {
     fn increment_vote(&mut self) {
         self.vote_count += 1;
     }
+
+    /// Replace the body, keeping the old one as a revision. Returns whether
+    /// the body changed.
+    fn revise(&mut self, body: &str, now: DateTime<Utc>) -> bool {
+        if self.body == body {
+            return false;
+        }
+        let old = std::mem::replace(&mut self.body, body.to_owned());
+        self.revisions.push(Revision {
+            body: old,
+            replaced_at: now,
+        });
+        if self.revisions.len() > MAX_REVISIONS {
+            let excess = self.revisions.len() - MAX_REVISIONS;
+            self.revisions.drain(..excess);
+        }
+        self.updated_at = Some(now);
+        true
+    }
+
+    /// Who reacted with each emoji, blank where that must stay hidden,
+    /// keeping how many did.
+    pub fn shown_reactions(&self, settings: &BoardSettings) -> IndexMap<String, Vec<String>> {
+        let mut reactions = self.reactions.clone();
+        for (emoji, participants) in &mut reactions {
+            let hidden = self.anonymous_reactions.get(emoji);
+            for participant in participants {
+                if settings.anonymous || hidden.is_some_and(|hidden| hidden.contains(participant)) {
+                    participant.clear();
+                }
+            }
+        }
+        reactions
+    }
+
+    /// The author other participants may see, given the board's settings.
+    pub fn shown_author(&self, settings: &BoardSettings) -> Option<&str> {
+        if settings.anonymous || self.anonymous {
+            None
+        } else {
+            self.author.as_deref()
+        }
+    }
+
+    /// Forget who added, commented on and reacted to the item where that
+    /// must stay hidden, here and in the items merged into this one.
+    fn hide_participants(&mut self, settings: &BoardSettings) {
+        if self.shown_author(settings).is_none() {
+            self.author = None;
+        }
+        hide_comment_authors(&mut self.comments, settings);
+        self.reactions = self.shown_reactions(settings);
+        self.anonymous_reactions.clear();
+        for merged in &mut self.merged {
+            merged.item.hide_participants(settings);
+        }
+    }
 }
 
-#[derive(Serialize, Deserialize)]
+/// Blank who wrote the comments written while the board was anonymous, or
+/// all of them while it is.
+pub fn hide_comment_authors(comments: &mut IndexMap<String, Comment>, settings: &BoardSettings) {
+    for comment in comments.values_mut() {
+        if settings.anonymous || comment.anonymous {
+            comment.author.clear();
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
 pub struct RetroLane {
     pub title: String,
     pub theme: String,
     pub items: HashMap<String, RetroItem>,
+    /// Named clusters of items, in the order they are shown
+    #[serde(default)]
+    pub groups: IndexMap<String, Group>,
+    /// Unknown for lanes stored before this was recorded
+    #[serde(default)]
+    pub created_at: Option<DateTime<Utc>>,
+    /// When items were last added to, removed from or edited in the lane
+    #[serde(default)]
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct Group {
+    pub title: String,
+    /// Votes cast on the group itself, when voting on groups
+    pub vote_count: u64,
 }
 
 impl RetroLane {
-    fn add_item(&mut self, body: &str) {
+    fn add_item(&mut self, body: &str) -> String {
         let id = Uuid::new_v4().to_string();
         let next_sort_order = self
             .items
@@ -32,25 +264,132 @@ impl RetroLane {
             .map(|item| item.sort_order)
             .max()
             .map_or(0, |max| max + 1);
+        let now = Some(Utc::now());
         self.items.insert(
-            id,
+            id.clone(),
             RetroItem {
                 body: body.to_owned(),
                 vote_count: 0,
                 sort_order: next_sort_order,
+                merged: Vec::new(),
+                group: None,
+                comments: IndexMap::new(),
+                reactions: IndexMap::new(),
+                anonymous_reactions: IndexMap::new(),
+                action: self.is_action_items().then(ActionDetails::default),
+                created_at: now,
+                updated_at: now,
+                revisions: Vec::new(),
+                author: None,
+                anonymous: false,
             },
         );
+        self.updated_at = now;
+        id
     }
 
-    fn remove_item(&mut self, id: &str) {
-        self.items.remove(id);
+    /// Whether the lane holds action items, new ones or carried over.
+    pub fn is_action_items(&self) -> bool {
+        self.theme == ACTION_ITEMS_THEME || self.theme == ACTION_REVIEW_THEME
+    }
+
+    fn hide_participants(&mut self, settings: &BoardSettings) {
+        for item in self.items.values_mut() {
+            item.hide_participants(settings);
+        }
     }
 
-    fn increment_vote(&mut self, id: &str) {
+    fn remove_item(&mut self, id: &str) -> Option<RetroItem> {
+        let item = self.items.remove(id)?;
+        self.updated_at = Some(Utc::now());
+        Some(item)
+    }
+
+    /// Votes of a group and of every item in it.
+    pub fn group_votes(&self, group_id: &str) -> u64 {
+        let own = self
+            .groups
+            .get(group_id)
+            .map_or(0, |group| group.vote_count);
+        let items: u64 = self
+            .items
+            .values()
+            .filter(|item| item.group.as_deref() == Some(group_id))
+            .map(|item| item.vote_count)
+            .sum();
+        own + items
+    }
+
+    fn increment_vote(&mut self, id: &str) -> bool {
         if let Some(item) = self.items.get_mut(id) {
             item.increment_vote();
+            true
         } else {
             tracing::error!("Item with ID '{}' not found in lane", id);
+            false
+        }
+    }
+}
+
+/// A deleted item or lane, kept until it is restored or purged.
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+pub struct Trashed {
+    /// Participant who deleted it
+    pub deleted_by: String,
+    pub deleted_at: DateTime<Utc>,
+    /// Whether it was deleted while the board was anonymous, which keeps
+    /// `deleted_by` hidden even once the board shows participants
+    #[serde(default)]
+    pub anonymous: bool,
+    #[serde(flatten)]
+    pub deleted: Deleted,
+}
+
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind")]
+pub enum Deleted {
+    Item {
+        /// Lane the item was in. Its `sort_order` is where it was.
+        lane_id: String,
+        item_id: String,
+        item: Box<RetroItem>,
+    },
+    /// A lane along with the items it held
+    Lane {
+        lane_id: String,
+        /// Where the lane was shown
+        position: usize,
+        lane: RetroLane,
+    },
+}
+
+/// Options chosen for one board.
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BoardSettings {
+    /// Upvote groups rather than the items in them
+    pub vote_on_groups: bool,
+    /// Emoji participants can react to items with, in the order offered
+    pub reactions: Vec<String>,
+    /// Team holding the retro, to tell boards apart when tracking action
+    /// items across them
+    pub team: Option<String>,
+    /// Keep participant IDs on the server, out of snapshots, exports and
+    /// logs. Whatever is done while this is on keeps who did it hidden for
+    /// good.
+    pub anonymous: bool,
+}
+
+/// Reactions offered on boards that have not chosen their own.
+pub const DEFAULT_REACTIONS: [&str; 6] = ["👍", "❤️", "😂", "🎉", "🤔", "👀"];
+
+impl Default for BoardSettings {
+    fn default() -> Self {
+        Self {
+            vote_on_groups: false,
+            reactions: DEFAULT_REACTIONS.map(str::to_string).to_vec(),
+            team: None,
+            anonymous: true,
         }
     }
 }
@@ -58,14 +397,111 @@ impl RetroLane {
 #[derive(Serialize, Deserialize)]
 pub struct RetroBoard {
     pub title: String,
-    pub lanes: HashMap<String, RetroLane>,
+    /// Lanes in the order they are shown
+    pub lanes: IndexMap<String, RetroLane>,
+    /// Upvotes cast so far, keyed by participant ID
+    pub votes_cast: HashMap<String, u64>,
+    /// Participants who voted while the board was anonymous, whose
+    /// `votes_cast` stay hidden even once the board shows participants
+    #[serde(default)]
+    pub anonymous_voters: HashSet<String>,
+    /// Unknown for boards stored before this was recorded
+    pub created_at: Option<DateTime<Utc>>,
+    /// Recent actions, for undo and redo. Not sent to clients.
+    pub history: History,
+    /// Deleted items and lanes by trash ID, oldest first
+    pub trash: IndexMap<String, Trashed>,
+    pub settings: BoardSettings,
 }
 
+/// Names accepted by `RetroBoard::from_template`.
+pub const TEMPLATES: [&str; 4] = ["demo", "went-well", "start-stop-continue", "mad-sad-glad"];
+
 impl RetroBoard {
+    pub fn new(title: &str) -> Self {
+        Self {
+            title: title.to_string(),
+            lanes: IndexMap::new(),
+            votes_cast: HashMap::new(),
+            anonymous_voters: HashSet::new(),
+            created_at: Some(Utc::now()),
+            history: History::default(),
+            trash: IndexMap::new(),
+            settings: BoardSettings::default(),
+        }
+    }
+
+    /// Build a new board from one of the built-in `TEMPLATES`.
+    pub fn from_template(name: &str) -> Option<Self> {
+        // (lane ID, title, theme)
+        let lanes: &[(&str, &str, &str)] = match name {
+            "demo" => return Some(Self::default()),
+            "went-well" => &[
+                ("went-well", "Went Well", "went-well"),
+                ("to-improve", "To Improve", "to-improve"),
+                ("action-items", "Action Items", "action-items"),
+            ],
+            "start-stop-continue" => &[
+                ("start", "Start", "went-well"),
+                ("stop", "Stop", "to-improve"),
+                ("continue", "Continue", "went-well"),
+                ("action-items", "Action Items", "action-items"),
+            ],
+            "mad-sad-glad" => &[
+                ("mad", "Mad", "to-improve"),
+                ("sad", "Sad", "to-improve"),
+                ("glad", "Glad", "went-well"),
+                ("action-items", "Action Items", "action-items"),
+            ],
+            _ => return None,
+        };
+
+        let mut board = Self::new("My Retro Board");
+        for (id, title, theme) in lanes {
+            board.lanes.insert(
+                id.to_string(),
+                RetroLane {
+                    title: title.to_string(),
+                    theme: theme.to_string(),
+                    items: HashMap::new(),
+                    groups: IndexMap::new(),
+                    created_at: board.created_at,
+                    updated_at: board.created_at,
+                },
+            );
+        }
+        Some(board)
+    }
+
+    /// A new board with the lanes and settings of this one but none of its
+    /// items, for the next retro of the same team.
+    pub fn empty_copy(&self, title: &str) -> Self {
+        let mut board = Self::new(title);
+        for (id, lane) in &self.lanes {
+            if lane.theme == ACTION_REVIEW_THEME {
+                continue;
+            }
+            board.lanes.insert(
+                id.clone(),
+                RetroLane {
+                    title: lane.title.clone(),
+                    theme: lane.theme.clone(),
+                    items: HashMap::new(),
+                    groups: IndexMap::new(),
+                    created_at: board.created_at,
+                    updated_at: board.created_at,
+                },
+            );
+        }
+        board.settings = self.settings.clone();
+        board
+    }
+
     pub fn default() -> Self {
+        let now = Some(Utc::now());
         Self {
             title: "My Retro Board".to_string(),
-            lanes: HashMap::from([
+            lanes: IndexMap::from([
                 (
                     "went-well".to_string(),
                     RetroLane {
@@ -78,6 +514,17 @@ impl RetroBoard {
                                     body: "We shipped the feature on time".to_string(),
                                     vote_count: 0,
                                     sort_order: 0,
+                                    merged: Vec::new(),
+                                    group: None,
+                                    comments: IndexMap::new(),
+                                    reactions: IndexMap::new(),
+                                    anonymous_reactions: IndexMap::new(),
+                                    action: None,
+                                    created_at: now,
+                                    updated_at: now,
+                                    revisions: Vec::new(),
+                                    author: None,
+                                    anonymous: false,
                                 },
                             ),
                             (
@@ -86,9 +533,23 @@ impl RetroBoard {
                                     body: "The team worked well together".to_string(),
                                     vote_count: 0,
                                     sort_order: 1,
+                                    merged: Vec::new(),
+                                    group: None,
+                                    comments: IndexMap::new(),
+                                    reactions: IndexMap::new(),
+                                    anonymous_reactions: IndexMap::new(),
+                                    action: None,
+                                    created_at: now,
+                                    updated_at: now,
+                                    revisions: Vec::new(),
+                                    author: None,
+                                    anonymous: false,
                                 },
                             ),
                         ]),
+                        groups: IndexMap::new(),
+                        created_at: now,
+                        updated_at: now,
                     },
                 ),
                 (
@@ -103,6 +564,17 @@ impl RetroBoard {
                                     body: "We need to improve our testing".to_string(),
                                     vote_count: 0,
                                     sort_order: 0,
+                                    merged: Vec::new(),
+                                    group: None,
+                                    comments: IndexMap::new(),
+                                    reactions: IndexMap::new(),
+                                    anonymous_reactions: IndexMap::new(),
+                                    action: None,
+                                    created_at: now,
+                                    updated_at: now,
+                                    revisions: Vec::new(),
+                                    author: None,
+                                    anonymous: false,
                                 },
                             ),
                             (
@@ -111,9 +583,23 @@ impl RetroBoard {
                                     body: "We need to improve our communication".to_string(),
                                     vote_count: 0,
                                     sort_order: 1,
+                                    merged: Vec::new(),
+                                    group: None,
+                                    comments: IndexMap::new(),
+                                    reactions: IndexMap::new(),
+                                    anonymous_reactions: IndexMap::new(),
+                                    action: None,
+                                    created_at: now,
+                                    updated_at: now,
+                                    revisions: Vec::new(),
+                                    author: None,
+                                    anonymous: false,
                                 },
                             ),
                         ]),
+                        groups: IndexMap::new(),
+                        created_at: now,
+                        updated_at: now,
                     },
                 ),
                 (
@@ -128,6 +614,17 @@ impl RetroBoard {
                                     body: "Write more tests".to_string(),
                                     vote_count: 0,
                                     sort_order: 0,
+                                    merged: Vec::new(),
+                                    group: None,
+                                    comments: IndexMap::new(),
+                                    reactions: IndexMap::new(),
+                                    anonymous_reactions: IndexMap::new(),
+                                    action: Some(ActionDetails::default()),
+                                    created_at: now,
+                                    updated_at: now,
+                                    revisions: Vec::new(),
+                                    author: None,
+                                    anonymous: false,
                                 },
                             ),
                             (
@@ -136,43 +633,113 @@ impl RetroBoard {
                                     body: "Schedule a team-building event".to_string(),
                                     vote_count: 0,
                                     sort_order: 1,
+                                    merged: Vec::new(),
+                                    group: None,
+                                    comments: IndexMap::new(),
+                                    reactions: IndexMap::new(),
+                                    anonymous_reactions: IndexMap::new(),
+                                    action: Some(ActionDetails::default()),
+                                    created_at: now,
+                                    updated_at: now,
+                                    revisions: Vec::new(),
+                                    author: None,
+                                    anonymous: false,
                                 },
                             ),
                         ]),
+                        groups: IndexMap::new(),
+                        created_at: now,
+                        updated_at: now,
                     },
                 ),
             ]),
+            votes_cast: HashMap::new(),
+            anonymous_voters: HashSet::new(),
+            created_at: now,
+            history: History::default(),
+            trash: IndexMap::new(),
+            settings: BoardSettings::default(),
         }
     }
 
-    pub fn save_to_file(&self, path: &str) {
-        let file = File::create(path).unwrap();
-        serde_json::to_writer(file, self).unwrap();
+    pub fn save_to_file(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        // Write a temporary file and rename it over the board, so a process
+        // killed mid-save never leaves a truncated board behind
+        let path = path.as_ref();
+        let mut tmp_path = path.as_os_str().to_owned();
+        tmp_path.push(".tmp");
+
+        let file = File::create(&tmp_path)?;
+        serde_json::to_writer(&file, &schema::Versioned::new(self))?;
+        file.sync_all()?;
+        std::fs::rename(&tmp_path, path)
     }
 
-    pub fn load_from_file(path: &str) -> Self {
-        let exists = std::path::Path::new(path).exists();
-        if exists {
-            let file = match File::open(path) {
-                Ok(file) => file,
-                Err(e) => {
-                    tracing::error!("Error opening file, returning default RetroBoard: {:?}", e);
-                    return Self::default();
-                }
-            };
-            match serde_json::from_reader(file) {
-                Ok(board) => board,
-                Err(e) => {
-                    tracing::error!("Error reading file, returning default RetroBoard: {:?}", e);
-                    Self::default()
-                }
+    /// The board as sent to clients, without what only the server needs.
+    pub fn client_snapshot(&self) -> String {
+        self.public_value().to_string()
+    }
+
+    /// The board without its history and without the participants kept
+    /// hidden, for anything that leaves the server. Anonymous boards leave
+    /// out every participant ID: who wrote, commented, reacted, voted and
+    /// deleted. So do boards that show participants for whatever was done
+    /// while they were anonymous.
+    pub fn public_value(&self) -> serde_json::Value {
+        let mut lanes = self.lanes.clone();
+        for lane in lanes.values_mut() {
+            lane.hide_participants(&self.settings);
+        }
+        let mut trash = self.trash.clone();
+        for trashed in trash.values_mut() {
+            if self.settings.anonymous || trashed.anonymous {
+                trashed.deleted_by.clear();
+            }
+            match &mut trashed.deleted {
+                Deleted::Item { item, .. } => item.hide_participants(&self.settings),
+                Deleted::Lane { lane, .. } => lane.hide_participants(&self.settings),
             }
-        } else {
-            Self::default()
         }
+
+        let mut value = serde_json::to_value(self).unwrap();
+        if let Some(board) = value.as_object_mut() {
+            board.remove("history");
+            board.insert("lanes".to_string(), serde_json::to_value(lanes).unwrap());
+            board.insert("trash".to_string(), serde_json::to_value(trash).unwrap());
+            board.remove("anonymous_voters");
+            let votes_cast: HashMap<&String, &u64> = self
+                .votes_cast
+                .iter()
+                .filter(|(participant, _)| {
+                    !self.settings.anonymous && !self.anonymous_voters.contains(*participant)
+                })
+                .collect();
+            board.insert(
+                "votes_cast".to_string(),
+                serde_json::to_value(votes_cast).unwrap(),
+            );
+        }
+        value
+    }
+
+    /// Load a saved board, or the default board if there is no file yet. A
+    /// file that exists but cannot be read or migrated is an error, so that
+    /// it is never replaced by the default board on the next save.
+    pub fn load_from_file(path: impl AsRef<Path>) -> Result<Self, String> {
+        let path = path.as_ref();
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let file =
+            File::open(path).map_err(|e| format!("cannot open {}: {}", path.display(), e))?;
+        serde_json::from_reader(file)
+            .map_err(schema::SchemaError::from)
+            .and_then(schema::from_value)
+            .map_err(|e| format!("cannot load {}: {}", path.display(), e))
     }
 
     pub fn add_lane(&mut self, title: &str) {
+        let now = Some(Utc::now());
         let title_string = title.to_string();
         self.lanes.insert(
             title_string.clone(),
@@ -180,31 +747,329 @@ impl RetroBoard {
                 title: title_string,
                 theme: self.title.clone(),
                 items: HashMap::new(),
+                groups: IndexMap::new(),
+                created_at: now,
+                updated_at: now,
             },
         );
     }
 
-    pub fn add_item(&mut self, lane_id: &str, body: &str) {
+    /// Add an item, returning its ID, or `None` if the lane was not found.
+    pub fn add_item(&mut self, lane_id: &str, body: &str) -> Option<String> {
         if let Some(lane) = self.lanes.get_mut(lane_id) {
-            lane.add_item(body);
+            Some(lane.add_item(body))
         } else {
             tracing::error!("Lane with ID '{}' not found", lane_id);
+            None
         }
     }
 
-    pub fn remove_item(&mut self, lane_id: &str, id: &str) {
-        if let Some(lane) = self.lanes.get_mut(lane_id) {
-            lane.remove_item(id);
+    /// Add an action item to the first action items lane, adding that lane
+    /// if there is none. Returns the lane and ID of the new item.
+    pub fn add_action_item(&mut self, body: &str, details: ActionDetails) -> (String, String) {
+        let lane_id = match self.action_items_lane_id() {
+            Some(lane_id) => lane_id.to_string(),
+            None => self.add_imported_lane("Action Items"),
+        };
+        let lane = self.lanes.get_mut(&lane_id).unwrap();
+        let id = lane.add_item(body);
+        lane.items.get_mut(&id).unwrap().action = Some(details);
+        (lane_id, id)
+    }
+
+    /// Record who added an item, and whether the board was anonymous then.
+    pub fn set_author(&mut self, lane_id: &str, item_id: &str, author: &str) {
+        let anonymous = self.settings.anonymous;
+        if let Some(item) = self
+            .lanes
+            .get_mut(lane_id)
+            .and_then(|lane| lane.items.get_mut(item_id))
+        {
+            item.author = Some(author.to_string());
+            item.anonymous = anonymous;
+        }
+    }
+
+    /// The lane new action items go to, if the board has one.
+    pub fn action_items_lane_id(&self) -> Option<&str> {
+        self.lanes
+            .iter()
+            .find(|(_, lane)| lane.theme == ACTION_ITEMS_THEME)
+            .map(|(id, _)| id.as_str())
+    }
+
+    /// Copy the open action items of `previous`, the board stored as
+    /// `previous_id`, into a review lane shown first. Each copy links back to
+    /// the item it came from. Returns how many were carried over.
+    pub fn carry_over_action_items(&mut self, previous_id: &str, previous: &RetroBoard) -> usize {
+        let mut items = Vec::new();
+        for (lane_id, lane) in previous.lanes.iter().filter(|(_, l)| l.is_action_items()) {
+            let mut open: Vec<_> = lane
+                .items
+                .iter()
+                .filter(|(_, item)| item.action.as_ref().is_some_and(|a| a.status.is_open()))
+                .collect();
+            open.sort_by_key(|(_, item)| item.sort_order);
+            items.extend(open.into_iter().map(|(item_id, item)| {
+                let details = item.action.as_ref().unwrap();
+                let details = ActionDetails {
+                    assignee: details.assignee.clone(),
+                    due: details.due,
+                    status: details.status,
+                    // The card it came out of is on the previous board
+                    source: None,
+                    carried_from: Some(Origin {
+                        board_id: previous_id.to_string(),
+                        lane_id: lane_id.clone(),
+                        item_id: item_id.clone(),
+                    }),
+                };
+                (item.body.as_str(), details)
+            }));
+        }
+        if items.is_empty() {
+            return 0;
+        }
+
+        let now = Some(Utc::now());
+        let mut lane = RetroLane {
+            title: "Previous Action Items".to_string(),
+            theme: ACTION_REVIEW_THEME.to_string(),
+            items: HashMap::new(),
+            groups: IndexMap::new(),
+            created_at: now,
+            updated_at: now,
+        };
+        let carried = items.len();
+        for (body, details) in items {
+            let id = lane.add_item(body);
+            lane.items.get_mut(&id).unwrap().action = Some(details);
+        }
+        let lane_id = if self.lanes.contains_key(ACTION_REVIEW_THEME) {
+            Uuid::new_v4().to_string()
+        } else {
+            ACTION_REVIEW_THEME.to_string()
+        };
+        self.lanes.shift_insert(0, lane_id, lane);
+        carried
+    }
+
+    /// Move a carried over action item into the action items lane, open
+    /// again, adding that lane if there is none.
+    pub fn recommit_action_item(&mut self, lane_id: &str, item_id: &str, due: Option<NaiveDate>) {
+        let to_lane_id = match self.action_items_lane_id() {
+            Some(to_lane_id) => to_lane_id.to_string(),
+            None => self.add_imported_lane("Action Items"),
+        };
+        if to_lane_id != lane_id {
+            self.move_item(lane_id, &to_lane_id, item_id);
+        }
+        let due = due.map(Some);
+        self.update_action_item(&to_lane_id, item_id, None, due, Some(ActionStatus::Open));
+    }
+
+    /// Change the details of an action item, leaving those given as `None`
+    /// as they are.
+    pub fn update_action_item(
+        &mut self,
+        lane_id: &str,
+        item_id: &str,
+        assignee: Option<Option<String>>,
+        due: Option<Option<NaiveDate>>,
+        status: Option<ActionStatus>,
+    ) {
+        let Some(details) = self
+            .item_mut(lane_id, item_id)
+            .and_then(|item| item.action.as_mut())
+        else {
+            tracing::error!("Action item '{}' not found in lane '{}'", item_id, lane_id);
+            return;
+        };
+        if let Some(assignee) = assignee {
+            details.assignee = assignee;
+        }
+        if let Some(due) = due {
+            details.due = due;
+        }
+        if let Some(status) = status {
+            details.status = status;
+        }
+    }
+
+    /// ID of the lane titled `title`, ignoring case and surrounding spaces.
+    pub fn lane_id_by_title(&self, title: &str) -> Option<&str> {
+        let title = title.trim();
+        self.lanes
+            .iter()
+            .find(|(_, lane)| lane.title.trim().eq_ignore_ascii_case(title))
+            .map(|(id, _)| id.as_str())
+    }
+
+    /// Add a lane for imported items and return its ID. Unlike `add_lane`,
+    /// this never replaces an existing lane. A lane titled "Action Items"
+    /// gets the action items theme.
+    pub fn add_imported_lane(&mut self, title: &str) -> String {
+        let title = title.trim();
+        let slug: String = title
+            .to_lowercase()
+            .split(|c: char| !c.is_ascii_alphanumeric())
+            .filter(|part| !part.is_empty())
+            .collect::<Vec<_>>()
+            .join("-");
+        let id = if slug.is_empty() || self.lanes.contains_key(&slug) {
+            Uuid::new_v4().to_string()
+        } else {
+            slug
+        };
+        let theme = if title.eq_ignore_ascii_case("action items") {
+            ACTION_ITEMS_THEME
         } else {
+            "went-well"
+        };
+        let now = Some(Utc::now());
+        self.lanes.insert(
+            id.clone(),
+            RetroLane {
+                title: title.to_string(),
+                theme: theme.to_string(),
+                items: HashMap::new(),
+                groups: IndexMap::new(),
+                created_at: now,
+                updated_at: now,
+            },
+        );
+        id
+    }
+
+    /// Move an item to the trash.
+    pub fn remove_item(&mut self, lane_id: &str, id: &str, deleted_by: &str) {
+        let Some(lane) = self.lanes.get_mut(lane_id) else {
             tracing::error!("Lane with ID '{}' not found", lane_id);
+            return;
+        };
+        if let Some(item) = lane.remove_item(id) {
+            let deleted = Deleted::Item {
+                lane_id: lane_id.to_string(),
+                item_id: id.to_string(),
+                item: Box::new(item),
+            };
+            self.trash(deleted, deleted_by);
         }
     }
 
-    pub fn upvote_item(&mut self, lane_id: &str, id: &str) {
+    /// Move a lane and its items to the trash.
+    pub fn remove_lane(&mut self, lane_id: &str, deleted_by: &str) {
+        let Some((position, lane_id, lane)) = self.lanes.shift_remove_full(lane_id) else {
+            tracing::error!("Lane with ID '{}' not found", lane_id);
+            return;
+        };
+        let deleted = Deleted::Lane {
+            lane_id,
+            position,
+            lane,
+        };
+        self.trash(deleted, deleted_by);
+    }
+
+    fn trash(&mut self, deleted: Deleted, deleted_by: &str) {
+        self.trash.insert(
+            Uuid::new_v4().to_string(),
+            Trashed {
+                deleted_by: deleted_by.to_string(),
+                deleted_at: Utc::now(),
+                anonymous: self.settings.anonymous,
+                deleted,
+            },
+        );
+    }
+
+    /// Put an item or lane from the trash back where it was deleted from.
+    pub fn restore(&mut self, trash_id: &str) {
+        let Some(trashed) = self.trash.get(trash_id) else {
+            tracing::error!("Nothing with ID '{}' in the trash", trash_id);
+            return;
+        };
+        match &trashed.deleted {
+            Deleted::Item {
+                lane_id, item_id, ..
+            } => {
+                let Some(lane) = self.lanes.get(lane_id) else {
+                    tracing::error!("Lane with ID '{}' not found, restore it first", lane_id);
+                    return;
+                };
+                if lane.items.contains_key(item_id) {
+                    tracing::error!("Item with ID '{}' already exists", item_id);
+                    return;
+                }
+            }
+            Deleted::Lane { lane_id, .. } => {
+                if self.lanes.contains_key(lane_id) {
+                    tracing::error!("Lane with ID '{}' already exists", lane_id);
+                    return;
+                }
+            }
+        }
+
+        match self.trash.shift_remove(trash_id).unwrap().deleted {
+            Deleted::Item {
+                lane_id,
+                item_id,
+                item,
+            } => {
+                let lane = self.lanes.get_mut(&lane_id).unwrap();
+                let mut item = *item;
+                if item
+                    .group
+                    .as_ref()
+                    .is_some_and(|group| !lane.groups.contains_key(group))
+                {
+                    item.group = None;
+                }
+                // Make room at the item's old place
+                for other in lane.items.values_mut() {
+                    if other.sort_order >= item.sort_order {
+                        other.sort_order += 1;
+                    }
+                }
+                lane.items.insert(item_id, item);
+                lane.updated_at = Some(Utc::now());
+            }
+            Deleted::Lane {
+                lane_id,
+                position,
+                lane,
+            } => {
+                let position = position.min(self.lanes.len());
+                self.lanes.shift_insert(position, lane_id, lane);
+            }
+        }
+    }
+
+    /// Delete for good everything trashed before `cutoff`, or everything if
+    /// not given. Returns how many were purged.
+    pub fn purge_trash(&mut self, cutoff: Option<DateTime<Utc>>) -> usize {
+        let before = self.trash.len();
+        self.trash
+            .retain(|_, trashed| cutoff.is_some_and(|cutoff| trashed.deleted_at >= cutoff));
+        before - self.trash.len()
+    }
+
+    /// Count a vote cast by `participant`, and whether the board was
+    /// anonymous then.
+    pub fn count_vote(&mut self, participant: &str) {
+        *self.votes_cast.entry(participant.to_string()).or_default() += 1;
+        if self.settings.anonymous {
+            self.anonymous_voters.insert(participant.to_string());
+        }
+    }
+
+    /// Upvote an item, returning whether the item was found.
+    pub fn upvote_item(&mut self, lane_id: &str, id: &str) -> bool {
         if let Some(lane) = self.lanes.get_mut(lane_id) {
-            lane.increment_vote(id);
+            lane.increment_vote(id)
         } else {
             tracing::error!("Lane with ID '{}' not found", lane_id);
+            false
         }
     }
 
@@ -228,8 +1093,7 @@ impl RetroBoard {
             .lanes
             .get_mut(from_lane_id)
             .unwrap()
-            .items
-            .remove(item_id);
+            .remove_item(item_id);
 
         // Add the item to the destination lane if it was found
         if let Some(mut item) = item {
@@ -242,7 +1106,16 @@ impl RetroBoard {
                 .max()
                 .map_or(0, |max| max.saturating_add(1));
             item.sort_order = next_sort_order;
+            // Groups belong to a lane
+            item.group = None;
+            // Only items in action items lanes are action items
+            if !to_lane.is_action_items() {
+                item.action = None;
+            } else if item.action.is_none() {
+                item.action = Some(ActionDetails::default());
+            }
             to_lane.items.insert(item_id.to_string(), item);
+            to_lane.updated_at = Some(Utc::now());
         } else {
             tracing::error!(
                 "Item with ID '{}' not found in lane '{}'",
@@ -252,10 +1125,14 @@ impl RetroBoard {
         }
     }
 
+    /// Change the body of an item, keeping the old one as a revision.
     pub fn edit_item(&mut self, lane_id: &str, id: &str, body: &str) {
         if let Some(lane) = self.lanes.get_mut(lane_id) {
             if let Some(item) = lane.items.get_mut(id) {
-                item.body = body.to_owned();
+                let now = Utc::now();
+                if item.revise(body, now) {
+                    lane.updated_at = Some(now);
+                }
             } else {
                 tracing::error!("Item with ID '{}' not found in lane '{}'", id, lane_id);
             }
@@ -299,20 +1176,324 @@ impl RetroBoard {
                 return;
             }
 
-            let source_votes = lane.items.get(source_id).unwrap().vote_count;
-
             // Remove the source item (safe — we verified it exists above)
-            lane.items.remove(source_id);
+            let source = lane.items.remove(source_id).unwrap();
 
             // Update the target item (safe — we verified it exists above)
             let target = lane.items.get_mut(target_id).unwrap();
-            target.body = merged_body.to_owned();
-            target.vote_count += source_votes;
+            target.vote_count += source.vote_count;
+            let target_body = std::mem::replace(&mut target.body, merged_body.to_owned());
+            let now = Some(Utc::now());
+            target.updated_at = now;
+            // The discussion carries over, and goes back if the merge is undone
+            target.comments.extend(source.comments.clone());
+            target.merged.push(Merged {
+                id: source_id.to_string(),
+                item: source,
+                target_body,
+            });
+            lane.updated_at = now;
         } else {
             tracing::error!("Lane with ID '{}' not found", lane_id);
         }
     }
 
+    /// Add an empty group at the end of a lane, returning its ID.
+    pub fn add_group(&mut self, lane_id: &str, title: &str) -> Option<String> {
+        let Some(lane) = self.lanes.get_mut(lane_id) else {
+            tracing::error!("Lane with ID '{}' not found", lane_id);
+            return None;
+        };
+        let id = Uuid::new_v4().to_string();
+        let group = Group {
+            title: title.to_string(),
+            vote_count: 0,
+        };
+        lane.groups.insert(id.clone(), group);
+        Some(id)
+    }
+
+    pub fn rename_group(&mut self, lane_id: &str, group_id: &str, title: &str) {
+        match self.group_mut(lane_id, group_id) {
+            Some(group) => group.title = title.to_string(),
+            None => tracing::error!("Group '{}' not found in lane '{}'", group_id, lane_id),
+        }
+    }
+
+    /// Remove a group. Its items stay in the lane, outside any group.
+    pub fn remove_group(&mut self, lane_id: &str, group_id: &str) {
+        let Some(lane) = self.lanes.get_mut(lane_id) else {
+            tracing::error!("Lane with ID '{}' not found", lane_id);
+            return;
+        };
+        if lane.groups.shift_remove(group_id).is_none() {
+            tracing::error!("Group '{}' not found in lane '{}'", group_id, lane_id);
+            return;
+        }
+        for item in lane.items.values_mut() {
+            if item.group.as_deref() == Some(group_id) {
+                item.group = None;
+            }
+        }
+    }
+
+    /// Put an item into a group of its lane, or take it out of its group.
+    pub fn group_item(&mut self, lane_id: &str, item_id: &str, group_id: Option<&str>) {
+        let Some(lane) = self.lanes.get_mut(lane_id) else {
+            tracing::error!("Lane with ID '{}' not found", lane_id);
+            return;
+        };
+        if let Some(group_id) = group_id {
+            if !lane.groups.contains_key(group_id) {
+                tracing::error!("Group '{}' not found in lane '{}'", group_id, lane_id);
+                return;
+            }
+        }
+        match lane.items.get_mut(item_id) {
+            Some(item) => item.group = group_id.map(str::to_string),
+            None => tracing::error!("Item with ID '{}' not found in lane '{}'", item_id, lane_id),
+        }
+    }
+
+    /// Move a group to `new_position` among the groups of its lane.
+    pub fn reorder_group(&mut self, lane_id: &str, group_id: &str, new_position: u64) {
+        let Some(lane) = self.lanes.get_mut(lane_id) else {
+            tracing::error!("Lane with ID '{}' not found", lane_id);
+            return;
+        };
+        let Some(index) = lane.groups.get_index_of(group_id) else {
+            tracing::error!("Group '{}' not found in lane '{}'", group_id, lane_id);
+            return;
+        };
+        let new_index = (new_position as usize).min(lane.groups.len() - 1);
+        lane.groups.move_index(index, new_index);
+    }
+
+    /// Upvote a group, returning whether the group was found.
+    pub fn upvote_group(&mut self, lane_id: &str, group_id: &str) -> bool {
+        match self.group_mut(lane_id, group_id) {
+            Some(group) => {
+                group.vote_count += 1;
+                true
+            }
+            None => {
+                tracing::error!("Group '{}' not found in lane '{}'", group_id, lane_id);
+                false
+            }
+        }
+    }
+
+    fn group_mut(&mut self, lane_id: &str, group_id: &str) -> Option<&mut Group> {
+        self.lanes.get_mut(lane_id)?.groups.get_mut(group_id)
+    }
+
+    /// Split the items merged into an item back out, right after it, and
+    /// give it back the body it had before the first merge. The votes the
+    /// merged items brought along go with them.
+    pub fn unmerge_items(&mut self, lane_id: &str, id: &str) {
+        let Some(lane) = self.lanes.get_mut(lane_id) else {
+            tracing::error!("Lane with ID '{}' not found", lane_id);
+            return;
+        };
+        let Some(item) = lane.items.get(id) else {
+            tracing::error!("Item with ID '{}' not found in lane '{}'", id, lane_id);
+            return;
+        };
+        if item.merged.is_empty() {
+            tracing::error!("Item with ID '{}' has nothing merged into it", id);
+            return;
+        }
+        if let Some(taken) = item
+            .merged
+            .iter()
+            .find(|merged| lane.items.contains_key(&merged.id))
+        {
+            tracing::error!("Item with ID '{}' already exists", taken.id);
+            return;
+        }
+
+        let item = lane.items.get_mut(id).unwrap();
+        let merged = std::mem::take(&mut item.merged);
+        item.body = merged[0].target_body.clone();
+        let now = Some(Utc::now());
+        item.updated_at = now;
+        let brought: u64 = merged.iter().map(|merged| merged.item.vote_count).sum();
+        item.vote_count = item.vote_count.saturating_sub(brought);
+        let after = item.sort_order;
+
+        // Make room right after the item, keeping the merge order
+        let count = merged.len() as u64;
+        for other in lane.items.values_mut() {
+            if other.sort_order > after {
+                other.sort_order += count;
+            }
+        }
+        let target = lane.items.get_mut(id).unwrap();
+        let group = target.group.clone();
+        let mut split = Vec::with_capacity(merged.len());
+        for (offset, merged) in (1..).zip(merged) {
+            let mut item = merged.item;
+            item.sort_order = after + offset;
+            item.group = group.clone();
+            // Take back its comments as they are now, without those deleted
+            // since the merge
+            item.comments = item
+                .comments
+                .keys()
+                .filter_map(|comment_id| target.comments.shift_remove_entry(comment_id))
+                .collect();
+            split.push((merged.id, item));
+        }
+        lane.items.extend(split);
+        lane.updated_at = now;
+    }
+
+    /// Give an item back the body of one of its `revisions`, by index. The
+    /// body it had is kept as a revision in turn.
+    pub fn roll_back_item(&mut self, lane_id: &str, item_id: &str, revision: usize) {
+        let Some(lane) = self.lanes.get_mut(lane_id) else {
+            tracing::error!("Lane with ID '{}' not found", lane_id);
+            return;
+        };
+        let Some(item) = lane.items.get_mut(item_id) else {
+            tracing::error!("Item with ID '{}' not found in lane '{}'", item_id, lane_id);
+            return;
+        };
+        let Some(body) = item.revisions.get(revision).map(|r| r.body.clone()) else {
+            tracing::error!("Item with ID '{}' has no revision {}", item_id, revision);
+            return;
+        };
+        let now = Utc::now();
+        if item.revise(&body, now) {
+            lane.updated_at = Some(now);
+        }
+    }
+
+    /// Comment on an item, returning the ID of the new comment.
+    pub fn add_comment(
+        &mut self,
+        lane_id: &str,
+        item_id: &str,
+        author: &str,
+        body: &str,
+        reply_to: Option<&str>,
+    ) -> Option<String> {
+        let anonymous = self.settings.anonymous;
+        let Some(item) = self.item_mut(lane_id, item_id) else {
+            tracing::error!("Item with ID '{}' not found in lane '{}'", item_id, lane_id);
+            return None;
+        };
+        if let Some(reply_to) = reply_to {
+            if !item.comments.contains_key(reply_to) {
+                tracing::error!("Comment '{}' not found on item '{}'", reply_to, item_id);
+                return None;
+            }
+        }
+        let id = Uuid::new_v4().to_string();
+        let comment = Comment {
+            author: author.to_string(),
+            body: body.to_string(),
+            created_at: Utc::now(),
+            edited_at: None,
+            reply_to: reply_to.map(str::to_string),
+            anonymous,
+        };
+        item.comments.insert(id.clone(), comment);
+        Some(id)
+    }
+
+    pub fn edit_comment(&mut self, lane_id: &str, item_id: &str, comment_id: &str, body: &str) {
+        match self.comment_mut(lane_id, item_id, comment_id) {
+            Some(comment) => {
+                comment.body = body.to_string();
+                comment.edited_at = Some(Utc::now());
+            }
+            None => tracing::error!("Comment '{}' not found on item '{}'", comment_id, item_id),
+        }
+    }
+
+    /// Delete a comment. Its replies move up to the comment it replied to,
+    /// so the rest of the thread stays.
+    pub fn delete_comment(&mut self, lane_id: &str, item_id: &str, comment_id: &str) {
+        let Some(item) = self.item_mut(lane_id, item_id) else {
+            tracing::error!("Item with ID '{}' not found in lane '{}'", item_id, lane_id);
+            return;
+        };
+        let Some(deleted) = item.comments.shift_remove(comment_id) else {
+            tracing::error!("Comment '{}' not found on item '{}'", comment_id, item_id);
+            return;
+        };
+        for comment in item.comments.values_mut() {
+            if comment.reply_to.as_deref() == Some(comment_id) {
+                comment.reply_to = deleted.reply_to.clone();
+            }
+        }
+    }
+
+    /// Add a participant's reaction to an item, or take it back if they
+    /// already reacted with that emoji.
+    pub fn toggle_reaction(
+        &mut self,
+        lane_id: &str,
+        item_id: &str,
+        emoji: &str,
+        participant: &str,
+    ) {
+        let anonymous = self.settings.anonymous;
+        let Some(item) = self.item_mut(lane_id, item_id) else {
+            tracing::error!("Item with ID '{}' not found in lane '{}'", item_id, lane_id);
+            return;
+        };
+        let participants = item.reactions.entry(emoji.to_string()).or_default();
+        match participants.iter().position(|p| p == participant) {
+            Some(index) => {
+                participants.remove(index);
+                if participants.is_empty() {
+                    item.reactions.shift_remove(emoji);
+                }
+                if let Some(hidden) = item.anonymous_reactions.get_mut(emoji) {
+                    hidden.retain(|p| p != participant);
+                    if hidden.is_empty() {
+                        item.anonymous_reactions.shift_remove(emoji);
+                    }
+                }
+            }
+            None => {
+                participants.push(participant.to_string());
+                if anonymous {
+                    item.anonymous_reactions
+                        .entry(emoji.to_string())
+                        .or_default()
+                        .push(participant.to_string());
+                }
+            }
+        }
+    }
+
+    pub fn comment(&self, lane_id: &str, item_id: &str, comment_id: &str) -> Option<&Comment> {
+        self.lanes
+            .get(lane_id)?
+            .items
+            .get(item_id)?
+            .comments
+            .get(comment_id)
+    }
+
+    fn comment_mut(
+        &mut self,
+        lane_id: &str,
+        item_id: &str,
+        comment_id: &str,
+    ) -> Option<&mut Comment> {
+        self.item_mut(lane_id, item_id)?
+            .comments
+            .get_mut(comment_id)
+    }
+
+    fn item_mut(&mut self, lane_id: &str, item_id: &str) -> Option<&mut RetroItem> {
+        self.lanes.get_mut(lane_id)?.items.get_mut(item_id)
+    }
+
     pub fn reorder_item(&mut self, lane_id: &str, item_id: &str, new_position: u64) {
         tracing::debug!(
             "reorder_item called - lane: {}, item: {}, position: {}",
@@ -376,6 +1557,9 @@ mod tests {
             title: "Test Lane".to_string(),
             theme: "Test Theme".to_string(),
             items: HashMap::new(),
+            groups: IndexMap::new(),
+            created_at: None,
+            updated_at: None,
         };
 
         lane.add_item("Test Item");
@@ -392,6 +1576,9 @@ mod tests {
             title: "Test Lane".to_string(),
             theme: "Test Theme".to_string(),
             items: HashMap::new(),
+            groups: IndexMap::new(),
+            created_at: None,
+            updated_at: None,
         };
 
         lane.add_item("First Item");
@@ -415,6 +1602,9 @@ mod tests {
             title: "Test Lane".to_string(),
             theme: "Test Theme".to_string(),
             items: HashMap::new(),
+            groups: IndexMap::new(),
+            created_at: None,
+            updated_at: None,
         };
 
         lane.add_item("Test Item");
@@ -429,6 +1619,9 @@ mod tests {
             title: "Test Lane".to_string(),
             theme: "Test Theme".to_string(),
             items: HashMap::new(),
+            groups: IndexMap::new(),
+            created_at: None,
+            updated_at: None,
         };
 
         // Try to remove an item that doesn't exist
@@ -442,6 +1635,9 @@ mod tests {
             title: "Test Lane".to_string(),
             theme: "Test Theme".to_string(),
             items: HashMap::new(),
+            groups: IndexMap::new(),
+            created_at: None,
+            updated_at: None,
         };
 
         lane.add_item("Test Item");
@@ -457,6 +1653,9 @@ mod tests {
             title: "Test Lane".to_string(),
             theme: "Test Theme".to_string(),
             items: HashMap::new(),
+            groups: IndexMap::new(),
+            created_at: None,
+            updated_at: None,
         };
 
         // Should not panic — gracefully logs and returns
@@ -469,6 +1668,17 @@ mod tests {
             body: "Test".to_string(),
             vote_count: 0,
             sort_order: 0,
+            merged: Vec::new(),
+            group: None,
+            comments: IndexMap::new(),
+            reactions: IndexMap::new(),
+            anonymous_reactions: IndexMap::new(),
+            action: None,
+            created_at: None,
+            updated_at: None,
+            revisions: Vec::new(),
+            author: None,
+            anonymous: false,
         };
 
         item.increment_vote();
@@ -480,10 +1690,7 @@ mod tests {
 
     #[test]
     fn test_add_lane() {
-        let mut board = RetroBoard {
-            title: "Test Board".to_string(),
-            lanes: HashMap::new(),
-        };
+        let mut board = RetroBoard::new("Test Board");
 
         board.add_lane("Test Lane");
         assert_eq!(board.lanes.len(), 1);
@@ -493,12 +1700,28 @@ mod tests {
         assert_eq!(lane.items.len(), 0);
     }
 
+    #[test]
+    fn test_imported_lanes() {
+        let mut board = RetroBoard::from_template("went-well").unwrap();
+        assert_eq!(board.lane_id_by_title(" went well "), Some("went-well"));
+        assert_eq!(board.lane_id_by_title("Kudos"), None);
+
+        let kudos = board.add_imported_lane("Kudos & Thanks");
+        assert_eq!(kudos, "kudos-thanks");
+        assert_eq!(board.lanes[&kudos].theme, "went-well");
+
+        // An existing lane is never replaced
+        let clash = board.add_imported_lane("went-well");
+        assert_ne!(clash, "went-well");
+        assert_eq!(board.lanes["went-well"].title, "Went Well");
+
+        let actions = board.add_imported_lane("ACTION ITEMS");
+        assert_eq!(board.lanes[&actions].theme, "action-items");
+    }
+
     #[test]
     fn test_add_duplicate_lane() {
-        let mut board = RetroBoard {
-            title: "Test Board".to_string(),
-            lanes: HashMap::new(),
-        };
+        let mut board = RetroBoard::new("Test Board");
 
         board.add_lane("Test Lane");
         board.add_lane("Test Lane"); // Add same lane again
@@ -507,10 +1730,7 @@ mod tests {
 
     #[test]
     fn test_add_item_to_lane() {
-        let mut board = RetroBoard {
-            title: "Test Board".to_string(),
-            lanes: HashMap::new(),
-        };
+        let mut board = RetroBoard::new("Test Board");
 
         board.add_lane("Test Lane");
         board.add_item("Test Lane", "Test Item");
@@ -522,10 +1742,7 @@ mod tests {
 
     #[test]
     fn test_add_item_to_nonexistent_lane() {
-        let mut board = RetroBoard {
-            title: "Test Board".to_string(),
-            lanes: HashMap::new(),
-        };
+        let mut board = RetroBoard::new("Test Board");
 
         // Should not panic — gracefully logs and returns
         board.add_item("Nonexistent Lane", "Test Item");
@@ -533,37 +1750,135 @@ mod tests {
 
     #[test]
     fn test_remove_item_from_lane() {
-        let mut board = RetroBoard {
-            title: "Test Board".to_string(),
-            lanes: HashMap::new(),
-        };
+        let mut board = RetroBoard::new("Test Board");
 
         board.add_lane("Test Lane");
         board.add_item("Test Lane", "Test Item");
         let lane = board.lanes.get("Test Lane").unwrap();
         let item_id = lane.items.keys().next().unwrap().clone();
-        board.remove_item("Test Lane", &item_id);
+        board.remove_item("Test Lane", &item_id, "tester");
         let lane = board.lanes.get("Test Lane").unwrap();
         assert_eq!(lane.items.len(), 0);
     }
 
     #[test]
     fn test_remove_item_from_nonexistent_lane() {
-        let mut board = RetroBoard {
-            title: "Test Board".to_string(),
-            lanes: HashMap::new(),
-        };
+        let mut board = RetroBoard::new("Test Board");
 
         // Should not panic — gracefully logs and returns
-        board.remove_item("Nonexistent Lane", "item_id");
+        board.remove_item("Nonexistent Lane", "item_id", "tester");
+    }
+
+    #[test]
+    fn test_removed_item_is_restored_in_place() {
+        let mut board = RetroBoard::from_template("went-well").unwrap();
+        let first = board.add_item("went-well", "First").unwrap();
+        let middle = board.add_item("went-well", "Middle").unwrap();
+        let last = board.add_item("went-well", "Last").unwrap();
+        board.upvote_item("went-well", &middle);
+
+        board.remove_item("went-well", &middle, "alice");
+        assert!(!board.lanes["went-well"].items.contains_key(&middle));
+        let (trash_id, trashed) = board.trash.first().unwrap();
+        let trash_id = trash_id.clone();
+        assert_eq!(trashed.deleted_by, "alice");
+        assert!(matches!(
+            &trashed.deleted,
+            Deleted::Item { item_id, item, .. } if *item_id == middle && item.vote_count == 1
+        ));
+
+        // It goes back to its old position among the items that are left
+        board.reorder_item("went-well", &last, 0);
+        board.restore(&trash_id);
+        assert!(board.trash.is_empty());
+        let lane = &board.lanes["went-well"];
+        let mut order: Vec<_> = lane.items.iter().collect();
+        order.sort_by_key(|(_, item)| item.sort_order);
+        let order: Vec<_> = order.into_iter().map(|(id, _)| id.clone()).collect();
+        assert_eq!(order, [last, middle.clone(), first]);
+        assert_eq!(lane.items[&middle].vote_count, 1);
+    }
+
+    #[test]
+    fn test_removed_lane_is_restored_in_place() {
+        let mut board = RetroBoard::from_template("went-well").unwrap();
+        let item = board.add_item("to-improve", "Flaky tests").unwrap();
+        board.remove_item("to-improve", &item, "alice");
+        board.remove_lane("to-improve", "bob");
+        assert_eq!(board.lanes.len(), 2);
+        assert_eq!(board.trash.len(), 2);
+
+        // The item needs its lane back first
+        let item_trash_id = board.trash.keys().next().unwrap().clone();
+        board.restore(&item_trash_id);
+        assert_eq!(board.trash.len(), 2);
+
+        let lane_trash_id = board.trash.keys().nth(1).unwrap().clone();
+        board.restore(&lane_trash_id);
+        let ids: Vec<_> = board.lanes.keys().map(String::as_str).collect();
+        assert_eq!(ids, ["went-well", "to-improve", "action-items"]);
+        board.restore(&item_trash_id);
+        assert!(board.lanes["to-improve"].items.contains_key(&item));
+        assert!(board.trash.is_empty());
+    }
+
+    #[test]
+    fn test_groups() {
+        let mut board = RetroBoard::from_template("went-well").unwrap();
+        let a = board.add_item("went-well", "Deploys").unwrap();
+        let b = board.add_item("went-well", "Releases").unwrap();
+        let shipping = board.add_group("went-well", "Shiping").unwrap();
+        let people = board.add_group("went-well", "People").unwrap();
+        board.rename_group("went-well", &shipping, "Shipping");
+        board.group_item("went-well", &a, Some(&shipping));
+        board.group_item("went-well", &b, Some(&shipping));
+        board.group_item("went-well", &b, Some("missing"));
+        board.upvote_item("went-well", &a);
+        board.upvote_group("went-well", &shipping);
+
+        let lane = &board.lanes["went-well"];
+        assert_eq!(lane.groups[&shipping].title, "Shipping");
+        assert_eq!(lane.items[&b].group.as_deref(), Some(shipping.as_str()));
+        assert_eq!(lane.group_votes(&shipping), 2);
+        assert_eq!(lane.group_votes(&people), 0);
+
+        board.reorder_group("went-well", &people, 0);
+        let order: Vec<_> = board.lanes["went-well"].groups.keys().cloned().collect();
+        assert_eq!(order, [people.clone(), shipping.clone()]);
+
+        // Taking an item out, moving it away or removing its group ungroups it
+        board.group_item("went-well", &a, None);
+        assert_eq!(board.lanes["went-well"].items[&a].group, None);
+        board.move_item("went-well", "to-improve", &b);
+        assert_eq!(board.lanes["to-improve"].items[&b].group, None);
+        board.group_item("to-improve", &b, Some(&shipping));
+        assert_eq!(board.lanes["to-improve"].items[&b].group, None);
+        board.group_item("went-well", &a, Some(&shipping));
+        board.remove_group("went-well", &shipping);
+        assert_eq!(board.lanes["went-well"].items[&a].group, None);
+        assert_eq!(board.lanes["went-well"].groups.len(), 1);
+    }
+
+    #[test]
+    fn test_purge_trash() {
+        let mut board = RetroBoard::from_template("went-well").unwrap();
+        board.remove_lane("went-well", "alice");
+        board.remove_lane("to-improve", "alice");
+        let old = board.trash.keys().next().unwrap().clone();
+        board.trash[&old].deleted_at = Utc::now() - chrono::Duration::days(40);
+
+        assert_eq!(
+            board.purge_trash(Some(Utc::now() - chrono::Duration::days(30))),
+            1
+        );
+        assert!(!board.trash.contains_key(&old));
+        assert_eq!(board.purge_trash(None), 1);
+        assert!(board.trash.is_empty());
     }
 
     #[test]
     fn test_upvote_item_in_lane() {
-        let mut board = RetroBoard {
-            title: "Test Board".to_string(),
-            lanes: HashMap::new(),
-        };
+        let mut board = RetroBoard::new("Test Board");
 
         board.add_lane("Test Lane");
         board.add_item("Test Lane", "Test Item");
@@ -577,10 +1892,7 @@ mod tests {
 
     #[test]
     fn test_upvote_item_in_nonexistent_lane() {
-        let mut board = RetroBoard {
-            title: "Test Board".to_string(),
-            lanes: HashMap::new(),
-        };
+        let mut board = RetroBoard::new("Test Board");
 
         // Should not panic — gracefully logs and returns
         board.upvote_item("Nonexistent Lane", "item_id");
@@ -588,10 +1900,7 @@ mod tests {
 
     #[test]
     fn test_upvote_nonexistent_item_in_lane() {
-        let mut board = RetroBoard {
-            title: "Test Board".to_string(),
-            lanes: HashMap::new(),
-        };
+        let mut board = RetroBoard::new("Test Board");
 
         board.add_lane("Test Lane");
 
@@ -601,10 +1910,7 @@ mod tests {
 
     #[test]
     fn test_move_item_between_lanes() {
-        let mut board = RetroBoard {
-            title: "Test Board".to_string(),
-            lanes: HashMap::new(),
-        };
+        let mut board = RetroBoard::new("Test Board");
 
         board.add_lane("Lane 1");
         board.add_lane("Lane 2");
@@ -625,10 +1931,7 @@ mod tests {
 
     #[test]
     fn test_move_item_same_lane() {
-        let mut board = RetroBoard {
-            title: "Test Board".to_string(),
-            lanes: HashMap::new(),
-        };
+        let mut board = RetroBoard::new("Test Board");
 
         board.add_lane("Lane 1");
         board.add_item("Lane 1", "Test Item");
@@ -646,10 +1949,7 @@ mod tests {
 
     #[test]
     fn test_move_item_from_nonexistent_lane() {
-        let mut board = RetroBoard {
-            title: "Test Board".to_string(),
-            lanes: HashMap::new(),
-        };
+        let mut board = RetroBoard::new("Test Board");
 
         board.add_lane("Lane 2");
 
@@ -662,10 +1962,7 @@ mod tests {
 
     #[test]
     fn test_move_item_to_nonexistent_lane() {
-        let mut board = RetroBoard {
-            title: "Test Board".to_string(),
-            lanes: HashMap::new(),
-        };
+        let mut board = RetroBoard::new("Test Board");
 
         board.add_lane("Lane 1");
         board.add_item("Lane 1", "Test Item");
@@ -683,10 +1980,7 @@ mod tests {
 
     #[test]
     fn test_reorder_item_within_lane() {
-        let mut board = RetroBoard {
-            title: "Test Board".to_string(),
-            lanes: HashMap::new(),
-        };
+        let mut board = RetroBoard::new("Test Board");
 
         board.add_lane("Lane 1");
 
@@ -721,10 +2015,7 @@ mod tests {
 
     #[test]
     fn test_reorder_item_in_nonexistent_lane() {
-        let mut board = RetroBoard {
-            title: "Test Board".to_string(),
-            lanes: HashMap::new(),
-        };
+        let mut board = RetroBoard::new("Test Board");
 
         // Try to reorder item in nonexistent lane - should do nothing
         board.reorder_item("Nonexistent Lane", "item_id", 0);
@@ -733,10 +2024,7 @@ mod tests {
 
     #[test]
     fn test_reorder_nonexistent_item() {
-        let mut board = RetroBoard {
-            title: "Test Board".to_string(),
-            lanes: HashMap::new(),
-        };
+        let mut board = RetroBoard::new("Test Board");
 
         board.add_lane("Lane 1");
         board.add_item("Lane 1", "Test Item");
@@ -750,10 +2038,7 @@ mod tests {
 
     #[test]
     fn test_reorder_item_beyond_bounds() {
-        let mut board = RetroBoard {
-            title: "Test Board".to_string(),
-            lanes: HashMap::new(),
-        };
+        let mut board = RetroBoard::new("Test Board");
 
         board.add_lane("Lane 1");
         board.add_item("Lane 1", "First item");
@@ -795,24 +2080,36 @@ mod tests {
         assert_eq!(action_items.items.len(), 2);
     }
 
+    #[test]
+    fn test_from_template() {
+        for name in TEMPLATES {
+            let board = RetroBoard::from_template(name).unwrap();
+            assert!(board.lanes.contains_key("action-items"), "{}", name);
+        }
+
+        let board = RetroBoard::from_template("start-stop-continue").unwrap();
+        assert_eq!(board.lanes.len(), 4);
+        assert_eq!(board.lanes.get("stop").unwrap().title, "Stop");
+        assert!(board.lanes.values().all(|lane| lane.items.is_empty()));
+
+        assert!(RetroBoard::from_template("unknown").is_none());
+    }
+
     #[test]
     fn test_save_and_load_from_file() {
         let dir = tempdir().unwrap();
         let file_path = dir.path().join("test_board.json");
         let file_path_str = file_path.to_str().unwrap();
 
-        let mut board = RetroBoard {
-            title: "Test Board".to_string(),
-            lanes: HashMap::new(),
-        };
+        let mut board = RetroBoard::new("Test Board");
         board.add_lane("Test Lane");
         board.add_item("Test Lane", "Test Item");
 
         // Test save
-        board.save_to_file(file_path_str);
+        board.save_to_file(file_path_str).unwrap();
 
         // Test load
-        let loaded_board = RetroBoard::load_from_file(file_path_str);
+        let loaded_board = RetroBoard::load_from_file(file_path_str).unwrap();
         assert_eq!(loaded_board.title, "Test Board");
         assert_eq!(loaded_board.lanes.len(), 1);
         assert!(loaded_board.lanes.contains_key("Test Lane"));
@@ -824,9 +2121,68 @@ mod tests {
         assert_eq!(item.body, "Test Item");
     }
 
+    #[test]
+    fn test_lane_order_survives_save_and_load() {
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("board.json");
+
+        let board = RetroBoard::from_template("mad-sad-glad").unwrap();
+        board.save_to_file(&file_path).unwrap();
+
+        let loaded = RetroBoard::load_from_file(&file_path).unwrap();
+        let ids: Vec<_> = loaded.lanes.keys().map(String::as_str).collect();
+        assert_eq!(ids, ["mad", "sad", "glad", "action-items"]);
+    }
+
+    #[test]
+    fn test_save_replaces_file_without_leftovers() {
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("board.json");
+
+        RetroBoard::new("First").save_to_file(&file_path).unwrap();
+        RetroBoard::new("Second").save_to_file(&file_path).unwrap();
+
+        assert_eq!(
+            RetroBoard::load_from_file(&file_path).unwrap().title,
+            "Second"
+        );
+        let files: Vec<_> = std::fs::read_dir(dir.path()).unwrap().collect();
+        assert_eq!(files.len(), 1);
+    }
+
+    #[test]
+    fn test_save_writes_schema_version() {
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("versioned.json");
+        let file_path_str = file_path.to_str().unwrap();
+
+        RetroBoard::default().save_to_file(file_path_str).unwrap();
+
+        let saved: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(file_path_str).unwrap()).unwrap();
+        assert_eq!(saved["schema_version"], schema::CURRENT_VERSION);
+    }
+
+    #[test]
+    fn test_load_unversioned_file() {
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("unversioned.json");
+        let file_path_str = file_path.to_str().unwrap();
+
+        std::fs::write(
+            file_path_str,
+            r#"{"title": "Old Board", "lanes": {"l": {"title": "L", "theme": "went-well", "items": {}}}}"#,
+        )
+        .unwrap();
+
+        let board = RetroBoard::load_from_file(file_path_str).unwrap();
+        assert_eq!(board.title, "Old Board");
+        assert!(board.lanes.contains_key("l"));
+    }
+
     #[test]
     fn test_load_from_nonexistent_file() {
-        let board = RetroBoard::load_from_file("/nonexistent/path.json");
+        let board = RetroBoard::load_from_file("/nonexistent/path.json").unwrap();
 
         // Should return default board when file doesn't exist
         assert_eq!(board.title, "My Retro Board");
@@ -842,19 +2198,30 @@ mod tests {
         // Create a file with invalid JSON
         std::fs::write(file_path_str, "invalid json content").unwrap();
 
-        let board = RetroBoard::load_from_file(file_path_str);
+        // A broken file is an error rather than a default board saved over it
+        assert!(RetroBoard::load_from_file(file_path_str).is_err());
+    }
 
-        // Should return default board when JSON is invalid
-        assert_eq!(board.title, "My Retro Board");
-        assert_eq!(board.lanes.len(), 3);
+    #[test]
+    fn test_load_from_newer_version() {
+        let dir = tempdir().unwrap();
+        let file_path = dir.path().join("newer.json");
+        let newer = format!(
+            r#"{{"schema_version": {}, "title": "From the future"}}"#,
+            schema::CURRENT_VERSION + 1
+        );
+        std::fs::write(&file_path, &newer).unwrap();
+
+        let Err(error) = RetroBoard::load_from_file(&file_path) else {
+            panic!("a newer board must not load");
+        };
+        assert!(error.contains("newer than the supported version"));
+        assert_eq!(std::fs::read_to_string(&file_path).unwrap(), newer);
     }
 
     #[test]
     fn test_edit_item() {
-        let mut board = RetroBoard {
-            title: "Test Board".to_string(),
-            lanes: HashMap::new(),
-        };
+        let mut board = RetroBoard::new("Test Board");
 
         board.add_lane("Test Lane");
         board.add_item("Test Lane", "Original body");
@@ -869,12 +2236,118 @@ mod tests {
         assert_eq!(item.body, "Updated body");
     }
 
+    #[test]
+    fn test_anonymous_boards_send_no_participant_ids() {
+        let mut board = RetroBoard::from_template("went-well").unwrap();
+        let participants = ["p-alice", "p-bob", "p-carol", "p-dave", "p-erin", "p-frank"];
+        let [alice, bob, carol, dave, erin, frank] = participants;
+        let kept = board.add_item("went-well", "Deploys").unwrap();
+        let merged = board.add_item("went-well", "Releases").unwrap();
+        let trashed = board.add_item("to-improve", "Standups").unwrap();
+        for id in [&kept, &merged] {
+            board.set_author("went-well", id, alice);
+            board.add_comment("went-well", id, bob, "Agreed", None);
+            board.toggle_reaction("went-well", id, "🎉", carol);
+        }
+        board.merge_items("went-well", &merged, &kept, "Deploys and releases");
+        board.set_author("to-improve", &trashed, frank);
+        board.add_comment("to-improve", &trashed, bob, "Too long", None);
+        board.toggle_reaction("to-improve", &trashed, "👀", carol);
+        board.count_vote(dave);
+        board.remove_item("to-improve", &trashed, erin);
+
+        for output in [board.client_snapshot(), schema::to_export_json(&board)] {
+            for participant in participants {
+                assert!(!output.contains(participant), "{} leaked", participant);
+            }
+        }
+        // Counts survive without the IDs
+        let snapshot: serde_json::Value = serde_json::from_str(&board.client_snapshot()).unwrap();
+        let item = &snapshot["lanes"]["went-well"]["items"][&kept];
+        assert_eq!(item["reactions"]["🎉"].as_array().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_participants_stay_hidden_once_anonymity_is_off() {
+        let mut board = RetroBoard::from_template("went-well").unwrap();
+        let item = board.add_item("went-well", "Deploys").unwrap();
+        let trashed = board.add_item("to-improve", "Standups").unwrap();
+        let hidden = ["p-alice", "p-bob", "p-carol", "p-dave"];
+        let shown = ["p-erin", "p-frank", "p-gina", "p-henry"];
+        for (round, [commenter, reactor, voter, deleter]) in [hidden, shown].into_iter().enumerate()
+        {
+            board.add_comment("went-well", &item, commenter, "Agreed", None);
+            board.toggle_reaction("went-well", &item, "🎉", reactor);
+            board.count_vote(voter);
+            if round == 0 {
+                board.remove_item("to-improve", &trashed, deleter);
+                board.settings.anonymous = false;
+            } else {
+                let id = board.add_item("to-improve", "Retros").unwrap();
+                board.remove_item("to-improve", &id, deleter);
+            }
+        }
+
+        for output in [board.client_snapshot(), schema::to_export_json(&board)] {
+            for participant in hidden {
+                assert!(!output.contains(participant), "{} leaked", participant);
+            }
+            for participant in shown {
+                assert!(output.contains(participant), "{} missing", participant);
+            }
+        }
+        // Taking back a reaction made while anonymous and reacting again shows it
+        board.toggle_reaction("went-well", &item, "🎉", "p-bob");
+        board.toggle_reaction("went-well", &item, "🎉", "p-bob");
+        assert!(board.client_snapshot().contains("p-bob"));
+    }
+
+    #[test]
+    fn test_edits_keep_revisions_and_timestamps() {
+        let mut board = RetroBoard::from_template("went-well").unwrap();
+        let id = board.add_item("went-well", "Version 0").unwrap();
+        let item = &board.lanes["went-well"].items[&id];
+        assert!(item.created_at.is_some());
+        assert_eq!(item.updated_at, item.created_at);
+        assert_eq!(board.lanes["went-well"].updated_at, item.created_at);
+        let created = item.created_at;
+
+        for version in 1..=MAX_REVISIONS + 2 {
+            board.edit_item("went-well", &id, &format!("Version {}", version));
+        }
+        // Saving the same body is not an edit
+        board.edit_item("went-well", &id, &format!("Version {}", MAX_REVISIONS + 2));
+        let item = &board.lanes["went-well"].items[&id];
+        assert_eq!(item.created_at, created);
+        assert!(item.updated_at > created);
+        assert_eq!(board.lanes["went-well"].updated_at, item.updated_at);
+        // Only the latest revisions are kept, oldest first
+        assert_eq!(item.revisions.len(), MAX_REVISIONS);
+        assert_eq!(item.revisions[0].body, "Version 2");
+        assert_eq!(
+            item.revisions[MAX_REVISIONS - 1].body,
+            format!("Version {}", MAX_REVISIONS + 1)
+        );
+
+        board.roll_back_item("went-well", &id, 0);
+        let item = &board.lanes["went-well"].items[&id];
+        assert_eq!(item.body, "Version 2");
+        assert_eq!(
+            item.revisions.last().unwrap().body,
+            format!("Version {}", MAX_REVISIONS + 2)
+        );
+        board.roll_back_item("went-well", &id, MAX_REVISIONS);
+        assert_eq!(board.lanes["went-well"].items[&id].body, "Version 2");
+
+        // Moving an item changes both lanes
+        let before = board.lanes["to-improve"].updated_at;
+        board.move_item("went-well", "to-improve", &id);
+        assert!(board.lanes["to-improve"].updated_at > before);
+    }
+
     #[test]
     fn test_edit_item_nonexistent_lane() {
-        let mut board = RetroBoard {
-            title: "Test Board".to_string(),
-            lanes: HashMap::new(),
-        };
+        let mut board = RetroBoard::new("Test Board");
 
         // Should not panic, just log error
         board.edit_item("Nonexistent", "id", "body");
@@ -882,10 +2355,7 @@ mod tests {
 
     #[test]
     fn test_edit_item_nonexistent_item() {
-        let mut board = RetroBoard {
-            title: "Test Board".to_string(),
-            lanes: HashMap::new(),
-        };
+        let mut board = RetroBoard::new("Test Board");
 
         board.add_lane("Test Lane");
 
@@ -895,10 +2365,7 @@ mod tests {
 
     #[test]
     fn test_merge_items() {
-        let mut board = RetroBoard {
-            title: "Test Board".to_string(),
-            lanes: HashMap::new(),
-        };
+        let mut board = RetroBoard::new("Test Board");
 
         board.add_lane("Test Lane");
         board.add_item("Test Lane", "First item");
@@ -910,6 +2377,9 @@ mod tests {
         let source_id = ids[0].clone();
         let target_id = ids[1].clone();
 
+        let source_body = lane.items[&source_id].body.clone();
+        let target_body = lane.items[&target_id].body.clone();
+
         // Upvote the source to verify vote transfer
         board.upvote_item("Test Lane", &source_id);
         board.upvote_item("Test Lane", &source_id);
@@ -925,14 +2395,134 @@ mod tests {
         let target = lane.items.get(&target_id).unwrap();
         assert_eq!(target.body, "Merged body");
         assert_eq!(target.vote_count, 3); // 2 from source + 1 from target
+        assert_eq!(target.merged.len(), 1);
+        assert_eq!(target.merged[0].id, source_id);
+        assert_eq!(target.merged[0].item.body, source_body);
+        assert_eq!(target.merged[0].item.vote_count, 2);
+        assert_eq!(target.merged[0].target_body, target_body);
     }
 
     #[test]
-    fn test_merge_items_nonexistent_lane() {
-        let mut board = RetroBoard {
-            title: "Test Board".to_string(),
-            lanes: HashMap::new(),
+    fn test_unmerge_items() {
+        let mut board = RetroBoard::from_template("went-well").unwrap();
+        let a = board.add_item("went-well", "Deploys").unwrap();
+        let b = board.add_item("went-well", "Releases").unwrap();
+        let c = board.add_item("went-well", "Rollbacks").unwrap();
+        let d = board.add_item("went-well", "Unrelated").unwrap();
+        board.upvote_item("went-well", &b);
+        board.upvote_item("went-well", &c);
+        board.merge_items("went-well", &b, &a, "Deploys and releases");
+        board.merge_items("went-well", &c, &a, "Shipping");
+        // Votes cast on the merged card stay with it
+        board.upvote_item("went-well", &a);
+        assert_eq!(board.lanes["went-well"].items[&a].vote_count, 3);
+
+        board.unmerge_items("went-well", &a);
+        let lane = &board.lanes["went-well"];
+        let mut order: Vec<_> = lane.items.iter().collect();
+        order.sort_by_key(|(_, item)| item.sort_order);
+        let order: Vec<_> = order
+            .into_iter()
+            .map(|(id, item)| (id.clone(), item.body.as_str(), item.vote_count))
+            .collect();
+        assert_eq!(
+            order,
+            [
+                (a.clone(), "Deploys", 1),
+                (b, "Releases", 1),
+                (c, "Rollbacks", 1),
+                (d, "Unrelated", 0),
+            ]
+        );
+        assert!(lane.items[&a].merged.is_empty());
+
+        // Nothing left to unmerge
+        board.unmerge_items("went-well", &a);
+        assert_eq!(board.lanes["went-well"].items.len(), 4);
+    }
+
+    #[test]
+    fn test_comment_thread() {
+        let mut board = RetroBoard::from_template("went-well").unwrap();
+        let item = board.add_item("went-well", "Deploys").unwrap();
+        let first = board
+            .add_comment("went-well", &item, "alice", "Which ones?", None)
+            .unwrap();
+        let reply = board
+            .add_comment("went-well", &item, "bob", "All of them", Some(&first))
+            .unwrap();
+        let answer = board
+            .add_comment("went-well", &item, "alice", "Nice", Some(&reply))
+            .unwrap();
+        // Replies stay on the item they answer
+        assert!(board
+            .add_comment("went-well", &item, "bob", "Lost", Some("missing"))
+            .is_none());
+
+        board.edit_comment("went-well", &item, &reply, "All of them!");
+        let comment = board.comment("went-well", &item, &reply).unwrap();
+        assert_eq!(comment.body, "All of them!");
+        assert!(comment.edited_at.is_some());
+
+        board.delete_comment("went-well", &item, &reply);
+        let comments = &board.lanes["went-well"].items[&item].comments;
+        assert_eq!(comments.keys().collect::<Vec<_>>(), [&first, &answer]);
+        assert_eq!(comments[&answer].reply_to.as_ref(), Some(&first));
+
+        // Comments go along when the item moves
+        board.move_item("went-well", "to-improve", &item);
+        assert_eq!(board.lanes["to-improve"].items[&item].comments.len(), 2);
+    }
+
+    #[test]
+    fn test_toggle_reaction() {
+        let mut board = RetroBoard::from_template("went-well").unwrap();
+        let item = board.add_item("went-well", "Deploys").unwrap();
+        board.toggle_reaction("went-well", &item, "👍", "alice");
+        board.toggle_reaction("went-well", &item, "👍", "bob");
+        board.toggle_reaction("went-well", &item, "🎉", "alice");
+        let reactions = &board.lanes["went-well"].items[&item].reactions;
+        assert_eq!(reactions["👍"], ["alice", "bob"]);
+        assert_eq!(reactions["🎉"], ["alice"]);
+
+        board.toggle_reaction("went-well", &item, "👍", "alice");
+        board.toggle_reaction("went-well", &item, "🎉", "alice");
+        let item = &board.lanes["went-well"].items[&item];
+        assert_eq!(item.reactions.keys().collect::<Vec<_>>(), ["👍"]);
+        assert_eq!(item.reactions["👍"], ["bob"]);
+        assert_eq!(item.vote_count, 0);
+    }
+
+    #[test]
+    fn test_comments_follow_merges() {
+        let mut board = RetroBoard::from_template("went-well").unwrap();
+        let a = board.add_item("went-well", "Deploys").unwrap();
+        let b = board.add_item("went-well", "Releases").unwrap();
+        board.add_comment("went-well", &a, "alice", "On a", None);
+        let on_b = board
+            .add_comment("went-well", &b, "bob", "On b", None)
+            .unwrap();
+        board.merge_items("went-well", &b, &a, "Shipping");
+        assert_eq!(board.lanes["went-well"].items[&a].comments.len(), 2);
+
+        board.edit_comment("went-well", &a, &on_b, "On b, edited");
+        board.add_comment("went-well", &a, "carol", "On the merge", None);
+        board.unmerge_items("went-well", &a);
+        let lane = &board.lanes["went-well"];
+        let bodies = |id: &str| -> Vec<String> {
+            lane.items[id]
+                .comments
+                .values()
+                .map(|comment| comment.body.clone())
+                .collect()
         };
+        assert_eq!(bodies(&a), ["On a", "On the merge"]);
+        assert_eq!(bodies(&b), ["On b, edited"]);
+    }
+
+    #[test]
+    fn test_merge_items_nonexistent_lane() {
+        let mut board = RetroBoard::new("Test Board");
 
         // Should not panic
         board.merge_items("Nonexistent", "s", "t", "body");
@@ -940,10 +2530,7 @@ mod tests {
 
     #[test]
     fn test_merge_items_nonexistent_target() {
-        let mut board = RetroBoard {
-            title: "Test Board".to_string(),
-            lanes: HashMap::new(),
-        };
+        let mut board = RetroBoard::new("Test Board");
 
         board.add_lane("Test Lane");
         board.add_item("Test Lane", "Item");
@@ -960,10 +2547,7 @@ mod tests {
 
     #[test]
     fn test_merge_items_same_id() {
-        let mut board = RetroBoard {
-            title: "Test Board".to_string(),
-            lanes: HashMap::new(),
-        };
+        let mut board = RetroBoard::new("Test Board");
 
         board.add_lane("Test Lane");
         board.add_item("Test Lane", "Item");
@@ -980,10 +2564,7 @@ mod tests {
 
     #[test]
     fn test_move_item_no_duplicate_sort_order_after_removal() {
-        let mut board = RetroBoard {
-            title: "Test Board".to_string(),
-            lanes: HashMap::new(),
-        };
+        let mut board = RetroBoard::new("Test Board");
 
         board.add_lane("Lane 1");
         board.add_lane("Lane 2");
@@ -1001,7 +2582,7 @@ mod tests {
             .find(|(_, item)| item.sort_order == 1)
             .map(|(id, _)| id.clone())
             .unwrap();
-        board.remove_item("Lane 2", &middle_id);
+        board.remove_item("Lane 2", &middle_id, "tester");
 
         // Lane 2 now has 2 items with sort_orders [0, 2]; len() == 2
         // Moving an item in should NOT assign sort_order 2 (duplicate)
@@ -1025,10 +2606,7 @@ mod tests {
 
     #[test]
     fn test_add_item_no_duplicate_sort_order_after_removal() {
-        let mut board = RetroBoard {
-            title: "Test Board".to_string(),
-            lanes: HashMap::new(),
-        };
+        let mut board = RetroBoard::new("Test Board");
 
         board.add_lane("Lane 1");
 
@@ -1045,7 +2623,7 @@ mod tests {
             .find(|(_, item)| item.sort_order == 1)
             .map(|(id, _)| id.clone())
             .unwrap();
-        board.remove_item("Lane 1", &middle_id);
+        board.remove_item("Lane 1", &middle_id, "tester");
 
         // Adding a new item should NOT assign sort_order 2
         board.add_item("Lane 1", "Item D");
@@ -0,0 +1,520 @@
+//! Versioning for the stored board format.
+//!
+//! Every stored board carries a `schema_version`; files written before the
+//! field existed count as version 0. Loading runs the migrations between the
+//! stored version and `CURRENT_VERSION` before deserializing, so changing the
+//! stored structs means bumping `CURRENT_VERSION`, appending a migration and
+//! adding a fixture for the new version under `fixtures/schema/`.
+
+use crate::history::History;
+use crate::retroboard::{ActionDetails, BoardSettings, RetroBoard, ACTION_ITEMS_THEME};
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::fmt;
+
+pub const CURRENT_VERSION: u64 = 15;
+
+type Migration = fn(&mut Map<String, Value>);
+
+/// `MIGRATIONS[n]` upgrades a version `n` document to version `n + 1`.
+const MIGRATIONS: [Migration; CURRENT_VERSION as usize] = [
+    v0_to_v1, v1_to_v2, v2_to_v3, v3_to_v4, v4_to_v5, v5_to_v6, v6_to_v7, v7_to_v8, v8_to_v9,
+    v9_to_v10, v10_to_v11, v11_to_v12, v12_to_v13, v13_to_v14, v14_to_v15,
+];
+
+#[derive(Debug)]
+pub enum SchemaError {
+    Json(serde_json::Error),
+    NotAnObject,
+    InvalidVersion(Value),
+    UnsupportedVersion(u64),
+}
+
+impl fmt::Display for SchemaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SchemaError::Json(e) => write!(f, "invalid board JSON: {}", e),
+            SchemaError::NotAnObject => write!(f, "stored board is not a JSON object"),
+            SchemaError::InvalidVersion(v) => write!(f, "invalid schema_version: {}", v),
+            SchemaError::UnsupportedVersion(v) => write!(
+                f,
+                "schema_version {} is newer than the supported version {}",
+                v, CURRENT_VERSION
+            ),
+        }
+    }
+}
+
+impl From<serde_json::Error> for SchemaError {
+    fn from(e: serde_json::Error) -> Self {
+        SchemaError::Json(e)
+    }
+}
+
+/// A board as it is written to storage, tagged with the current version.
+#[derive(Serialize)]
+pub struct Versioned<'a> {
+    schema_version: u64,
+    #[serde(flatten)]
+    board: &'a RetroBoard,
+}
+
+impl<'a> Versioned<'a> {
+    pub fn new(board: &'a RetroBoard) -> Self {
+        Self {
+            schema_version: CURRENT_VERSION,
+            board,
+        }
+    }
+}
+
+pub fn to_json(board: &RetroBoard) -> String {
+    serde_json::to_string(&Versioned::new(board)).unwrap()
+}
+
+/// A board as it is exported: versioned like a stored one, but with an
+/// empty history and without the authors kept from participants.
+pub fn to_export_json(board: &RetroBoard) -> String {
+    let mut value = board.public_value();
+    value["schema_version"] = CURRENT_VERSION.into();
+    value["history"] = serde_json::to_value(History::default()).unwrap();
+    value.to_string()
+}
+
+pub fn from_json(json: &str) -> Result<RetroBoard, SchemaError> {
+    from_value(serde_json::from_str(json)?)
+}
+
+/// Upgrade a stored board of any known version and deserialize it.
+pub fn from_value(value: Value) -> Result<RetroBoard, SchemaError> {
+    let Value::Object(mut doc) = value else {
+        return Err(SchemaError::NotAnObject);
+    };
+
+    let version = match doc.get("schema_version") {
+        None => 0,
+        Some(v) => v
+            .as_u64()
+            .ok_or_else(|| SchemaError::InvalidVersion(v.clone()))?,
+    };
+    if version > CURRENT_VERSION {
+        return Err(SchemaError::UnsupportedVersion(version));
+    }
+
+    for (from, migration) in MIGRATIONS.iter().enumerate().skip(version as usize) {
+        tracing::info!("Migrating board from schema {} to {}", from, from + 1);
+        migration(&mut doc);
+        doc.insert("schema_version".to_string(), Value::from(from as u64 + 1));
+    }
+
+    Ok(serde_json::from_value(Value::Object(doc))?)
+}
+
+/// Version 1 introduced `schema_version` itself; the board layout is unchanged.
+fn v0_to_v1(_doc: &mut Map<String, Value>) {}
+
+/// Version 2 tracks how many upvotes each participant has cast.
+fn v1_to_v2(doc: &mut Map<String, Value>) {
+    doc.entry("votes_cast")
+        .or_insert_with(|| Value::Object(Map::new()));
+}
+
+/// Version 3 records when a board was created. Older boards leave it unset.
+fn v2_to_v3(doc: &mut Map<String, Value>) {
+    doc.entry("created_at").or_insert(Value::Null);
+}
+
+/// Version 4 keeps recent actions for undo and redo, starting with none.
+fn v3_to_v4(doc: &mut Map<String, Value>) {
+    doc.entry("history")
+        .or_insert_with(|| serde_json::to_value(History::default()).unwrap());
+}
+
+/// Version 5 keeps deleted items and lanes in a trash, starting empty.
+fn v4_to_v5(doc: &mut Map<String, Value>) {
+    doc.entry("trash")
+        .or_insert_with(|| Value::Object(Map::new()));
+}
+
+/// Version 6 keeps the items merged into each item. Older items have none,
+/// which `#[serde(default)]` fills in wherever they are stored.
+fn v5_to_v6(_doc: &mut Map<String, Value>) {}
+
+/// Version 7 adds per-board settings, and groups of items within lanes.
+/// Lanes and items without groups are covered by `#[serde(default)]`.
+fn v6_to_v7(doc: &mut Map<String, Value>) {
+    doc.entry("settings")
+        .or_insert_with(|| serde_json::to_value(BoardSettings::default()).unwrap());
+}
+
+/// Version 8 adds comments on items. Items without any are covered by
+/// `#[serde(default)]`.
+fn v7_to_v8(_doc: &mut Map<String, Value>) {}
+
+/// Version 9 adds reactions to items, and the emoji offered to the board's
+/// settings. Both are covered by `#[serde(default)]`, so older boards offer
+/// the default reactions.
+fn v8_to_v9(_doc: &mut Map<String, Value>) {}
+
+/// Version 10 gives the items of action items lanes an assignee, due date
+/// and status, starting open and unassigned. Lanes and items in the trash
+/// are included.
+fn v9_to_v10(doc: &mut Map<String, Value>) {
+    let open = serde_json::to_value(ActionDetails::default()).unwrap();
+    let is_action_lane = |lane: &Value| lane["theme"] == ACTION_ITEMS_THEME;
+    let make_action_item = |item: &mut Value| {
+        if let Some(item) = item.as_object_mut() {
+            item.entry("action").or_insert_with(|| open.clone());
+        }
+    };
+
+    let mut action_lane_ids = Vec::new();
+    if let Some(lanes) = doc.get_mut("lanes").and_then(Value::as_object_mut) {
+        for (lane_id, lane) in lanes.iter_mut().filter(|(_, lane)| is_action_lane(lane)) {
+            action_lane_ids.push(lane_id.clone());
+            if let Some(items) = lane["items"].as_object_mut() {
+                items.values_mut().for_each(make_action_item);
+            }
+        }
+    }
+    if let Some(trash) = doc.get_mut("trash").and_then(Value::as_object_mut) {
+        for trashed in trash.values_mut() {
+            if trashed["kind"] == "Lane" && is_action_lane(&trashed["lane"]) {
+                if let Some(items) = trashed["lane"]["items"].as_object_mut() {
+                    items.values_mut().for_each(make_action_item);
+                }
+            } else if trashed["kind"] == "Item"
+                && trashed["lane_id"]
+                    .as_str()
+                    .is_some_and(|lane_id| action_lane_ids.iter().any(|id| id == lane_id))
+            {
+                make_action_item(&mut trashed["item"]);
+            }
+        }
+    }
+}
+
+/// Version 11 links action items carried over from an earlier board to
+/// where they came from. Older action items have no such link, which
+/// `#[serde(default)]` covers.
+fn v10_to_v11(_doc: &mut Map<String, Value>) {}
+
+/// Version 12 adds the team holding the retro to the board's settings.
+/// Older boards have none, which `#[serde(default)]` covers.
+fn v11_to_v12(_doc: &mut Map<String, Value>) {}
+
+/// Version 13 records when items and lanes were created and last changed,
+/// and the earlier bodies of edited items. Older ones have no timestamps
+/// or revisions, which `#[serde(default)]` covers.
+fn v12_to_v13(_doc: &mut Map<String, Value>) {}
+
+/// Version 14 records who added each item, and lets boards show authors.
+/// Older items have no author, and older boards stay anonymous, which
+/// `#[serde(default)]` covers.
+fn v13_to_v14(_doc: &mut Map<String, Value>) {}
+
+/// Version 15 records whether each comment, reaction, vote and deletion was
+/// made while the board was anonymous. Older ones count as made while it
+/// was not, which `#[serde(default)]` covers.
+fn v14_to_v15(_doc: &mut Map<String, Value>) {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::retroboard::{ActionStatus, Deleted, DEFAULT_REACTIONS};
+    use chrono::NaiveDate;
+
+    fn fixture(version: u64) -> String {
+        let path = format!(
+            "{}/fixtures/schema/v{}.json",
+            env!("CARGO_MANIFEST_DIR"),
+            version
+        );
+        std::fs::read_to_string(&path).unwrap_or_else(|e| panic!("{}: {}", path, e))
+    }
+
+    #[test]
+    fn test_every_version_has_a_loadable_fixture() {
+        for version in 0..=CURRENT_VERSION {
+            let board = from_json(&fixture(version))
+                .unwrap_or_else(|e| panic!("fixture v{} failed to load: {}", version, e));
+            assert_eq!(board.title, "Sprint 42 Retro", "fixture v{}", version);
+        }
+    }
+
+    #[test]
+    fn test_load_v0_fixture() {
+        let board = from_json(&fixture(0)).unwrap();
+        assert_eq!(board.lanes.len(), 2);
+
+        let lane = board.lanes.get("went-well").unwrap();
+        assert_eq!(lane.title, "Went Well");
+        assert_eq!(lane.theme, "went-well");
+        let item = lane.items.get("a1").unwrap();
+        assert_eq!(item.body, "Deploys were smooth");
+        assert_eq!(item.vote_count, 3);
+        assert_eq!(item.sort_order, 0);
+    }
+
+    #[test]
+    fn test_load_v1_fixture() {
+        let board = from_json(&fixture(1)).unwrap();
+        let lane = board.lanes.get("to-improve").unwrap();
+        let item = lane.items.get("b1").unwrap();
+        assert_eq!(item.body, "Flaky integration tests");
+        assert_eq!(item.vote_count, 5);
+    }
+
+    #[test]
+    fn test_load_v2_fixture() {
+        let board = from_json(&fixture(2)).unwrap();
+        assert_eq!(board.votes_cast.get("alice"), Some(&3));
+
+        // Older files start with no votes cast
+        let board = from_json(&fixture(1)).unwrap();
+        assert!(board.votes_cast.is_empty());
+    }
+
+    #[test]
+    fn test_load_v3_fixture() {
+        let board = from_json(&fixture(3)).unwrap();
+        assert_eq!(
+            board.created_at.unwrap().to_rfc3339(),
+            "2026-03-06T09:30:00+00:00"
+        );
+
+        // Older files don't know when the board was created
+        let board = from_json(&fixture(2)).unwrap();
+        assert!(board.created_at.is_none());
+    }
+
+    #[test]
+    fn test_load_v4_fixture() {
+        let board = from_json(&fixture(4)).unwrap();
+        let entries: Vec<_> = board.history.entries().collect();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].participant, "alice");
+        assert_eq!(entries[0].action, "AddItem");
+
+        // Older files start with nothing to undo
+        let board = from_json(&fixture(3)).unwrap();
+        assert_eq!(board.history.entries().count(), 0);
+    }
+
+    #[test]
+    fn test_load_v5_fixture() {
+        let board = from_json(&fixture(5)).unwrap();
+        let trashed = &board.trash["t1"];
+        assert_eq!(trashed.deleted_by, "bob");
+        assert!(matches!(
+            &trashed.deleted,
+            Deleted::Item { lane_id, item, .. } if lane_id == "to-improve" && item.vote_count == 2
+        ));
+
+        // Older files start with an empty trash
+        let board = from_json(&fixture(4)).unwrap();
+        assert!(board.trash.is_empty());
+    }
+
+    #[test]
+    fn test_load_v6_fixture() {
+        let board = from_json(&fixture(6)).unwrap();
+        let item = &board.lanes["went-well"].items["a1"];
+        assert_eq!(item.merged.len(), 1);
+        assert_eq!(item.merged[0].id, "a3");
+        assert_eq!(item.merged[0].item.body, "Fast rollbacks");
+        assert_eq!(item.merged[0].target_body, "Deploys");
+
+        // Older files have no merged items
+        let board = from_json(&fixture(5)).unwrap();
+        assert!(board.lanes["went-well"].items["a1"].merged.is_empty());
+    }
+
+    #[test]
+    fn test_load_v7_fixture() {
+        let board = from_json(&fixture(7)).unwrap();
+        assert!(board.settings.vote_on_groups);
+        let lane = &board.lanes["went-well"];
+        assert_eq!(lane.groups["g1"].title, "Shipping");
+        assert_eq!(lane.items["a2"].group.as_deref(), Some("g1"));
+        assert_eq!(lane.group_votes("g1"), 3);
+
+        // Older files have default settings and no groups
+        let board = from_json(&fixture(6)).unwrap();
+        assert!(!board.settings.vote_on_groups);
+        assert!(board.lanes["went-well"].groups.is_empty());
+    }
+
+    #[test]
+    fn test_load_v8_fixture() {
+        let board = from_json(&fixture(8)).unwrap();
+        let comments = &board.lanes["to-improve"].items["b1"].comments;
+        assert_eq!(comments.keys().collect::<Vec<_>>(), ["c1", "c2"]);
+        assert_eq!(comments["c1"].author, "alice");
+        assert_eq!(comments["c2"].reply_to.as_deref(), Some("c1"));
+        assert!(comments["c2"].edited_at.is_some());
+
+        // Older files have no comments
+        let board = from_json(&fixture(7)).unwrap();
+        assert!(board.lanes["to-improve"].items["b1"].comments.is_empty());
+    }
+
+    #[test]
+    fn test_load_v9_fixture() {
+        let board = from_json(&fixture(9)).unwrap();
+        assert_eq!(board.settings.reactions, ["🎉", "👀", "🚀"]);
+        let reactions = &board.lanes["went-well"].items["a2"].reactions;
+        assert_eq!(reactions["🎉"], ["alice", "bob"]);
+        assert_eq!(board.lanes["went-well"].items["a2"].vote_count, 1);
+
+        // Older files offer the default reactions and have none yet
+        let board = from_json(&fixture(8)).unwrap();
+        assert_eq!(board.settings.reactions, DEFAULT_REACTIONS);
+        assert!(board.lanes["went-well"].items["a2"].reactions.is_empty());
+    }
+
+    #[test]
+    fn test_load_v10_fixture() {
+        let board = from_json(&fixture(10)).unwrap();
+        let item = &board.lanes["action-items"].items["c1"];
+        let details = item.action.as_ref().unwrap();
+        assert_eq!(details.assignee.as_deref(), Some("bob"));
+        assert_eq!(details.due, NaiveDate::from_ymd_opt(2026, 3, 20));
+        assert_eq!(details.status, ActionStatus::InProgress);
+        assert_eq!(details.source.as_ref().unwrap().item_id, "b1");
+        assert!(board.lanes["went-well"].items["a1"].action.is_none());
+
+        // Items of older action items lanes become open action items
+        let mut doc: Value = serde_json::from_str(&fixture(9)).unwrap();
+        doc["lanes"]["action-items"] = serde_json::json!({
+            "title": "Action Items",
+            "theme": "action-items",
+            "items": { "c1": { "body": "Quarantine", "vote_count": 0, "sort_order": 0 } }
+        });
+        let board = from_value(doc).unwrap();
+        let details = board.lanes["action-items"].items["c1"].action.as_ref();
+        assert_eq!(details.unwrap().status, ActionStatus::Open);
+        assert!(board.lanes["to-improve"].items["b1"].action.is_none());
+    }
+
+    #[test]
+    fn test_load_v11_fixture() {
+        let board = from_json(&fixture(11)).unwrap();
+        assert_eq!(board.lanes.keys().next().unwrap(), "action-review");
+        let review = &board.lanes["action-review"];
+        assert!(review.is_action_items());
+        let details = review.items["r1"].action.as_ref().unwrap();
+        let origin = details.carried_from.as_ref().unwrap();
+        assert_eq!(origin.board_id, "sprint-41");
+        assert_eq!(origin.item_id, "x1");
+        assert_eq!(board.action_items_lane_id(), Some("action-items"));
+
+        // Older action items were not carried over
+        let board = from_json(&fixture(10)).unwrap();
+        let details = board.lanes["action-items"].items["c1"].action.as_ref();
+        assert!(details.unwrap().carried_from.is_none());
+    }
+
+    #[test]
+    fn test_load_v12_fixture() {
+        let board = from_json(&fixture(12)).unwrap();
+        assert_eq!(board.settings.team.as_deref(), Some("Platform"));
+
+        // Older boards belong to no team
+        let board = from_json(&fixture(11)).unwrap();
+        assert_eq!(board.settings.team, None);
+    }
+
+    #[test]
+    fn test_load_v13_fixture() {
+        let board = from_json(&fixture(13)).unwrap();
+        let lane = &board.lanes["to-improve"];
+        assert_eq!(
+            lane.created_at.unwrap().to_rfc3339(),
+            "2026-03-06T09:30:00+00:00"
+        );
+        let item = &lane.items["b1"];
+        assert_eq!(item.updated_at, lane.updated_at);
+        assert_eq!(item.revisions.len(), 1);
+        assert_eq!(item.revisions[0].body, "Flaky tests");
+
+        // Older items and lanes have no timestamps or revisions
+        let board = from_json(&fixture(12)).unwrap();
+        let lane = &board.lanes["to-improve"];
+        assert!(lane.created_at.is_none());
+        assert!(lane.items["b1"].created_at.is_none());
+        assert!(lane.items["b1"].revisions.is_empty());
+    }
+
+    #[test]
+    fn test_load_v14_fixture() {
+        let board = from_json(&fixture(14)).unwrap();
+        assert!(!board.settings.anonymous);
+        let lane = &board.lanes["went-well"];
+        assert_eq!(lane.items["a1"].author.as_deref(), Some("alice"));
+        assert!(lane.items["a2"].anonymous);
+
+        // Older boards stay anonymous and their items have no author
+        let board = from_json(&fixture(13)).unwrap();
+        assert!(board.settings.anonymous);
+        assert!(board.lanes["to-improve"].items["b1"].author.is_none());
+    }
+
+    #[test]
+    fn test_load_v15_fixture() {
+        let board = from_json(&fixture(15)).unwrap();
+        let item = &board.lanes["to-improve"].items["b1"];
+        assert!(item.comments["c1"].anonymous);
+        assert!(!item.comments["c2"].anonymous);
+        let item = &board.lanes["went-well"].items["a2"];
+        assert_eq!(item.anonymous_reactions["🎉"], ["alice"]);
+        assert!(board.anonymous_voters.contains("alice"));
+        assert!(board.trash["t1"].anonymous);
+
+        // Older ones count as made while the board showed participants
+        let board = from_json(&fixture(14)).unwrap();
+        assert!(!board.lanes["to-improve"].items["b1"].comments["c1"].anonymous);
+        assert!(board.lanes["went-well"].items["a2"]
+            .anonymous_reactions
+            .is_empty());
+        assert!(board.anonymous_voters.is_empty());
+        assert!(!board.trash["t1"].anonymous);
+    }
+
+    #[test]
+    fn test_to_json_writes_current_version() {
+        let board = RetroBoard::new("Board");
+        let value: Value = serde_json::from_str(&to_json(&board)).unwrap();
+        assert_eq!(value["schema_version"], CURRENT_VERSION);
+        assert_eq!(value["title"], "Board");
+
+        let loaded = from_json(&to_json(&board)).unwrap();
+        assert_eq!(loaded.title, "Board");
+    }
+
+    #[test]
+    fn test_newer_version_is_rejected() {
+        let json = format!(
+            r#"{{"schema_version": {}, "title": "Future", "lanes": {{}}}}"#,
+            CURRENT_VERSION + 1
+        );
+        assert!(matches!(
+            from_json(&json),
+            Err(SchemaError::UnsupportedVersion(_))
+        ));
+    }
+
+    #[test]
+    fn test_invalid_version_is_rejected() {
+        let json = r#"{"schema_version": "one", "title": "Board", "lanes": {}}"#;
+        assert!(matches!(
+            from_json(json),
+            Err(SchemaError::InvalidVersion(_))
+        ));
+    }
+
+    #[test]
+    fn test_non_object_is_rejected() {
+        assert!(matches!(from_json("[]"), Err(SchemaError::NotAnObject)));
+    }
+}
@@ -0,0 +1,473 @@
+//! User-defined export templates.
+//!
+//! A template is a file named `<name>.<ext>` in the templates directory,
+//! written in the Jinja syntax of `minijinja` and rendered against a
+//! `BoardView`. Templates are read on every export, so they can be edited
+//! without restarting the server. Templates ending in `.html` or `.xml` have
+//! their values escaped.
+
+use crate::boards::is_valid_id;
+use crate::export::{constituents, group_of, sorted_items, ItemOrder};
+use crate::retroboard::{ActionDetails, ActionStatus, Origin, RetroBoard};
+use crate::AppState;
+use axum::{extract::State, routing::get, Json, Router};
+use chrono::Utc;
+use indexmap::IndexMap;
+use minijinja::{Environment, UndefinedBehavior};
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// What a template is rendered against. Field names are part of the
+/// template API, so rename them with care.
+#[derive(Serialize)]
+pub struct BoardView<'a> {
+    pub id: &'a str,
+    pub title: &'a str,
+    /// RFC 3339, or none for boards stored before this was recorded
+    pub created_at: Option<String>,
+    /// RFC 3339
+    pub exported_at: String,
+    pub lanes: Vec<LaneView<'a>>,
+    /// The items of every action items lane
+    pub action_items: Vec<ItemView<'a>>,
+    pub item_count: usize,
+    pub total_votes: u64,
+}
+
+#[derive(Serialize)]
+pub struct LaneView<'a> {
+    pub id: &'a str,
+    pub title: &'a str,
+    pub theme: &'a str,
+    pub is_action_items: bool,
+    /// In the order requested with `sort`
+    pub items: Vec<ItemView<'a>>,
+    pub groups: Vec<GroupView<'a>>,
+    pub total_votes: u64,
+}
+
+#[derive(Serialize)]
+pub struct GroupView<'a> {
+    pub id: &'a str,
+    pub title: &'a str,
+    /// Votes on the group and on every item in it
+    pub votes: u64,
+    /// IDs of the items in the group, in the order of `items`
+    pub item_ids: Vec<&'a str>,
+}
+
+#[derive(Clone, Serialize)]
+pub struct ItemView<'a> {
+    pub id: &'a str,
+    pub body: &'a str,
+    pub votes: u64,
+    pub sort_order: u64,
+    /// Title of the lane the item is in
+    pub lane: &'a str,
+    /// Items merged into this one, in the order they were merged
+    pub merged: Vec<MergedView<'a>>,
+    /// Title of the item's group, if it is in one
+    pub group: Option<&'a str>,
+    /// How many comments the item has
+    pub comments: usize,
+    /// How many participants reacted with each emoji
+    pub reactions: IndexMap<&'a str, usize>,
+    /// Set for action items
+    pub action: Option<ActionView<'a>>,
+    /// RFC 3339, unknown for items from before this was recorded
+    pub created_at: Option<String>,
+    /// When the body last changed, as RFC 3339
+    pub updated_at: Option<String>,
+    /// Participant who added the item, unless the board keeps authors hidden
+    pub author: Option<&'a str>,
+}
+
+#[derive(Clone, Serialize)]
+pub struct ActionView<'a> {
+    pub assignee: Option<&'a str>,
+    /// As `YYYY-MM-DD`
+    pub due: Option<String>,
+    pub status: ActionStatus,
+    /// Whether it still needs doing: open or in progress
+    pub open: bool,
+    pub source: Option<SourceView<'a>>,
+    /// Action item of an earlier board this one was carried over from
+    pub carried_from: Option<&'a Origin>,
+}
+
+/// The card an action item came out of.
+#[derive(Clone, Serialize)]
+pub struct SourceView<'a> {
+    pub lane_id: &'a str,
+    pub item_id: &'a str,
+    /// Body of the card, unless it has been removed since
+    pub body: Option<&'a str>,
+}
+
+impl<'a> ActionView<'a> {
+    fn new(board: &'a RetroBoard, details: &'a ActionDetails) -> Self {
+        Self {
+            assignee: details.assignee.as_deref(),
+            due: details.due.map(|due| due.to_string()),
+            status: details.status,
+            open: details.status.is_open(),
+            source: details.source.as_ref().map(|source| SourceView {
+                lane_id: &source.lane_id,
+                item_id: &source.item_id,
+                body: board
+                    .lanes
+                    .get(&source.lane_id)
+                    .and_then(|lane| lane.items.get(&source.item_id))
+                    .map(|item| item.body.as_str()),
+            }),
+            carried_from: details.carried_from.as_ref(),
+        }
+    }
+}
+
+#[derive(Clone, Serialize)]
+pub struct MergedView<'a> {
+    pub id: &'a str,
+    pub body: &'a str,
+    pub votes: u64,
+}
+
+impl<'a> BoardView<'a> {
+    pub fn new(id: &'a str, board: &'a RetroBoard, order: ItemOrder) -> Self {
+        let lanes: Vec<_> = board
+            .lanes
+            .iter()
+            .map(|(lane_id, lane)| {
+                let items: Vec<_> = sorted_items(lane, order)
+                    .into_iter()
+                    .map(|(id, item)| ItemView {
+                        id,
+                        body: &item.body,
+                        votes: item.vote_count,
+                        sort_order: item.sort_order,
+                        lane: &lane.title,
+                        group: group_of(lane, item).map(|group| group.title.as_str()),
+                        comments: item.comments.len(),
+                        created_at: item.created_at.map(|at| at.to_rfc3339()),
+                        updated_at: item.updated_at.map(|at| at.to_rfc3339()),
+                        author: item.shown_author(&board.settings),
+                        action: item
+                            .action
+                            .as_ref()
+                            .map(|details| ActionView::new(board, details)),
+                        reactions: item
+                            .reactions
+                            .iter()
+                            .map(|(emoji, participants)| (emoji.as_str(), participants.len()))
+                            .collect(),
+                        merged: constituents(item)
+                            .into_iter()
+                            .map(|merged| MergedView {
+                                id: &merged.id,
+                                body: &merged.item.body,
+                                votes: merged.item.vote_count,
+                            })
+                            .collect(),
+                    })
+                    .collect();
+                let groups = lane
+                    .groups
+                    .iter()
+                    .map(|(group_id, group)| GroupView {
+                        id: group_id,
+                        title: &group.title,
+                        votes: lane.group_votes(group_id),
+                        item_ids: sorted_items(lane, order)
+                            .into_iter()
+                            .filter(|(_, item)| item.group.as_deref() == Some(group_id))
+                            .map(|(id, _)| id)
+                            .collect(),
+                    })
+                    .collect();
+                LaneView {
+                    id: lane_id,
+                    groups,
+                    title: &lane.title,
+                    theme: &lane.theme,
+                    is_action_items: lane.is_action_items(),
+                    total_votes: items.iter().map(|item| item.votes).sum(),
+                    items,
+                }
+            })
+            .collect();
+        let action_items = lanes
+            .iter()
+            .filter(|lane| lane.is_action_items)
+            .flat_map(|lane| lane.items.iter().cloned())
+            .collect();
+
+        Self {
+            id,
+            title: &board.title,
+            created_at: board.created_at.map(|created| created.to_rfc3339()),
+            exported_at: Utc::now().to_rfc3339(),
+            item_count: lanes.iter().map(|lane| lane.items.len()).sum(),
+            total_votes: lanes.iter().map(|lane| lane.total_votes).sum(),
+            action_items,
+            lanes,
+        }
+    }
+}
+
+/// The file of the template called `name`, if there is one.
+pub fn find(dir: &Path, name: &str) -> Option<PathBuf> {
+    if !is_valid_id(name) {
+        return None;
+    }
+    let mut matches: Vec<_> = std::fs::read_dir(dir)
+        .ok()?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file() && path.file_stem().is_some_and(|stem| stem == name))
+        .collect();
+    // Pick the same file every time if several extensions are present
+    matches.sort();
+    matches.into_iter().next()
+}
+
+/// Names of every template in `dir`, sorted.
+pub fn names(dir: &Path) -> Vec<String> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut names: Vec<_> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file())
+        .filter_map(|path| Some(path.file_stem()?.to_str()?.to_string()))
+        .filter(|name| is_valid_id(name))
+        .collect();
+    names.sort();
+    names.dedup();
+    names
+}
+
+/// Render the template in `path` against `view`.
+pub fn render(path: &Path, view: &BoardView) -> Result<String, String> {
+    let source = std::fs::read_to_string(path).map_err(|e| e.to_string())?;
+    let file_name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+
+    let mut env = Environment::new();
+    // Misspelled fields are errors rather than silently empty
+    env.set_undefined_behavior(UndefinedBehavior::Strict);
+    let template = env
+        .template_from_named_str(&file_name, &source)
+        .map_err(|e| format!("{:#}", e))?;
+    template.render(view).map_err(|e| format!("{:#}", e))
+}
+
+/// Content type of a rendered template, guessed from its extension.
+pub fn content_type(path: &Path) -> &'static str {
+    match path.extension().and_then(|ext| ext.to_str()) {
+        Some("html" | "htm") => "text/html; charset=utf-8",
+        Some("md" | "markdown") => "text/markdown; charset=utf-8",
+        Some("csv") => "text/csv; charset=utf-8",
+        Some("json") => "application/json",
+        Some("xml") => "application/xml",
+        _ => "text/plain; charset=utf-8",
+    }
+}
+
+pub fn routes() -> Router<Arc<AppState>> {
+    Router::new().route("/api/templates", get(list))
+}
+
+async fn list(State(state): State<Arc<AppState>>) -> Json<Vec<String>> {
+    Json(names(&state.templates_dir))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{router, test_app_state};
+    use axum::body::{to_bytes, Body};
+    use axum::http::{header::CONTENT_TYPE, Request, StatusCode};
+    use tower::ServiceExt;
+
+    fn board() -> RetroBoard {
+        let mut board = RetroBoard::from_template("went-well").unwrap();
+        board.title = "Sprint 42 Retro".to_string();
+        board.add_item("went-well", "Deploys were <smooth>");
+        board.add_item("action-items", "Fix the flaky tests");
+        let id = board.lanes["went-well"]
+            .items
+            .keys()
+            .next()
+            .unwrap()
+            .clone();
+        board.upvote_item("went-well", &id);
+        let merged = board.add_item("action-items", "Add a retry").unwrap();
+        let fix = board.lanes["action-items"]
+            .items
+            .iter()
+            .find(|(_, item)| item.body == "Fix the flaky tests")
+            .map(|(id, _)| id.clone())
+            .unwrap();
+        board.merge_items("action-items", &merged, &fix, "Fix the flaky tests");
+        board
+    }
+
+    fn write_template(dir: &Path, file_name: &str, source: &str) -> PathBuf {
+        std::fs::create_dir_all(dir).unwrap();
+        let path = dir.join(file_name);
+        std::fs::write(&path, source).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_view_model() {
+        let board = board();
+        let view = BoardView::new("sprint-42", &board, ItemOrder::Board);
+        assert_eq!(view.id, "sprint-42");
+        assert_eq!(view.lanes.len(), 3);
+        assert_eq!(view.item_count, 2);
+        assert_eq!(view.total_votes, 1);
+        assert_eq!(view.lanes[0].total_votes, 1);
+        assert!(view.lanes[2].is_action_items);
+        assert_eq!(view.action_items.len(), 1);
+        assert_eq!(view.action_items[0].body, "Fix the flaky tests");
+        assert_eq!(view.action_items[0].lane, "Action Items");
+        assert_eq!(view.action_items[0].merged.len(), 1);
+        assert_eq!(view.action_items[0].merged[0].body, "Add a retry");
+        assert!(view.lanes[0].groups.is_empty());
+        assert_eq!(view.lanes[0].items[0].group, None);
+    }
+
+    #[test]
+    fn test_group_view() {
+        let mut board = board();
+        let id = board.lanes["went-well"]
+            .items
+            .keys()
+            .next()
+            .unwrap()
+            .clone();
+        let group = board.add_group("went-well", "Shipping").unwrap();
+        board.group_item("went-well", &id, Some(&group));
+
+        let view = BoardView::new("id", &board, ItemOrder::Board);
+        let lane = &view.lanes[0];
+        assert_eq!(lane.groups.len(), 1);
+        assert_eq!(lane.groups[0].title, "Shipping");
+        assert_eq!(lane.groups[0].votes, 1);
+        assert_eq!(lane.groups[0].item_ids, [id.as_str()]);
+        assert_eq!(lane.items[0].group, Some("Shipping"));
+    }
+
+    #[test]
+    fn test_render_slack_template() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = write_template(
+            dir.path(),
+            "slack.txt",
+            "*{{ title }}*\n\
+             {% for lane in lanes if not lane.is_action_items %}\
+             _{{ lane.title }}_\n\
+             {% for item in lane.items %}• {{ item.body }} ({{ item.votes }})\n{% endfor %}\
+             {% endfor %}\
+             {% for item in action_items %}☐ {{ item.body }}\n{% endfor %}",
+        );
+        let board = board();
+        let output = render(&path, &BoardView::new("id", &board, ItemOrder::Board)).unwrap();
+        assert_eq!(
+            output,
+            "*Sprint 42 Retro*\n\
+             _Went Well_\n• Deploys were <smooth> (1)\n\
+             _To Improve_\n\
+             ☐ Fix the flaky tests\n"
+        );
+    }
+
+    #[test]
+    fn test_html_templates_are_escaped() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = write_template(
+            dir.path(),
+            "page.html",
+            "{% for item in lanes[0].items %}<li>{{ item.body }}</li>{% endfor %}",
+        );
+        let board = board();
+        let output = render(&path, &BoardView::new("id", &board, ItemOrder::Board)).unwrap();
+        assert_eq!(output, "<li>Deploys were &lt;smooth&gt;</li>");
+    }
+
+    #[test]
+    fn test_unknown_fields_are_errors() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = write_template(dir.path(), "typo.txt", "{{ titel }}");
+        let board = board();
+        let error = render(&path, &BoardView::new("id", &board, ItemOrder::Board)).unwrap_err();
+        assert!(error.contains("undefined"), "{}", error);
+    }
+
+    #[test]
+    fn test_find_and_names() {
+        let dir = tempfile::tempdir().unwrap();
+        write_template(dir.path(), "slack.txt", "");
+        write_template(dir.path(), "org.org", "");
+        write_template(dir.path(), "not a name.txt", "");
+
+        assert_eq!(names(dir.path()), ["org", "slack"]);
+        assert_eq!(
+            find(dir.path(), "slack"),
+            Some(dir.path().join("slack.txt"))
+        );
+        assert_eq!(find(dir.path(), "missing"), None);
+        assert_eq!(find(dir.path(), "../slack"), None);
+        assert!(names(&dir.path().join("missing")).is_empty());
+    }
+
+    async fn get(dir: &Path, uri: &str) -> (StatusCode, String, String) {
+        let state = test_app_state(board(), dir.join("board.json"));
+        let response = router(Arc::new(state))
+            .oneshot(Request::get(uri).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let status = response.status();
+        let content_type = response
+            .headers()
+            .get(CONTENT_TYPE)
+            .map(|value| value.to_str().unwrap().to_string())
+            .unwrap_or_default();
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        (
+            status,
+            content_type,
+            String::from_utf8(body.to_vec()).unwrap(),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_export_with_template() {
+        let dir = tempfile::tempdir().unwrap();
+        let templates = dir.path().join("templates");
+        write_template(&templates, "confluence.wiki", "h1. {{ title }} ({{ id }})");
+        write_template(&templates, "broken.txt", "{% for %}");
+
+        let (status, content_type, body) =
+            get(dir.path(), "/api/boards/default/export?template=confluence").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(content_type, "text/plain; charset=utf-8");
+        assert_eq!(body, "h1. Sprint 42 Retro (default)");
+
+        let (status, _, _) = get(dir.path(), "/api/boards/default/export?template=missing").await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+
+        let (status, _, body) = get(dir.path(), "/api/boards/default/export?template=broken").await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+        assert!(body.starts_with("Template 'broken' failed"), "{}", body);
+
+        let (status, _, body) = get(dir.path(), "/api/templates").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body, r#"["broken","confluence"]"#);
+    }
+}